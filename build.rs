@@ -4,6 +4,7 @@ fn main() {
     println!("cargo:rerun-if-changed=src/types.rs");
     println!("cargo:rerun-if-changed=src/se050.rs");
     println!("cargo:rerun-if-changed=build.rs");
+    println!("cargo:rerun-if-changed=conv.py");
 
     let conv1 = Command::new("./conv.py")
         .args(&["src/types.rs", "src/types_convs.rs"])
@@ -52,6 +52,8 @@ def emit_convs(tn, d, mask):
 	outfile.write(BITOR_IMPL % tn)
 
 tname = None
+# attributes seen since the last item, to find #[repr(u8)]
+attrs = []
 
 infile = open(sys.argv[1], "r")
 outfile = open(sys.argv[2], "w")
@@ -60,24 +62,38 @@ outfile.write("// THIS FILE IS AUTOGENERATED -- DO NOT EDIT\n")
 outfile.write("use core::ops::BitOr;\n")
 
 for ln in infile:
-	if ln.startswith("pub enum"):
-		fields = ln.split(" ")
+	sln = ln.strip()
+	if tname is None and sln.startswith("#["):
+		attrs.append(sln)
+		continue
+	# #[repr(u8)] enums may be indented, with blank and comment lines;
+	# the others have to be laid out strictly
+	if ln.startswith("pub enum") or (tname is None and sln.startswith("pub enum") and "#[repr(u8)]" in attrs):
+		fields = sln.split()
 		tname = fields[2]
 		vRev = {}
 		mask = None
+		repr_u8 = "#[repr(u8)]" in attrs
 	elif tname is not None:
 		if ln.startswith("    /* mask:"):
 			mask = ln[12:-4]
-		elif "=" in ln:
+		elif repr_u8 and (sln == "" or sln.startswith("//")):
+			pass
+		elif repr_u8 and "=" in sln:
+			fn, vn = sln.split("=")
+			vRev[vn.strip().strip(",").strip()] = fn.strip()
+		elif "=" in ln and not repr_u8:
 			fn, _, vn = ln.strip().split(" ")
 			vn = vn.strip(",")
 			vRev[vn] = fn
-		elif ln.startswith("}"):
+		elif ln.startswith("}") or (repr_u8 and sln.startswith("}")):
 			emit_convs(tname, vRev, mask)
 			tname = None
 		else:
 			outfile.write("// not emitting %s, bogus line: %s\n" % (tname, ln))
 			# suppress emitting this enum, probably not meant to be?
 			tname = None
+	if sln != "" and not sln.startswith("//"):
+		attrs = []
 
 sys.exit(0)
@@ -0,0 +1,84 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413, 4.7.3.1 ReadObject (INS_ATTEST) P.65-66
+// Parser for the response TLVs returned by attest_with_factory_key:
+//TLV[TAG_1] object data
+//TLV[TAG_2] object attributes
+//TLV[TAG_3] 12-byte timestamp
+//TLV[TAG_4] 16-byte freshness random
+//TLV[TAG_5] 18-byte chip unique ID
+//TLV[TAG_6] signature over the preceding response TLVs
+// The driver has no host-side crypto: verify() hands the signed bytes to an
+// AttestationVerifier built on whatever ECDSA/RSA implementation the host uses.
+
+pub trait AttestationVerifier {
+    // true if `signature` over `message` checks out against `publickey`
+    fn verify(&self, publickey: &[u8], message: &[u8], signature: &[u8]) -> bool;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct AttestationResult<'a> {
+    pub data: &'a [u8],
+    pub attributes: &'a [u8],
+    pub timestamp: &'a [u8],
+    pub freshness: &'a [u8],
+    pub chip_id: &'a [u8],
+    pub signature: &'a [u8],
+    // TAG_1 to TAG_5 exactly as received, the input to the signature
+    pub signed_data: &'a [u8],
+}
+
+impl<'a> AttestationResult<'a> {
+    pub fn parse(response: &'a [u8]) -> Result<Self, Se050Error> {
+        let mut tlvs: [Option<&'a [u8]>; 6] = [None; 6];
+        let mut signed_len = 0;
+
+        let rapdu = RawRApdu { data: response, sw: 0x9000 };
+        let mut iter = rapdu.tlv_iter();
+        loop {
+            let offset = response.len() - iter.remaining().len();
+            let Some(item) = iter.next() else { break };
+            let (tag, value) = item.map_err(|_| {
+                error!("SE050 attestation: malformed TLV");
+                Se050Error::UnknownError })?;
+            let idx = tag.wrapping_sub(Se050TlvTag::Tag1.into()) as usize;
+            if idx >= tlvs.len() || tlvs[idx].is_some() {
+                error!("SE050 attestation: unexpected tag {:x}", tag);
+                return Err(Se050Error::UnknownError);
+            }
+            tlvs[idx] = Some(value);
+            if idx == 5 {
+                signed_len = offset;
+                break;
+            }
+        }
+
+        let get = |i: usize| tlvs[i].ok_or_else(|| {
+            error!("SE050 attestation: TLV {} missing", i + 1);
+            Se050Error::UnknownError });
+        Ok(AttestationResult {
+            data: get(0)?,
+            attributes: get(1)?,
+            timestamp: get(2)?,
+            freshness: get(3)?,
+            chip_id: get(4)?,
+            signature: get(5)?,
+            signed_data: &response[0..signed_len],
+        })
+    }
+
+    // checks the signature and that the attestation answers our freshness random
+    pub fn verify<V: AttestationVerifier>(&self, verifier: &V, publickey: &[u8], freshness: &[u8; 16]) -> Result<(), Se050Error> {
+        if self.freshness != freshness {
+            error!("SE050 attestation: freshness mismatch");
+            return Err(Se050Error::VerificationFailed);
+        }
+        if !verifier.verify(publickey, self.signed_data, self.signature) {
+            error!("SE050 attestation: signature invalid");
+            return Err(Se050Error::VerificationFailed);
+        }
+        Ok(())
+    }
+}
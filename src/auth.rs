@@ -0,0 +1,82 @@
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413, reserved file identifiers (RESERVED_ID_*) and 4.6 Module management
+// Which commands the applet only accepts from a session authenticated with one of
+// the reserved credentials. Everything else is governed by the object policies
+// alone, so requires_auth() returning None does not mean no session is ever needed.
+// Lets tooling check a command sequence before running it.
+
+pub const RESERVED_ID_TRANSPORT: [u8; 4] = [0x7f, 0xff, 0x02, 0x00];
+pub const RESERVED_ID_FEATURE: [u8; 4] = [0x7f, 0xff, 0x02, 0x04];
+pub const RESERVED_ID_FACTORY_RESET: [u8; 4] = [0x7f, 0xff, 0x02, 0x05];
+pub const RESERVED_ID_PLATFORM_SCP: [u8; 4] = [0x7f, 0xff, 0x02, 0x07];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Se050Operation {
+    GetVersion,
+    GetRandom,
+    CreateSession,
+    WriteObject,
+    ReadObject,
+    DeleteObject,
+    Sign,
+    Verify,
+    Encrypt,
+    Decrypt,
+    Digest,
+    Attest,
+    // 4.6.1 SetLockState, split by target state: locking is open to any session,
+    // unlocking needs the transport credential
+    SetLockStateLocked,
+    SetLockStateUnlocked,
+    // 4.6.2 SetPlatformSCPRequest
+    SetPlatformScpRequest,
+    // 4.6.3 SetAppletFeatures
+    SetAppletFeatures,
+    // 4.19.5 DeleteAll
+    DeleteAll,
+}
+
+// the reserved credential the session must be opened with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AuthKind {
+    Transport,
+    Feature,
+    FactoryReset,
+    PlatformScp,
+}
+
+impl AuthKind {
+    pub fn object_id(&self) -> [u8; 4] {
+        match self {
+        AuthKind::Transport => RESERVED_ID_TRANSPORT,
+        AuthKind::Feature => RESERVED_ID_FEATURE,
+        AuthKind::FactoryReset => RESERVED_ID_FACTORY_RESET,
+        AuthKind::PlatformScp => RESERVED_ID_PLATFORM_SCP,
+        }
+    }
+}
+
+pub fn requires_auth(op: Se050Operation) -> Option<AuthKind> {
+    match op {
+    Se050Operation::SetLockStateUnlocked => Some(AuthKind::Transport),
+    Se050Operation::SetPlatformScpRequest => Some(AuthKind::PlatformScp),
+    Se050Operation::SetAppletFeatures => Some(AuthKind::Feature),
+    Se050Operation::DeleteAll => Some(AuthKind::FactoryReset),
+    _ => None,
+    }
+}
+
+// index of the first operation that would run without its credential, with
+// `session` the authentication object of the open session (None: no session)
+#[allow(dead_code)]
+pub fn check_sequence(ops: &[Se050Operation], session: Option<&[u8; 4]>) -> Result<(), usize> {
+    for (i, op) in ops.iter().enumerate() {
+        if let Some(kind) = requires_auth(*op) {
+            if session != Some(&kind.object_id()) {
+                error!("SE050 {:?} needs a {:?} session", op, kind);
+                return Err(i);
+            }
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,54 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413, 4.7.3.2 ExportObject P.67 and 4.7.1.8 ImportObject P.63-64
+// Backup of Secure Objects: ExportObject serializes an object encrypted under a
+// device-bound key, so the blob itself only restores on the SE050 it came from
+// and its contents cannot be parsed on the host. export_object therefore
+// records the object identifier and SecureObjectType (read from the object
+// attributes) next to the blob, which is all restore_object needs to put it back
+// into the original slot.
+
+pub struct ExportedObject<'a> {
+    pub id: ObjectId,
+    pub object_type: Se050ApduSecObjType,
+    // component an RSA key pair was exported for, see ExportObject TLV[TAG_2]
+    pub rsa_component: Option<Se050RSAKeyComponent>,
+    // serialized, encrypted object
+    pub blob: &'a [u8],
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    #[inline(never)]
+    pub fn export_object<'a>(&mut self, objectidentifier: &[u8;4], rsakeycomponent: Option<Se050RSAKeyComponent>, out: &'a mut [u8], delay: &mut DelayWrapper) -> Result<ExportedObject<'a>, Se050Error> {
+        self.require_enabled()?;
+        let mut attributes: [u8; 256] = [0; 256];
+        let len = self.read_object_attributes(objectidentifier, &mut attributes, delay)?;
+        if len <= OBJECT_ATTRIBUTES_CLASS_OFFSET {
+            error!("SE050 export_object: attributes too short");
+            return Err(Se050Error::UnknownError);
+        }
+        let object_type = Se050ApduSecObjType::try_from(attributes[OBJECT_ATTRIBUTES_CLASS_OFFSET]).map_err(|_| {
+            error!("SE050 export_object: unknown object type {:x}", attributes[OBJECT_ATTRIBUTES_CLASS_OFFSET]);
+            Se050Error::UnknownError })?;
+
+        let len = self.export_object_into(objectidentifier, rsakeycomponent, out, delay)?;
+
+        debug!("SE050 export_object OK");
+        Ok(ExportedObject { id: ObjectId(*objectidentifier), object_type, rsa_component: rsakeycomponent, blob: &out[0..len] })
+    }
+
+    // imports `exported` back under the identifier it was exported from
+    #[inline(never)]
+    pub fn restore_object(&mut self, exported: &ExportedObject, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        self.import_object(&exported.id.0, exported.rsa_component, exported.blob, delay)?;
+
+        debug!("SE050 restore_object OK");
+        Ok(())
+    }
+}
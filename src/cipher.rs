@@ -0,0 +1,240 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413 //4.12 Crypto operations AES/DES //4.12.4 CipherOneShot P.87
+// AES-CTR over data of any length, fed in pieces: every piece is sent as one or
+// more CipherOneShot commands and the context carries the counter block from one
+// command to the next, so the output matches encrypting the whole stream at once.
+// The counter is the full 16-byte block, incremented big-endian once per AES block.
+// CTR is symmetric, so the same context decrypts.
+// Only the last piece may end on a partial block; update() fails after that.
+//
+// AES-CBC with a fresh IV per message: aes_cbc_encrypt takes the IV from GetRandom
+// and returns it with the ciphertext; the caller stores it next to the ciphertext
+// for aes_cbc_decrypt. The mode is AES_CBC_NOPAD, so the data must be whole blocks.
+// Longer data is split over several CipherOneShot commands, each chained to the
+// last ciphertext block of the previous one.
+//
+// encrypt_stream takes the input as an iterator of slices of any length and
+// re-blocks it into CipherOneShot commands of CHUNK_LEN bytes, chaining the IV
+// between them as above (the counter for CTR, the last ciphertext block for CBC);
+// the output of every command goes to the sink. Only the unpadded modes can be
+// chained this way, and all but CTR need a whole number of blocks in total.
+
+pub const AES_BLOCK_LEN: usize = 16;
+
+// input per CipherOneShot: the command (header, TAG_1, TAG_2, TAG_3 and TAG_4
+// headers, the IV, Le) has to fit in a single T=1 frame, rounded down to whole blocks
+const CHUNK_LEN: usize = (MAX_IFSC - 5 - 6 - 3 - 3 - 2 - AES_BLOCK_LEN - 1) / AES_BLOCK_LEN * AES_BLOCK_LEN;
+
+pub struct AesCtrContext {
+    keyobjectidentifier: [u8; 4],
+    counter: [u8; AES_BLOCK_LEN],
+    finished: bool,
+}
+
+impl AesCtrContext {
+    pub fn new(keyobjectidentifier: [u8; 4], initial_counter: [u8; AES_BLOCK_LEN]) -> Self {
+        Self { keyobjectidentifier, counter: initial_counter, finished: false }
+    }
+
+    // counter block for the next AES block of the stream
+    pub fn counter(&self) -> &[u8; AES_BLOCK_LEN] {
+        &self.counter
+    }
+
+    // Encrypts (or decrypts) `input` into `output`, which must be at least as long.
+    // Returns the number of bytes written.
+    #[inline(never)]
+    pub fn update<T: T1Proto>(&mut self, se050: &mut Se050<T>, input: &[u8], output: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        if self.finished {
+            error!("SE050 AES-CTR update after a partial block");
+            return Err(Se050Error::UnknownError);
+        }
+        if output.len() < input.len() {
+            error!("SE050 AES-CTR Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+
+        for (i, chunk) in input.chunks(CHUNK_LEN).enumerate() {
+            let out = &mut output[i * CHUNK_LEN..];
+            let len = se050.cipher_one_shot_encrypt_into(&self.keyobjectidentifier, Se050CipherModeconstants::AesCtr, chunk, &self.counter, out, delay)?;
+            if len != chunk.len() {
+                error!("SE050 AES-CTR Length Mismatch");
+                return Err(Se050Error::UnknownError);
+            }
+            counter_add(&mut self.counter, chunk.len().div_ceil(AES_BLOCK_LEN) as u32);
+            self.finished = chunk.len() % AES_BLOCK_LEN != 0;
+        }
+
+        Ok(input.len())
+    }
+}
+
+fn check_cbc_len(len: usize) -> Result<(), Se050Error> {
+    if len == 0 || !len.is_multiple_of(AES_BLOCK_LEN) {
+        error!("SE050 AES-CBC: {} bytes is not a non-zero multiple of the block size", len);
+        return Err(Se050Error::UnknownError);
+    }
+    Ok(())
+}
+
+// cipher block size for the modes encrypt_stream can chain, None for padding modes
+fn stream_block_len(mode: Se050CipherModeconstants) -> Option<usize> {
+    match mode {
+    Se050CipherModeconstants::DesEcbNopad
+    | Se050CipherModeconstants::DesCbcNopad => Some(8),
+    Se050CipherModeconstants::AesEcbNopad
+    | Se050CipherModeconstants::AesCbcNopad
+    | Se050CipherModeconstants::AesCtr => Some(AES_BLOCK_LEN),
+    _ => None,
+    }
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    //###########################################################################
+    // Encrypts `plaintext` under the AES key `keyobjectidentifier` with a random IV.
+    // Returns the IV and the length of the ciphertext written to `out`.
+
+    #[inline(never)]
+    pub fn aes_cbc_encrypt(&mut self, keyobjectidentifier: &[u8;4], plaintext: &[u8], out: &mut [u8], delay: &mut DelayWrapper) -> Result<([u8; AES_BLOCK_LEN], usize), Se050Error> {
+        self.require_enabled()?;
+        check_cbc_len(plaintext.len())?;
+        if out.len() < plaintext.len() {
+            error!("SE050 aes_cbc_encrypt Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mut iv = [0u8; AES_BLOCK_LEN];
+        self.get_random(&mut iv, delay)?;
+
+        let mut chain = iv;
+        for (i, chunk) in plaintext.chunks(CHUNK_LEN).enumerate() {
+            let off = i * CHUNK_LEN;
+            let len = self.cipher_one_shot_encrypt_into(keyobjectidentifier, Se050CipherModeconstants::AesCbcNopad, chunk, &chain, &mut out[off..], delay)?;
+            if len != chunk.len() {
+                error!("SE050 aes_cbc_encrypt Length Mismatch");
+                return Err(Se050Error::MissingResponseTlv);
+            }
+            chain.copy_from_slice(&out[off + len - AES_BLOCK_LEN..off + len]);
+        }
+
+        debug!("SE050 aes_cbc_encrypt OK");
+        Ok((iv, plaintext.len()))
+    }
+
+    //###########################################################################
+    // Decrypts `ciphertext` produced by aes_cbc_encrypt with its `iv`.
+    // Returns the length of the plaintext written to `out`.
+
+    #[inline(never)]
+    pub fn aes_cbc_decrypt(&mut self, keyobjectidentifier: &[u8;4], iv: &[u8; AES_BLOCK_LEN], ciphertext: &[u8], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        check_cbc_len(ciphertext.len())?;
+        if out.len() < ciphertext.len() {
+            error!("SE050 aes_cbc_decrypt Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mut chain = *iv;
+        for (i, chunk) in ciphertext.chunks(CHUNK_LEN).enumerate() {
+            let len = self.cipher_one_shot_decrypt_into(keyobjectidentifier, Se050CipherModeconstants::AesCbcNopad, chunk, &chain, &mut out[i * CHUNK_LEN..], delay)?;
+            if len != chunk.len() {
+                error!("SE050 aes_cbc_decrypt Length Mismatch");
+                return Err(Se050Error::MissingResponseTlv);
+            }
+            chain.copy_from_slice(&chunk[chunk.len() - AES_BLOCK_LEN..]);
+        }
+
+        debug!("SE050 aes_cbc_decrypt OK");
+        Ok(ciphertext.len())
+    }
+
+    //###########################################################################
+    // Encrypts the concatenation of the slices from `input` under `keyobjectidentifier`
+    // with `ciphermode`, starting from `iv` (empty for a zero IV, see check_cipher_iv).
+    // The ciphertext is passed to `out_sink` one command at a time, in order.
+    // Returns the total ciphertext length. On error, the sink may already have
+    // received the output of the commands sent before the failure.
+
+    #[inline(never)]
+    pub fn encrypt_stream<'i, I, F>(&mut self, keyobjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants, iv: &[u8], input: I, mut out_sink: F, delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    where
+        I: Iterator<Item = &'i [u8]>,
+        F: FnMut(&[u8]),
+    {
+        self.require_enabled()?;
+        let block_len = stream_block_len(ciphermode).ok_or_else(|| {
+            error!("SE050 encrypt_stream: {:?} cannot be chained", ciphermode);
+            Se050Error::UnknownError })?;
+        let iv_len = ciphermode.iv_len();
+        check_cipher_iv(ciphermode, iv)?;
+
+        let mut chain = [0u8; AES_BLOCK_LEN];
+        chain[0..iv.len()].copy_from_slice(iv);
+
+        let mut pending = [0u8; CHUNK_LEN];
+        let mut filled = 0;
+        let mut total = 0;
+        for mut piece in input {
+            while !piece.is_empty() {
+                let n = piece.len().min(CHUNK_LEN - filled);
+                pending[filled..filled + n].copy_from_slice(&piece[0..n]);
+                filled += n;
+                piece = &piece[n..];
+                if filled == CHUNK_LEN {
+                    total += self.encrypt_stream_chunk(keyobjectidentifier, ciphermode, &pending, &mut chain[0..iv_len], &mut out_sink, delay)?;
+                    filled = 0;
+                }
+            }
+        }
+
+        if !filled.is_multiple_of(block_len) && ciphermode != Se050CipherModeconstants::AesCtr {
+            error!("SE050 encrypt_stream: {} bytes left over, not a multiple of the block size", filled);
+            return Err(Se050Error::UnknownError);
+        }
+        if filled != 0 {
+            total += self.encrypt_stream_chunk(keyobjectidentifier, ciphermode, &pending[0..filled], &mut chain[0..iv_len], &mut out_sink, delay)?;
+        }
+
+        debug!("SE050 encrypt_stream OK");
+        Ok(total)
+    }
+
+    // one CipherOneShot of encrypt_stream; moves `chain` on to the IV of the next chunk
+    fn encrypt_stream_chunk<F: FnMut(&[u8])>(&mut self, keyobjectidentifier: &[u8;4], mode: Se050CipherModeconstants, chunk: &[u8], chain: &mut [u8], out_sink: &mut F, delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let mut out = [0u8; CHUNK_LEN];
+        let len = self.cipher_one_shot_encrypt_into(keyobjectidentifier, mode, chunk, chain, &mut out, delay)?;
+        if len != chunk.len() {
+            error!("SE050 encrypt_stream Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+
+        if mode == Se050CipherModeconstants::AesCtr {
+            let counter: &mut [u8; AES_BLOCK_LEN] = chain.try_into().map_err(|_| Se050Error::UnknownError)?;
+            counter_add(counter, len.div_ceil(AES_BLOCK_LEN) as u32);
+        } else if !chain.is_empty() {
+            chain.copy_from_slice(&out[len - chain.len()..len]);
+        }
+
+        out_sink(&out[0..len]);
+        Ok(len)
+    }
+}
+
+// big-endian addition on the whole counter block, wrapping at 2^128
+fn counter_add(counter: &mut [u8; AES_BLOCK_LEN], blocks: u32) {
+    let mut carry = blocks as u64;
+    for b in counter.iter_mut().rev() {
+        if carry == 0 {
+            break;
+        }
+        let sum = *b as u64 + (carry & 0xff);
+        *b = sum as u8;
+        carry = (carry >> 8) + (sum >> 8);
+    }
+}
@@ -0,0 +1,39 @@
+use crate::types::*;
+use crate::se050::*;
+use crate::keywrap::SymmKeyType;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413,// 4.15 MIFARE DESFire support P.92-100
+// Storage and diversification of MIFARE DESFire EV2 keys, for readers that keep
+// their master keys in the SE050. The applet limits DESFire support to AES-128
+// keys, stored as ordinary AES key objects; their policy decides whether they may
+// be used for DESFire authentication (PolicyObjAllowDesfireAuthentication) or
+// release session keys (PolicyObjAllowDesfireDumpSessionKeys).
+// All of this needs CONFIG_MIFARE, see Capabilities::mifare.
+
+pub const DESFIRE_KEY_LEN: usize = 16;
+// DFDiversifyKey takes up to 31 bytes of divInput (AN10922)
+pub const DESFIRE_MAX_DIV_INPUT_LEN: usize = 31;
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    //###########################################################################
+    //AN12413 //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60-61
+    // Writes an AES-128 DESFire key; with `kek` the value is RFC3394 wrapped.
+    // As write_symm_key_with_config, after checking the applet has CONFIG_MIFARE.
+
+    #[inline(never)]
+    pub fn write_desfire_key(&mut self, objectidentifier: &[u8;4], kek: Option<&[u8;4]>, key: &[u8], config: &KeyConfig, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        self.require_enabled()?;
+        self.require_mifare()?;
+        // a wrapped key carries the 8 bytes of RFC3394 overhead
+        let len = if kek.is_some() { DESFIRE_KEY_LEN + crate::keywrap::KEY_WRAP_OVERHEAD } else { DESFIRE_KEY_LEN };
+        if key.len() != len {
+            error!("SE050 write_desfire_key: {} bytes, expected {}", key.len(), len);
+            return Err(Se050Error::UnknownError);
+        }
+        self.write_symm_key_with_config(objectidentifier, SymmKeyType::Aes, kek, key, config, delay)
+    }
+}
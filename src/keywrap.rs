@@ -0,0 +1,108 @@
+use crate::types::*;
+use crate::se050::*;
+use byteorder::{ByteOrder, BE};
+
+//////////////////////////////////////////////////////////////////////////////
+// RFC3394 AES key wrap with a key encryption key (KEK) held in the SE050.
+// unwrap_key imports a wrapped AES/DES/HMAC key straight into a Secure Object:
+// WriteSymmKey with a KEK identifier makes the applet unwrap the value, so the
+// plaintext key never crosses the I2C bus. import_wrapped_aes_key does the same
+// for AES keys and checks the wrapped length first.
+// The applet never releases symmetric key material, so a stored key cannot be
+// wrapped out again. wrap_key instead wraps key material the host is about to
+// provision, running every AES block operation in the SE under the KEK, and
+// produces the blob unwrap_key (on this or another device with the same KEK) accepts.
+
+// initial value, RFC3394 2.2.3.1
+pub const KEY_WRAP_IV: [u8; 8] = [0xa6; 8];
+pub const KEY_WRAP_OVERHEAD: usize = 8;
+
+// key object types WriteSymmKey accepts (AN12413, Table 22. P1Cred constants)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SymmKeyType {
+    Aes,
+    Des,
+    Hmac,
+}
+
+impl SymmKeyType {
+    pub(crate) fn p1(&self) -> Se050ApduP1CredType {
+        match self {
+        SymmKeyType::Aes => Se050ApduP1CredType::AES,
+        SymmKeyType::Des => Se050ApduP1CredType::DES,
+        SymmKeyType::Hmac => Se050ApduP1CredType::HMAC,
+        }
+    }
+}
+
+// RFC3394 works on 64-bit blocks and needs at least two of them
+pub(crate) fn check_key_wrap_len(len: usize) -> Result<(), Se050Error> {
+    if len < 16 || !len.is_multiple_of(8) {
+        error!("SE050 key wrap: {} bytes is not a multiple of 8 of at least 16", len);
+        return Err(Se050Error::UnknownError);
+    }
+    Ok(())
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    //###########################################################################
+    // RFC3394 2.2.1 key wrap, with W(K, A | R[i]) computed by CipherOneShot AES_ECB_NOPAD
+    // under `kek` (see AN12413, 4.12.4 CipherOneShot P.87).
+    // Writes the wrapped key (key.len() + 8 bytes) to `out` and returns its length.
+
+    #[inline(never)]
+    pub fn wrap_key(&mut self, kek: ObjectId, key: &[u8], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        check_key_wrap_len(key.len())?;
+        let wrapped_len = key.len() + KEY_WRAP_OVERHEAD;
+        if out.len() < wrapped_len {
+            error!("SE050 wrap_key Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+
+        // out = A | R[1] | ... | R[n]
+        out[0..8].copy_from_slice(&KEY_WRAP_IV);
+        out[8..wrapped_len].copy_from_slice(key);
+        let n = key.len() / 8;
+
+        let mut block = [0u8; 16];
+        let mut enc = [0u8; 16];
+        for j in 0..6 {
+            for i in 1..=n {
+                block[0..8].copy_from_slice(&out[0..8]);
+                block[8..16].copy_from_slice(&out[8 * i..8 * i + 8]);
+                let len = self.cipher_one_shot_encrypt_into(&kek.0, Se050CipherModeconstants::AesEcbNopad, &block, &[], &mut enc, delay)?;
+                if len != 16 {
+                    error!("SE050 wrap_key Length Mismatch");
+                    return Err(Se050Error::UnknownError);
+                }
+                let t = (n * j + i) as u64;
+                BE::write_u64(&mut out[0..8], BE::read_u64(&enc[0..8]) ^ t);
+                out[8 * i..8 * i + 8].copy_from_slice(&enc[8..16]);
+            }
+        }
+
+        debug!("SE050 wrap_key OK");
+        Ok(wrapped_len)
+    }
+
+    //###########################################################################
+    // Stores an RFC3394-wrapped AES-128/192/256 key (24, 32 or 40 bytes) in `obj`,
+    // unwrapped by the applet with `kek`, see unwrap_key.
+
+    #[inline(never)]
+    pub fn import_wrapped_aes_key(&mut self, obj: ObjectId, kek: ObjectId, wrapped: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        if !matches!(wrapped.len().saturating_sub(KEY_WRAP_OVERHEAD), 16 | 24 | 32) {
+            error!("SE050 import_wrapped_aes_key: {} bytes is not a wrapped AES key", wrapped.len());
+            return Err(Se050Error::UnknownError);
+        }
+
+        self.unwrap_key(kek, obj, SymmKeyType::Aes, wrapped, delay)?;
+        debug!("SE050 import_wrapped_aes_key OK");
+        Ok(())
+    }
+}
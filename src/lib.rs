@@ -3,13 +3,39 @@
 extern crate delog;
 delog::generate_macros!();
 
+mod attestation;
+mod auth;
+mod backup;
+mod cipher;
+mod desfire;
+mod keywrap;
+mod mac;
+mod pin;
+mod provision;
 mod se050;
+mod selftest;
+mod session;
+mod signature;
 mod t1;
+mod transient;
 mod types;
 
-pub use crate::se050::{Se050, Se050Device};
-pub use types::{ObjectId, DelayWrapper};
-pub use t1::T1overI2C;
+pub use crate::se050::{AppletFeatures, Capabilities, FACTORY_ATTESTATION_KEY_EC, FACTORY_ATTESTATION_KEY_RSA, KeyConfig, LifecycleState, MAX_OBJECT_ID_PROBES, MAX_RANDOM_LEN, MemoryInfo, Se050, Se050AppInfo, Se050ApduMemoryType, Se050ApduSecObjType, Se050AttestationAlgo, Se050CipherModeconstants, Se050CryptoContextconstants, Se050CryptoObjectSubtype, DesKeyType, Se050Device, Se050ECCurveconstants, Se050ECCurveParamconstants, Se050ECDAASignatureAlgo, Se050ECSignatureAlgo, Se050EDSignatureAlgo, Se050Error, Se050LockIndicatorconstants, Se050LockStateconstants, Se050DigestModeconstants, Se050MACAlgoconstants, Se050Objectpolicies, Se050RSAEncryptionAlgo, Se050RSAKeyComponent, Se050RSASignatureAlgo, RSA_KEY_SIZE_MAX, RSA_KEY_SIZE_MIN, RSA_KEY_SIZE_MIN_FIPS, SE050_APPLET_AID, SelectStatus, SessionPolicy, SessionState, StrictFailures, SW_SELECTED_FILE_DEACTIVATED, UserIdObject};
+pub use crate::attestation::{AttestationResult, AttestationVerifier};
+pub use crate::auth::{AuthKind, RESERVED_ID_FACTORY_RESET, RESERVED_ID_FEATURE, RESERVED_ID_PLATFORM_SCP, RESERVED_ID_TRANSPORT, Se050Operation, requires_auth};
+pub use crate::backup::ExportedObject;
+pub use crate::cipher::{AES_BLOCK_LEN, AesCtrContext};
+pub use crate::desfire::{DESFIRE_KEY_LEN, DESFIRE_MAX_DIV_INPUT_LEN};
+pub use crate::keywrap::{KEY_WRAP_IV, KEY_WRAP_OVERHEAD, SymmKeyType};
+pub use crate::mac::MacContext;
+pub use crate::pin::{PinResult, SW_AUTHENTICATION_BLOCKED};
+pub use crate::provision::{MAX_PROVISION_STEPS, ProvisionError, ProvisionStep};
+pub use crate::selftest::{BENCH_AES_LEN, BenchReport, SelfTestReport};
+pub use crate::session::{SESSION_SCRATCH_LEN, Se050Session};
+pub use crate::signature::{MAX_DER_SIGNATURE_LEN, SignatureFormat, der_to_raw, raw_to_der};
+pub use crate::transient::{EPHEMERAL_OBJECT_ID_START, TransientObject};
+pub use types::{AnswerToReset, CApdu, Clock, I2cErrorKind, NestedTlv, ObjectId, RApdu, RawCApdu, SessionId, DelayWrapper, RawRApdu, RawTlvIterator, T1Error, T1ErrorDetection, T1Proto};
+pub use t1::{FrameRecorder, SharedI2c, T1overI2C};
 
 #[cfg(test)]
 mod tests;
@@ -0,0 +1,65 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413 //4.13 Message Authentication Codes P.88-90
+// HMAC or CMAC over data that arrives in pieces, e.g. a firmware image read from
+// flash: init() creates a crypto object (CC_SIGNATURE with the MACAlgo as subtype)
+// and runs MACInit, update() sends MACUpdate commands, finish() returns the MAC
+// from MACFinal and deletes the crypto object again. The result equals
+// mac_one_shot_into over the concatenated pieces.
+// The input always comes from the host: no MAC, cipher or digest command of
+// AN12413 takes its input data from a stored Secure Object (only key material is
+// referenced by identifier), so a binary file has to be read out and fed to update().
+
+// MACUpdate data per command: header, TAG_1 header (4 bytes above 127 bytes)
+// and TAG_2 have to fit in a single T=1 frame
+const MAC_CHUNK_LEN: usize = MAX_IFSC - 5 - 4 - 4;
+
+pub struct MacContext {
+    cryptoobjectidentifier: [u8; 2],
+}
+
+impl MacContext {
+    // `cryptoobjectidentifier` must not be in use; it is freed by finish() (or abort())
+    #[inline(never)]
+    pub fn init<T: T1Proto>(se050: &mut Se050<T>, keyobjectidentifier: &[u8; 4], macalgo: Se050MACAlgoconstants, cryptoobjectidentifier: [u8; 2], delay: &mut DelayWrapper) -> Result<Self, Se050Error> {
+        se050.create_crypto_object(&cryptoobjectidentifier, Se050CryptoObjectSubtype::Signature(macalgo), delay)?;
+
+        let ctx = Self { cryptoobjectidentifier };
+        if let Err(e) = se050.mac_init(keyobjectidentifier, &ctx.cryptoobjectidentifier, delay) {
+            ctx.abort(se050, delay).ok();
+            return Err(e);
+        }
+        Ok(ctx)
+    }
+
+    pub fn cryptoobjectidentifier(&self) -> &[u8; 2] {
+        &self.cryptoobjectidentifier
+    }
+
+    #[inline(never)]
+    pub fn update<T: T1Proto>(&mut self, se050: &mut Se050<T>, data: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        for chunk in data.chunks(MAC_CHUNK_LEN) {
+            se050.mac_update(chunk, &self.cryptoobjectidentifier, delay)?;
+        }
+        Ok(())
+    }
+
+    // Writes the MAC to `mac` and returns its length. The crypto object is
+    // deleted whatever the outcome of MACFinal.
+    #[inline(never)]
+    pub fn finish<T: T1Proto>(self, se050: &mut Se050<T>, mac: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let r = se050.mac_final_into(&[], &self.cryptoobjectidentifier, mac, delay);
+        let d = self.abort(se050, delay);
+        let len = r?;
+        d?;
+        debug!("SE050 MacContext finish OK");
+        Ok(len)
+    }
+
+    // drops the computation and frees the crypto object
+    pub fn abort<T: T1Proto>(self, se050: &mut Se050<T>, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        se050.delete_crypto_object(&self.cryptoobjectidentifier, delay)
+    }
+}
@@ -0,0 +1,60 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413, 4.5 Session management //4.5.2.1 VerifySessionUserID P.51-52
+// PIN check against a UserID object: the PIN is verified in a session opened
+// for the object, which is closed again right after, so the check leaves no
+// authenticated session behind. Failures the applet reports through the status
+// word are returned as a PinResult rather than an error.
+
+// SW_AUTH_METHOD_BLOCKED, ISO 7816-4 "authentication method blocked"
+pub const SW_AUTHENTICATION_BLOCKED: u16 = 0x6983;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PinResult {
+    Ok,
+    // 0x63Cx with x > 0
+    WrongPin { attempts_left: u8 },
+    // 0x6983, or a wrong PIN that used up the last attempt (0x63C0)
+    Locked,
+}
+
+impl PinResult {
+    // None for status words that are not a verification outcome
+    pub fn from_sw(sw: u16) -> Option<Self> {
+        match sw {
+        0x9000 => Some(PinResult::Ok),
+        SW_AUTHENTICATION_BLOCKED => Some(PinResult::Locked),
+        _ => match sw_attempts_remaining(sw)? {
+            0 => Some(PinResult::Locked),
+            attempts_left => Some(PinResult::WrongPin { attempts_left }),
+            },
+        }
+    }
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    #[inline(never)]
+    pub fn verify_pin(&mut self, objectidentifier: &[u8;4], pin: &[u8], delay: &mut DelayWrapper) -> Result<PinResult, Se050Error> {
+        self.require_enabled()?;
+        // UserID values are 4 to 16 bytes (AN12413, 4.7.1.5 WriteUserID)
+        if pin.len() < 4 || pin.len() > 16 {
+            error!("SE050 verify_pin: PIN of {} bytes", pin.len());
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mut session = self.open_session(objectidentifier, delay)?;
+        let sw = session.send_verify_user_id(pin)?;
+        session.close()?;
+
+        let result = PinResult::from_sw(sw).ok_or_else(|| {
+            error!("SE050 verify_pin Failed: {:x}", sw);
+            Se050Error::UnknownError })?;
+        debug!("SE050 verify_pin: {:?}", result);
+        Ok(result)
+    }
+}
@@ -0,0 +1,101 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+// Batch provisioning: runs a list of object writes in order and, on failure,
+// reports the failing step. With rollback enabled, the objects created by the
+// earlier steps are deleted again; objects that already existed are left alone.
+
+pub const MAX_PROVISION_STEPS: usize = 16;
+
+pub enum ProvisionStep<'a> {
+    // generate a key pair on the given curve, see write_ec_key
+    EcKey { objectid: [u8; 4], curve: Se050ECCurveconstants },
+    // create (if absent) and write a binary file, see write_binary_object
+    Binary { objectid: [u8; 4], data: &'a [u8], policy: Option<&'a [u8]> },
+    // see write_user_id
+    UserId { objectid: [u8; 4], policy: &'a [u8], value: [u8; 16], max_attempts: Option<u16> },
+}
+
+impl<'a> ProvisionStep<'a> {
+    pub fn objectid(&self) -> &[u8; 4] {
+        match self {
+        ProvisionStep::EcKey { objectid, .. } => objectid,
+        ProvisionStep::Binary { objectid, .. } => objectid,
+        ProvisionStep::UserId { objectid, .. } => objectid,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct ProvisionError {
+    // index of the failing step in `steps`, 0 with TooManySteps
+    pub step: usize,
+    pub error: Se050Error,
+    // all objects created before the failure were deleted
+    pub rolled_back: bool,
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    #[inline(never)]
+    pub fn provision(&mut self, steps: &[ProvisionStep], rollback: bool, delay: &mut DelayWrapper) -> Result<(), ProvisionError> {
+        if steps.len() > MAX_PROVISION_STEPS {
+            error!("SE050 provision: too many steps ({})", steps.len());
+            return Err(ProvisionError { step: 0, error: Se050Error::TooManySteps, rolled_back: false });
+        }
+
+        let mut created: heapless::Vec<[u8; 4], MAX_PROVISION_STEPS> = heapless::Vec::new();
+        for (i, step) in steps.iter().enumerate() {
+            let r = self.provision_step(step, &mut created, delay);
+            if let Err(error) = r {
+                error!("SE050 provision: step {} failed", i);
+                let mut rolled_back = false;
+                if rollback {
+                    rolled_back = true;
+                    for objectid in created.iter().rev() {
+                        if self.delete_secure_object(objectid, delay).is_err() {
+                            error!("SE050 provision: rollback of {:?} failed", objectid);
+                            rolled_back = false;
+                        }
+                    }
+                }
+                return Err(ProvisionError { step: i, error, rolled_back });
+            }
+        }
+
+        debug!("SE050 provision OK");
+        Ok(())
+    }
+
+    fn provision_step(&mut self, step: &ProvisionStep, created: &mut heapless::Vec<[u8; 4], MAX_PROVISION_STEPS>, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        let mut exists: [u8; 1] = [0];
+        self.check_object_exists(&mut exists, step.objectid(), delay)?;
+        let is_new = exists[0] != Se050Resultconstants::ResultSuccess as u8;
+
+        let r = match step {
+        ProvisionStep::EcKey { objectid, curve } => self.write_ec_key(objectid, *curve, delay),
+        ProvisionStep::Binary { objectid, data, policy } => self.write_binary_object(objectid, data, *policy, delay),
+        ProvisionStep::UserId { objectid, policy, value, max_attempts } => self.write_user_id(policy, objectid, value, *max_attempts, delay).map(|_| ()),
+        };
+
+        // a write that fails after creating the object, e.g. on a later chunk of a
+        // binary file, leaves it half written: it is rolled back like a complete one
+        if is_new && (r.is_ok() || self.provision_step_created(step.objectid(), delay)) {
+            created.push(*step.objectid()).map_err(|_| Se050Error::UnknownError)?;
+        }
+        r
+    }
+
+    // whether the failed step left its object behind; if that cannot be told it is
+    // assumed to, the rollback then reports a failed delete instead of missing it
+    fn provision_step_created(&mut self, objectid: &[u8; 4], delay: &mut DelayWrapper) -> bool {
+        let mut exists: [u8; 1] = [0];
+        match self.check_object_exists(&mut exists, objectid, delay) {
+        Ok(()) => exists[0] == Se050Resultconstants::ResultSuccess as u8,
+        Err(_) => true,
+        }
+    }
+}
@@ -1,11 +1,55 @@
  use crate::types::*;
-use core::{convert::{From, TryFrom}};
-use byteorder::{ByteOrder, BE};
+use crate::attestation::AttestationResult;
+use crate::desfire::DESFIRE_MAX_DIV_INPUT_LEN;
+use crate::keywrap::{check_key_wrap_len, KEY_WRAP_OVERHEAD, SymmKeyType};
+use crate::session::Se050Session;
+use core::{convert::{From, TryFrom, TryInto}};
+use byteorder::{ByteOrder, BE, LE};
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum Se050Error {
     UnknownError,
     T1Error(T1Error),
+    NotAuthenticated,
+    PolicyViolation,
+    // wrong UserID/PIN, with the number of attempts left (SW 0x63Cx)
+    AuthenticationFailed(u8),
+    SecureBoxUnavailable,
+    // DES key or cipher mode on an applet without CONFIG_DES (e.g. FIPS SKUs)
+    DesUnavailable,
+    // signature check failed, e.g. on an attestation
+    VerificationFailed,
+    // command issued before enable() reset the T=1 link
+    NotInitialized,
+    // algorithm blocked while the applet runs in FIPS mode, see is_fips_mode
+    FipsRestricted,
+    // MIFARE DESFire command on an applet without CONFIG_MIFARE
+    MifareUnavailable,
+    // transient object requested, but the SE has no transient memory left to hold it
+    TransientUnavailable,
+    // response lacks the TLV its command documents, or the TLV has an implausible length
+    MissingResponseTlv,
+    // timing requested, but the DelayWrapper has no Clock
+    ClockUnavailable,
+    // strict mode: the device configuration fails the listed hardening checks
+    PolicyNotMet(StrictFailures),
+    // RSA key on an applet without CONFIG_RSA_PLAIN and CONFIG_RSA_CRT
+    RsaUnavailable,
+    // RSA key size outside the range of rsa_key_size_range
+    RsaKeySizeUnsupported,
+    // AN12413 defines no command for the operation
+    Unsupported,
+    // provision() given more than MAX_PROVISION_STEPS steps, none of them run
+    TooManySteps,
+}
+
+// Authentication state of the current session, as tracked by the driver
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionState {
+    None,
+    UserId,
+    Scp03,
+    EcKey,
 }
 
 
@@ -24,6 +68,26 @@ pub enum Se050ApduError {
     SwCommandNotAllowed = 0x6986 , 
     
     }
+
+// ISO 7816-4: not enough memory space, answered to a transient object that does not fit
+pub(crate) const SW_FILE_FULL: u16 = 0x6a84;
+
+// ISO 7816-4 warning 0x63Cx: verification failed, x attempts remaining
+pub(crate) fn sw_attempts_remaining(sw: u16) -> Option<u8> {
+    if (sw & 0xfff0) == 0x63c0 {
+        Some((sw & 0x000f) as u8)
+    } else {
+        None
+    }
+}
+
+// maps a failed verification SW to AuthenticationFailed if it carries a counter
+pub(crate) fn verification_error(sw: u16) -> Se050Error {
+    match sw_attempts_remaining(sw) {
+        Some(attempts) => Se050Error::AuthenticationFailed(attempts),
+        None => Se050Error::UnknownError,
+    }
+}
     
  
 
@@ -43,6 +107,10 @@ pub const APDU_INSTRUCTION_AUTH_OBJECT: u8 = 0x40;
 #[allow(dead_code)]
 pub const APDU_INSTRUCTION_ATTEST: u8 = 0x20;
 
+// Attestation key pairs provisioned by NXP (see AN12436, SE050 configurations)
+pub const FACTORY_ATTESTATION_KEY_EC: [u8; 4] = [0xf0, 0x00, 0x00, 0x12];
+pub const FACTORY_ATTESTATION_KEY_RSA: [u8; 4] = [0xf0, 0x00, 0x00, 0x10];
+
 
  
 
@@ -181,11 +249,13 @@ pub enum Se050ApduP2 {
     SCP = 0x52,
     AuthFirstPart1 = 0x53,
     AuthNonfirstPart1 = 0x54,
+    State = 0x5b,
     ECKSGECKAPK_P2 = 0x21,
 }
 
 // See AN12413, 4.3.6 SecureObject type Table 24. SecureObjectType constants   P. 38
 #[allow(dead_code, clippy::upper_case_acronyms)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050ApduSecObjType {
     ECKeyPair = 0x01,
@@ -245,9 +315,35 @@ pub enum Se050TlvTag {
     Tag10 = 0x4a,
 }
 
+// Command TLVs are built from a Se050TlvTag so each parameter names its tag;
+// SimpleTlv::try_new with a raw u8 stays for data parsed off the wire.
+impl<'a> SimpleTlv<'a> {
+    pub fn with_tag(tag: Se050TlvTag, data: &'a [u8]) -> Result<Self, Iso7816Error> {
+        Self::try_new(tag.into(), data)
+    }
+}
+
+// See AN12413, 4.3.22 AttestationAlgo P.43: either an ECSignatureAlgo or an
+// RSASignatureAlgo, which also tells which kind of key attests.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Se050AttestationAlgo {
+    Ec(Se050ECSignatureAlgo),
+    Rsa(Se050RSASignatureAlgo),
+}
+
+impl From<Se050AttestationAlgo> for u8 {
+    fn from(value: Se050AttestationAlgo) -> u8 {
+        match value {
+        Se050AttestationAlgo::Ec(algo) => algo.into(),
+        Se050AttestationAlgo::Rsa(algo) => algo.into(),
+        }
+    }
+}
+
 // See AN12413,4.3.10 ECSignatureAlgo Table 28. ECSignatureAlgo P.39
 //(See AN12413, 4.3.22 AttestationAlgo AttestationAlgo is either ECSignatureAlgo or RSASignatureAlgo. P.43)
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050ECSignatureAlgo {
 SigEcdsaPlain = 0x09,
@@ -259,8 +355,24 @@ SigEcdsaSha512 = 0x26,
 
 }
 
+impl Se050ECSignatureAlgo {
+    // The applet never hashes for ECDSASign: the algorithm only announces which
+    // digest the input is. None for SigEcdsaPlain, whose input length is free.
+    pub fn digest_mode(&self) -> Option<Se050DigestModeconstants> {
+        match self {
+        Se050ECSignatureAlgo::SigEcdsaPlain => None,
+        Se050ECSignatureAlgo::SigEcdsaSha => Some(Se050DigestModeconstants::DigestSha),
+        Se050ECSignatureAlgo::SigEcdsaSha224 => Some(Se050DigestModeconstants::DigestSha224),
+        Se050ECSignatureAlgo::SigEcdsaSha256 => Some(Se050DigestModeconstants::DigestSha256),
+        Se050ECSignatureAlgo::SigEcdsaSha384 => Some(Se050DigestModeconstants::DigestSha384),
+        Se050ECSignatureAlgo::SigEcdsaSha512 => Some(Se050DigestModeconstants::DigestSha512),
+        }
+    }
+}
+
 // See AN12413, 4.3.11 EDSignatureAlgo Table 29. EDSignatureAlgo P.39
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050EDSignatureAlgo {
 
@@ -270,6 +382,7 @@ pub enum Se050EDSignatureAlgo {
 
 // See AN12413, 4.3.12 ECDAASignatureAlgo Table 30. ECDAASignatureAlgo P.40
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050ECDAASignatureAlgo {
 
@@ -280,6 +393,7 @@ pub enum Se050ECDAASignatureAlgo {
 // See AN12413, 4.3.13 RSASignatureAlgo Table 31. RSASignatureAlgo P.40
 //See AN12413, 4.3.22 AttestationAlgo AttestationAlgo is either ECSignatureAlgo or RSASignatureAlgo. P.43
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050RSASignatureAlgo {
     
@@ -299,6 +413,7 @@ RsaSha512Pkcs1 = 0x2A ,
 
  // See AN12413, 4.3.14 RSAEncryptionAlgo Table 32. RSAEncryptionAlgo P.40
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050RSAEncryptionAlgo {
 
@@ -325,6 +440,7 @@ pub enum Se050RSAEncryptionAlgo {
 
 // See AN12413, 4.3.16 RSAKeyComponent Table 34. RSAKeyComponentP.41
 #[allow(dead_code)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 #[repr(u8)]
 pub enum Se050RSAKeyComponent {    
     
@@ -342,7 +458,12 @@ pub enum Se050RSAKeyComponent {
  
 
     // See AN12413, 4.3.17 DigestMode Table 35. DigestMode constants P.41
+    // This is the complete table: the applet has no truncated SHA-512 variants
+    // (SHA-512/224, SHA-512/256). They differ from SHA-512 in their initial hash
+    // value, so truncating a DigestSha512 output does not give SHA-512/256 either;
+    // compute those on the host.
     #[allow(dead_code)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum Se050DigestModeconstants {
   
@@ -355,8 +476,23 @@ pub enum Se050RSAKeyComponent {
 
 }
 
+impl Se050DigestModeconstants {
+    // length of the digest in bytes, None for DigestNoHash
+    pub fn output_len(&self) -> Option<usize> {
+        match self {
+        Se050DigestModeconstants::DigestNoHash => None,
+        Se050DigestModeconstants::DigestSha => Some(20),
+        Se050DigestModeconstants::DigestSha224 => Some(28),
+        Se050DigestModeconstants::DigestSha256 => Some(32),
+        Se050DigestModeconstants::DigestSha384 => Some(48),
+        Se050DigestModeconstants::DigestSha512 => Some(64),
+        }
+    }
+}
+
     // See AN12413, 4.3.18 MACAlgo Table 36. MACAlgo constants P.41- 42
     #[allow(dead_code)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum Se050MACAlgoconstants {
       
@@ -382,6 +518,7 @@ pub enum Se050RSAKeyComponent {
 
     // See AN12413,4.3.19 ECCurve Table 37. ECCurve constants   P.42
     #[allow(dead_code)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum Se050ECCurveconstants  {
     
@@ -410,9 +547,144 @@ pub enum Se050RSAKeyComponent {
  
 }
 
+// group orders n, big-endian (SEC 2, FIPS 186-4 D.1.2, RFC 5639)
+const ORDER_NIST_P192: [u8; 24] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x99, 0xde, 0xf8, 0x36,
+    0x14, 0x6b, 0xc9, 0xb1, 0xb4, 0xd2, 0x28, 0x31,
+];
+const ORDER_NIST_P224: [u8; 28] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x16, 0xa2,
+    0xe0, 0xb8, 0xf0, 0x3e, 0x13, 0xdd, 0x29, 0x45, 0x5c, 0x5c, 0x2a, 0x3d,
+];
+const ORDER_NIST_P256: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+const ORDER_NIST_P384: [u8; 48] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xc7, 0x63, 0x4d, 0x81, 0xf4, 0x37, 0x2d, 0xdf,
+    0x58, 0x1a, 0x0d, 0xb2, 0x48, 0xb0, 0xa7, 0x7a, 0xec, 0xec, 0x19, 0x6a, 0xcc, 0xc5, 0x29, 0x73,
+];
+const ORDER_NIST_P521: [u8; 66] = [
+    0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xff, 0xfa, 0x51, 0x86, 0x87, 0x83, 0xbf, 0x2f, 0x96, 0x6b, 0x7f, 0xcc, 0x01, 0x48, 0xf7, 0x09,
+    0xa5, 0xd0, 0x3b, 0xb5, 0xc9, 0xb8, 0x89, 0x9c, 0x47, 0xae, 0xbb, 0x6f, 0xb7, 0x1e, 0x91, 0x38,
+    0x64, 0x09,
+];
+const ORDER_BRAINPOOL_P256R1: [u8; 32] = [
+    0xa9, 0xfb, 0x57, 0xdb, 0xa1, 0xee, 0xa9, 0xbc, 0x3e, 0x66, 0x0a, 0x90, 0x9d, 0x83, 0x8d, 0x71,
+    0x8c, 0x39, 0x7a, 0xa3, 0xb5, 0x61, 0xa6, 0xf7, 0x90, 0x1e, 0x0e, 0x82, 0x97, 0x48, 0x56, 0xa7,
+];
+const ORDER_SECP256K1: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xfe,
+    0xba, 0xae, 0xdc, 0xe6, 0xaf, 0x48, 0xa0, 0x3b, 0xbf, 0xd2, 0x5e, 0x8c, 0xd0, 0x36, 0x41, 0x41,
+];
+
+impl Se050ECCurveconstants {
+    const ALL: [Se050ECCurveconstants; 19] = [
+        Se050ECCurveconstants::NistP192,
+        Se050ECCurveconstants::NistP224,
+        Se050ECCurveconstants::NistP256,
+        Se050ECCurveconstants::NistP384,
+        Se050ECCurveconstants::NistP521,
+        Se050ECCurveconstants::Brainpool160,
+        Se050ECCurveconstants::Brainpool192,
+        Se050ECCurveconstants::Brainpool224,
+        Se050ECCurveconstants::Brainpool256,
+        Se050ECCurveconstants::Brainpool320,
+        Se050ECCurveconstants::Brainpool384,
+        Se050ECCurveconstants::Brainpool512,
+        Se050ECCurveconstants::Secp160k1,
+        Se050ECCurveconstants::Secp192k1,
+        Se050ECCurveconstants::Secp224k1,
+        Se050ECCurveconstants::Secp256k1,
+        Se050ECCurveconstants::TpmEccBnP256,
+        Se050ECCurveconstants::IdEccEd25519,
+        Se050ECCurveconstants::IdEccMontDh25519,
+    ];
+
+    pub fn from_u8(curve: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|c| *c as u8 == curve)
+    }
+
+    // length of a public key as exchanged with the SE050 (see ECKey):
+    // 0x04 || X || Y for the Weierstrass curves, the raw 32-byte u / encoded A for X25519 / Ed25519
+    pub fn public_key_len(&self) -> usize {
+        match self {
+        Se050ECCurveconstants::Brainpool160 | Se050ECCurveconstants::Secp160k1 => 1 + 2 * 20,
+        Se050ECCurveconstants::NistP192 | Se050ECCurveconstants::Brainpool192 | Se050ECCurveconstants::Secp192k1 => 1 + 2 * 24,
+        Se050ECCurveconstants::NistP224 | Se050ECCurveconstants::Brainpool224 | Se050ECCurveconstants::Secp224k1 => 1 + 2 * 28,
+        Se050ECCurveconstants::NistP256 | Se050ECCurveconstants::Brainpool256 | Se050ECCurveconstants::Secp256k1
+        | Se050ECCurveconstants::TpmEccBnP256 => 1 + 2 * 32,
+        Se050ECCurveconstants::Brainpool320 => 1 + 2 * 40,
+        Se050ECCurveconstants::NistP384 | Se050ECCurveconstants::Brainpool384 => 1 + 2 * 48,
+        Se050ECCurveconstants::Brainpool512 => 1 + 2 * 64,
+        Se050ECCurveconstants::NistP521 => 1 + 2 * 66,
+        Se050ECCurveconstants::IdEccEd25519 | Se050ECCurveconstants::IdEccMontDh25519 => 32,
+        }
+    }
+
+    // length of r and s in a raw (IEEE P1363) ECDSA signature: the byte length of
+    // the group order, one more than the field for secp160k1
+    pub fn scalar_len(&self) -> usize {
+        match self {
+        Se050ECCurveconstants::Secp160k1 => 21,
+        _ => self.public_key_len() / 2,
+        }
+    }
+
+    // group order n, for the curves whose private keys can be range checked
+    pub fn order(&self) -> Option<&'static [u8]> {
+        match self {
+        Se050ECCurveconstants::NistP192 => Some(&ORDER_NIST_P192),
+        Se050ECCurveconstants::NistP224 => Some(&ORDER_NIST_P224),
+        Se050ECCurveconstants::NistP256 => Some(&ORDER_NIST_P256),
+        Se050ECCurveconstants::NistP384 => Some(&ORDER_NIST_P384),
+        Se050ECCurveconstants::NistP521 => Some(&ORDER_NIST_P521),
+        Se050ECCurveconstants::Brainpool256 => Some(&ORDER_BRAINPOOL_P256R1),
+        Se050ECCurveconstants::Secp256k1 => Some(&ORDER_SECP256K1),
+        _ => None,
+        }
+    }
+
+    // Checks that a big-endian private scalar of the curve's length is in [1, n-1]
+    pub fn check_private_key(&self, privatekey: &[u8]) -> Result<(), Se050Error> {
+        let order = self.order().ok_or_else(|| {
+            error!("SE050 {:?}: no private key range check for this curve", self);
+            Se050Error::UnknownError })?;
+        if privatekey.len() != order.len() {
+            error!("SE050 {:?} private key must be {} bytes, got {}", self, order.len(), privatekey.len());
+            return Err(Se050Error::UnknownError);
+        }
+        if privatekey.iter().all(|b| *b == 0) || privatekey >= order {
+            error!("SE050 {:?} private key out of range", self);
+            return Err(Se050Error::UnknownError);
+        }
+        Ok(())
+    }
+
+    // Checks the encoding of an external public key for this curve
+    pub fn check_public_key(&self, publickey: &[u8]) -> Result<(), Se050Error> {
+        if publickey.len() != self.public_key_len() {
+            error!("SE050 {:?} public key must be {} bytes, got {}", self, self.public_key_len(), publickey.len());
+            return Err(Se050Error::UnknownError);
+        }
+        match self {
+        Se050ECCurveconstants::IdEccEd25519 | Se050ECCurveconstants::IdEccMontDh25519 => Ok(()),
+        _ if publickey[0] == 0x04 => Ok(()),
+        _ => {
+            error!("SE050 {:?} public key must be uncompressed (0x04 prefix)", self);
+            Err(Se050Error::UnknownError)
+        }
+        }
+    }
+}
+
 
     // See AN12413, 4.3.20 ECCurveParam  Table 38. ECCurveParam constants P 42
     #[allow(dead_code)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum Se050ECCurveParamconstants {     
     
@@ -425,7 +697,12 @@ pub enum Se050RSAKeyComponent {
 }
  
     // See AN12413,4.3.21 CipherMode Table 39. CipherMode constants   P.43
+    // None of these modes is authenticated: CipherOneShot and CipherFinal return the
+    // ciphertext in TAG_1 and nothing else, there is no GCM/CCM and no tag to return.
+    // For integrity, pair the cipher with a MAC under a second key (encrypt-then-MAC,
+    // see MacContext and mac_one_shot_into).
     #[allow(dead_code)]
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
     #[repr(u8)]
     pub enum  Se050CipherModeconstants {
          
@@ -447,6 +724,114 @@ pub enum Se050RSAKeyComponent {
  
 }
 
+impl Se050CipherModeconstants {
+    const ALL: [Se050CipherModeconstants; 14] = [
+        Se050CipherModeconstants::DesCbcNopad,
+        Se050CipherModeconstants::DesCbcIso9797M1,
+        Se050CipherModeconstants::DesCbcIso9797M2,
+        Se050CipherModeconstants::DesCbcPkcs5,
+        Se050CipherModeconstants::DesEcbNopad,
+        Se050CipherModeconstants::DesEcbIso9797M1,
+        Se050CipherModeconstants::DesEcbIso9797M2,
+        Se050CipherModeconstants::DesEcbPkcs5,
+        Se050CipherModeconstants::AesEcbNopad,
+        Se050CipherModeconstants::AesCbcNopad,
+        Se050CipherModeconstants::AesCbcIso9797M1,
+        Se050CipherModeconstants::AesCbcIso9797M2,
+        Se050CipherModeconstants::AesCbcPkcs5,
+        Se050CipherModeconstants::AesCtr,
+    ];
+
+    pub fn from_u8(mode: u8) -> Option<Self> {
+        Self::ALL.iter().copied().find(|m| *m as u8 == mode)
+    }
+
+    pub fn is_des(&self) -> bool {
+        (*self as u8) <= Se050CipherModeconstants::DesEcbPkcs5 as u8
+    }
+
+    // length of the IV (or initial counter block) the mode takes, 0 for ECB modes
+    // DES has a 64-bit block for both single DES and 3DES keys, AES a 128-bit block
+    pub fn iv_len(&self) -> usize {
+        match self {
+        Se050CipherModeconstants::DesCbcNopad
+        | Se050CipherModeconstants::DesCbcIso9797M1
+        | Se050CipherModeconstants::DesCbcIso9797M2
+        | Se050CipherModeconstants::DesCbcPkcs5 => 8,
+        Se050CipherModeconstants::DesEcbNopad
+        | Se050CipherModeconstants::DesEcbIso9797M1
+        | Se050CipherModeconstants::DesEcbIso9797M2
+        | Se050CipherModeconstants::DesEcbPkcs5
+        | Se050CipherModeconstants::AesEcbNopad => 0,
+        Se050CipherModeconstants::AesCbcNopad
+        | Se050CipherModeconstants::AesCbcIso9797M1
+        | Se050CipherModeconstants::AesCbcIso9797M2
+        | Se050CipherModeconstants::AesCbcPkcs5
+        | Se050CipherModeconstants::AesCtr => 16,
+        }
+    }
+}
+
+// Rejects an IV whose length does not match the CipherMode before the command is sent.
+// An empty IV is always accepted: the applet then uses its default (all zero) IV.
+pub(crate) fn check_cipher_iv(ciphermode: Se050CipherModeconstants, initializationvector: &[u8]) -> Result<(), Se050Error> {
+    if !initializationvector.is_empty() && initializationvector.len() != ciphermode.iv_len() {
+        error!("SE050 {:?} takes a {}-byte IV, got {}", ciphermode, ciphermode.iv_len(), initializationvector.len());
+        return Err(Se050Error::UnknownError);
+    }
+    Ok(())
+}
+
+// MAC from TLV[TAG_1] of a MACFinal or MACOneShot response
+fn copy_mac(rapdu: &RApdu, mac: &mut [u8]) -> Result<usize, Se050Error> {
+    let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+        error!("SE050 MAC Return TLV Missing");
+        Se050Error::MissingResponseTlv })?;
+    let data = tlv1_ret.get_data();
+    if data.len() > mac.len() {
+        error!("SE050 MAC Buffer Too Small");
+        return Err(Se050Error::UnknownError);
+    }
+    mac[0..data.len()].copy_from_slice(data);
+    Ok(data.len())
+}
+
+// DES key object subtype, selected by the key length in WriteSymmKey
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DesKeyType {
+    SingleDes,
+    TwoKey3Des,
+    ThreeKey3Des,
+}
+
+impl DesKeyType {
+    pub fn from_key_len(len: usize) -> Option<Self> {
+        match len {
+        8 => Some(DesKeyType::SingleDes),
+        16 => Some(DesKeyType::TwoKey3Des),
+        24 => Some(DesKeyType::ThreeKey3Des),
+        _ => None,
+        }
+    }
+}
+
+// Total length (header included) of the DER element at the start of `der`,
+// e.g. an X.509 certificate SEQUENCE; None if the header is malformed.
+// 0x30 0x82 followed by a two-byte length, enough for any certificate a binary file holds
+const CERTIFICATE_HEADER_LEN: usize = 4;
+
+pub(crate) fn der_element_len(der: &[u8]) -> Option<usize> {
+    let lenbyte = *der.get(1)?;
+    if lenbyte < 0x80 {
+        return Some(2 + lenbyte as usize);
+    }
+    let n = (lenbyte & 0x7f) as usize;
+    if n == 0 || n > 3 || der.len() < 2 + n {
+        return None;
+    }
+    Some(2 + n + BE::read_uint(&der[2..2 + n], n) as usize)
+}
+
 // See AN12413,4.3.23 // 4.3.22 AttestationAlgo // AttestationAlgo is either ECSignatureAlgo or RSASignatureAlgo.
 
 
@@ -499,6 +884,35 @@ pub enum Se050RSAKeyComponent {
 }
 
 
+    // See AN12413, 4.3.26 CryptoContext P.44: the Crypto Object subtype is a
+    // DigestMode, CipherMode or MACAlgo, and that choice fixes the CryptoContext.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub enum Se050CryptoObjectSubtype {
+        Digest(Se050DigestModeconstants),
+        Cipher(Se050CipherModeconstants),
+        Signature(Se050MACAlgoconstants),
+    }
+
+    impl Se050CryptoObjectSubtype {
+        pub fn context(&self) -> Se050CryptoContextconstants {
+            match self {
+            Se050CryptoObjectSubtype::Digest(_) => Se050CryptoContextconstants::CcDigest,
+            Se050CryptoObjectSubtype::Cipher(_) => Se050CryptoContextconstants::CcCipher,
+            Se050CryptoObjectSubtype::Signature(_) => Se050CryptoContextconstants::CcSignature,
+            }
+        }
+    }
+
+    impl From<Se050CryptoObjectSubtype> for u8 {
+        fn from(value: Se050CryptoObjectSubtype) -> u8 {
+            match value {
+            Se050CryptoObjectSubtype::Digest(mode) => mode.into(),
+            Se050CryptoObjectSubtype::Cipher(mode) => mode.into(),
+            Se050CryptoObjectSubtype::Signature(algo) => algo.into(),
+            }
+        }
+    }
+
     // See AN12413,   4.3.26 CryptoContext , Table 43. P.44
     #[allow(dead_code)]
     #[repr(u8)]
@@ -623,6 +1037,7 @@ pub enum Se050RSAKeyComponent {
 
 
     #[allow(dead_code)]    
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
          #[repr(u32)]
     pub enum Se050Objectpolicies {    
 
@@ -663,6 +1078,105 @@ pub enum Se050RSAKeyComponent {
         pub enum Se050keyversionnumber {
             KeyVersionNumber00 = 0x00,
         }
+
+// See AN12413, 4.7.3.1 ReadObject, Object attributes (P2_ATTRIBUTES)
+// 4-byte object identifier, 1-byte object class, 1-byte authentication indicator,
+// 2-byte authentication attempts counter, 4-byte authentication object identifier,
+// 2-byte maximum authentication attempts, policy set, 1-byte origin.
+const OBJECT_ATTRIBUTES_POLICY_OFFSET: usize = 14;
+pub(crate) const OBJECT_ATTRIBUTES_CLASS_OFFSET: usize = 4;
+const OBJECT_ATTRIBUTES_AUTH_INDICATOR_OFFSET: usize = 5;
+const OBJECT_ATTRIBUTES_ATTEMPTS_OFFSET: usize = 6;
+const OBJECT_ATTRIBUTES_MAX_ATTEMPTS_OFFSET: usize = 12;
+
+// Remaining authentication attempts of an authentication object (e.g. a UserID)
+// from its attributes: maximum attempts minus the failed attempts counter.
+// A maximum of 0 means unlimited attempts, reported as u16::MAX.
+pub(crate) fn auth_attempts_remaining(attributes: &[u8]) -> Result<u16, Se050Error> {
+    if attributes.len() < OBJECT_ATTRIBUTES_POLICY_OFFSET {
+        return Err(Se050Error::UnknownError);
+    }
+    if attributes[OBJECT_ATTRIBUTES_AUTH_INDICATOR_OFFSET] != 0x01 {
+        error!("SE050 not an authentication object");
+        return Err(Se050Error::UnknownError);
+    }
+    let attempts = BE::read_u16(&attributes[OBJECT_ATTRIBUTES_ATTEMPTS_OFFSET..OBJECT_ATTRIBUTES_ATTEMPTS_OFFSET + 2]);
+    let max_attempts = BE::read_u16(&attributes[OBJECT_ATTRIBUTES_MAX_ATTEMPTS_OFFSET..OBJECT_ATTRIBUTES_MAX_ATTEMPTS_OFFSET + 2]);
+    if max_attempts == 0 {
+        return Ok(u16::MAX);
+    }
+    Ok(max_attempts.saturating_sub(attempts))
+}
+
+// See AN12413, 3.7.3 Session policies
+// One entry of the session policy: its header bit, plus the 2-byte maximum
+// number of APDUs for MaxApdu.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SessionPolicy {
+    MaxApdu(u16),
+    AllowRefresh,
+}
+
+impl SessionPolicy {
+    fn header_bit(&self) -> u8 {
+        match self {
+        SessionPolicy::MaxApdu(_) => Se050Sessionpolicies::PolicySessionMaxApdu as u8,
+        SessionPolicy::AllowRefresh => Se050Sessionpolicies::PolicySessionAllowRefresh as u8,
+        }
+    }
+}
+
+// The session policy: length byte, the 2-byte header with the bits of all entries
+// ORed into B1, then the maximum APDU count if MaxApdu is set. An entry may appear once.
+pub(crate) fn encode_session_policy(policies: &[SessionPolicy]) -> Result<heapless::Vec<u8, 5>, Se050Error> {
+    let mut header = 0u8;
+    let mut max_apdu = None;
+    for policy in policies {
+        if header & policy.header_bit() != 0 {
+            error!("SE050 session policy: {:?} given twice", policy);
+            return Err(Se050Error::UnknownError);
+        }
+        header |= policy.header_bit();
+        if let SessionPolicy::MaxApdu(n) = policy {
+            max_apdu = Some(*n);
+        }
+    }
+
+    let mut data: heapless::Vec<u8, 5> = heapless::Vec::new();
+    let len = if max_apdu.is_some() { 4 } else { 2 };
+    data.extend_from_slice(&[len, header, 0x00]).map_err(|_| Se050Error::UnknownError)?;
+    if let Some(n) = max_apdu {
+        data.extend_from_slice(&n.to_be_bytes()).map_err(|_| Se050Error::UnknownError)?;
+    }
+    Ok(data)
+}
+
+// See AN12413, 3.7 Policies
+// Each policy is a 1-byte length followed by a 4-byte authentication object identifier,
+// a 4-byte access rule header (B1 first, matching Se050Objectpolicies) and optional extensions.
+// Returns whether any policy in the set grants `required`; an empty set means the
+// default policy applies, which grants everything.
+pub(crate) fn object_policy_allows(attributes: &[u8], required: u32) -> Result<bool, Se050Error> {
+    if attributes.len() < OBJECT_ATTRIBUTES_POLICY_OFFSET + 1 {
+        return Err(Se050Error::UnknownError);
+    }
+    let mut policies = &attributes[OBJECT_ATTRIBUTES_POLICY_OFFSET..attributes.len()-1];
+    if policies.is_empty() {
+        return Ok(true);
+    }
+    while !policies.is_empty() {
+        let len = policies[0] as usize;
+        if len < 8 || len + 1 > policies.len() {
+            return Err(Se050Error::UnknownError);
+        }
+        let ar_header = LE::read_u32(&policies[5..9]);
+        if ar_header & (Se050Objectpolicies::PolicyObjForbidAll as u32) == 0 && ar_header & required == required {
+            return Ok(true);
+        }
+        policies = &policies[len+1..];
+    }
+    Ok(false)
+}
  
 
 include!("se050_convs.rs");
@@ -678,7 +1192,7 @@ include!("se050_convs.rs");
 //trait-Se050Device ->  struct Se050
 pub trait Se050Device {
     //OLD VERSION
-    fn enable(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn enable(&mut self, delay: &mut DelayWrapper) -> Result<SelectStatus, Se050Error>;
     //OLD VERSION
     fn disable(&mut self, _delay: &mut DelayWrapper);
 
@@ -718,7 +1232,16 @@ pub trait Se050Device {
     //See AN12413 , 4.6 Module management 
 
     //See AN12413 , 4.6 Module management //   4.6.1 SetLockState P.54-55 
-    fn set_lock_state(&mut self, lockindicator: &[u8], lockstate: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn set_lock_state(&mut self, lockindicator: Se050LockIndicatorconstants, lockstate: Se050LockStateconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+
+    //See AN12413 , 4.6 Module management //   4.6.1 SetLockState P.54-55 
+    fn set_transport_state(&mut self, lock: bool, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    //See SE05x applet 3.x , Module management // ReadState (P2_STATE, not in AN12413)
+    fn get_transport_state(&mut self, delay: &mut DelayWrapper) -> Result<bool, Se050Error>;
+
+    //See SE05x applet 3.x , Module management // ReadState (P2_STATE, not in AN12413)
+    fn get_applet_lifecycle(&mut self, delay: &mut DelayWrapper) -> Result<LifecycleState, Se050Error>;
 
     //See AN12413 , 4.6 Module management //   4.6.2 SetPlatformSCPRequest P.55-56 
     fn set_platform_scp_request(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error>;
@@ -732,7 +1255,7 @@ pub trait Se050Device {
     // See AN12413,  4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey //P1_EC ///P.58-59 
     // fn generate_eccurve_key(&mut self, eccurve: &[u8], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>; //ERWEITERT      
     //fn write_ec_key(&mut self,policy: &[u8],  objectid: &[u8;4], eccurve: &[u8], private_key_value: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>  ;
-    fn write_ec_key(&mut self,   objectid: &[u8;4], eccurve: &[u8],    delay: &mut DelayWrapper) -> Result<(), Se050Error>  ;
+    fn write_ec_key(&mut self,   objectid: &[u8;4], eccurve: Se050ECCurveconstants,    delay: &mut DelayWrapper) -> Result<(), Se050Error>  ;
     //OLD VERSION
    // fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> ;
     //DEFAULT CONFIGURATION OF SE050   
@@ -744,7 +1267,26 @@ pub trait Se050Device {
 
   // fn generate_ed255_key_pair(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
 
+   #[deprecated(note = "use generate_ed25519_key")]
    fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> ;
+
+    // See AN12413,  4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey //P1_EC ///P.58-59 
+    fn generate_x25519_key(&mut self, objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    // See AN12413,  4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey //P1_EC ///P.58-59 
+    fn generate_ed25519_key(&mut self, objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    // See AN12413,  4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey //P1_EC //P1_PUBLIC ///P.58-59 
+    fn write_ec_public_key(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, publickey: &[u8], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey P.58-59
+    fn write_ec_private_key(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, privatekey: &[u8], publickey: Option<&[u8]>, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey P.58-59
+    fn generate_ec_key_with_config(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, config: &KeyConfig, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60-61
+    fn write_symm_key_with_config(&mut self, objectidentifier: &[u8;4], keytype: SymmKeyType, kek: Option<&[u8;4]>, key: &[u8], config: &KeyConfig, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
  
   
     //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject// 4.7.1.2 WriteRSAKey //P.59-60  
@@ -758,7 +1300,12 @@ pub trait Se050Device {
     //NEW VERSION
     //  fn write_aes_key(&mut self,policy: &[u8], objectid: &[u8;4],kekid: &[u8;4],key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
-    fn generate_aes_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+    //OLD VERSION
+    //fn generate_aes_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
+
+    //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60
+    // not supported: AN12413 has no on-chip symmetric key generation, always returns Unsupported
+    fn generate_aes_key(&mut self, objectid: &[u8;4], bits: u16, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>;
 
     fn write_des_key(&mut self,policy: &[u8], objectid: &[u8;4],kekid: &[u8;4],key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
  
@@ -766,9 +1313,18 @@ pub trait Se050Device {
 
     // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.4 WriteBinary  //P.61
     fn write_binary(&mut self,policy: &[u8], objectid: &[u8;4],file_offset: &[u8;2], file_length: &[u8;2], data1: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.4 WriteBinary  //P.61
+    fn write_binary_object(&mut self, objectid: &[u8;4], data: &[u8], policy: Option<&[u8]>, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management //4.7.1.4 WriteBinary //4.7.3.1 ReadObject //P.61, P.65-66
+    fn store_certificate(&mut self, objectid: &[u8;4], der: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    fn load_certificate(&mut self, objectid: &[u8;4], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
  
     // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject P.57 //4.7.1.5 write_user_id  //P.62    
-    fn write_user_id(&mut self, policy: &[u8],objectid: &[u8;4],user_identifier_value : &[u8;16], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn write_user_id(&mut self, policy: &[u8],objectid: &[u8;4],user_identifier_value : &[u8;16], max_attempts: Option<u16>, delay: &mut DelayWrapper) -> Result<UserIdObject, Se050Error> ;
      
     // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.6 WriteCounter  //P.62
     fn write_counter(&mut self,policy: &[u8], counterid: &[u8;4],countersize: &[u8;2], counterfile: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
@@ -779,7 +1335,7 @@ pub trait Se050Device {
     
     // See AN12413 // 4.7 Secure Object management // 4.7.1.8  ImportObject P.63-64
         
-    fn import_object(&mut self,identifier: &[u8;4], rsakeycomponent: &[u8],serializedobjectencrypted: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn import_object(&mut self,identifier: &[u8;4], rsakeycomponent: Option<Se050RSAKeyComponent>,serializedobjectencrypted: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
       
 
 
@@ -791,6 +1347,24 @@ pub trait Se050Device {
     //fn read_secure_object(&mut self,objectidentifier: &[u8;4], offset: &[u8;2],length: &[u8;2], rsakeycomponent : &[u8],  attobjectidentifier: &[u8;4],  attlogo: &[u8],   freshnessrandom: &[u8;16],     delay: &mut DelayWrapper) -> Result<(), Se050Error>;
 
     fn read_secure_object(&mut self, buf: &mut [u8], objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    fn read_rsa_public_key(&mut self, objectidentifier: &[u8;4], component: Se050RSAKeyComponent, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (INS_ATTEST) // P.65-66
+    fn attest_with_factory_key(&mut self, objectidentifier: &[u8;4], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8;16], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (INS_ATTEST) // P.65-66
+    fn read_object_attested<'o>(&mut self, objectidentifier: &[u8;4], attestationkey: &[u8;4], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8;16], out: &'o mut [u8], delay: &mut DelayWrapper) -> Result<AttestationResult<'o>, Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (P2_ATTRIBUTES) // P.65-66
+    fn read_object_attributes(&mut self, objectidentifier: &[u8;4], attributes: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
+
+    // See AN12413 // 3.7 Policies
+    fn check_object_policy(&mut self, objectidentifier: &[u8;4], required: Se050Objectpolicies, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (P2_ATTRIBUTES) // P.65-66
+    fn get_userid_attempts(&mut self, objectid: ObjectId, delay: &mut DelayWrapper) -> Result<u16, Se050Error>;
     
 
     // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.2 ExportObject // P.67
@@ -805,6 +1379,9 @@ pub trait Se050Device {
     // See AN12413// 4.7 Secure Object management  //4.7.4 ManageSecureObject // 4.7.4.3 ReadIDList P.69 
     fn read_id_list(&mut self,offset: &[u8;2] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>;
 
+    // See AN12413// 4.7 Secure Object management  //4.7.4 ManageSecureObject // 4.7.4.3 ReadIDList P.69
+    fn object_count(&mut self, delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
+
     // See AN12413// 4.7 Secure Object management  //4.7.4 ManageSecureObject // 4.7.4.4 CheckObjectExists P.70 
   //  fn check_object_exists(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
@@ -819,25 +1396,28 @@ pub trait Se050Device {
     // See AN12413//   4.8 EC curve management
 
     // See AN12413//   4.8 EC curve management // 4.8.1 CreateECCurve -Create an EC curve listed in ECCurve P.71-72
-    fn create_eccurve(&mut self, eccurve: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn create_eccurve(&mut self, eccurve: Se050ECCurveconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
  
     // See AN12413//   4.8 EC curve management //  4.8.2 SetECCurveParam -Set a curve parameter. The curve must have been created first by CreateEcCurve. P.72
-    fn set_eccurve_param(&mut self, eccurve: &[u8], eccurveptaram: &[u8], curveparametervalue: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn set_eccurve_param(&mut self, eccurve: Se050ECCurveconstants, eccurveptaram: Se050ECCurveParamconstants, curveparametervalue: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>;
      
     // See AN12413//   4.8 EC curve management //  4.8.3 GetECCurveID Get the curve associated with an EC key.. P.72-73  
-    fn get_eccurve_id(&mut self, identifier: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn get_eccurve_id(&mut self, identifier: &[u8;4], delay: &mut DelayWrapper) -> Result<Se050ECCurveconstants, Se050Error>;
      
     // See AN12413//   4.8 EC curve management //  4.8.3 GetECCurveID Get the curve associated with an EC key.. P.73    
     fn read_eccurve_list(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error>;   
 
+    // See AN12413//   4.8 EC curve management //  4.8.4 ReadECCurveList P. 73
+    fn supported_curves(&mut self, delay: &mut DelayWrapper) -> Result<heapless::Vec<Se050ECCurveconstants, 32>, Se050Error>;
+
     // See AN12413//   4.8 EC curve management // 4.8.5 DeleteECCurve - Deletes an EC curve P.74  
-    fn delete_eccurve(&mut self,  eccurve: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn delete_eccurve(&mut self,  eccurve: Se050ECCurveconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
 
     // See AN12413// 4.9 Crypto Object management
 
     // See AN12413// 4.9 Crypto Object management // 4.9.1 CreateCryptoObject - Creates a Crypto Object on the SE050. P74-75
-    fn create_crypto_object(&mut self,  cryptoobjectidentifier: &[u8;2], cryptocontext: &[u8], cryptoobjectsubtype: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn create_crypto_object(&mut self,  cryptoobjectidentifier: &[u8;2], cryptoobjectsubtype: Se050CryptoObjectSubtype, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
    
     // See AN12413// 4.9 Crypto Object management // 4.9.2 ReadCryptoObjectList. P.75
     fn read_crypto_object_list(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error>;
@@ -846,40 +1426,40 @@ pub trait Se050Device {
     fn delete_crypto_object(&mut self, cryptoobjectidentifier: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>;
      
     // See AN12413 // 4.10 Crypto operations EC // 4.10.1 Signature generation // 4.10.1.1 ECDSASign P.76-77  
-    fn ecdsa_sign(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: &[u8],inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn ecdsa_sign(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, inputdata: &[u8], signature: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
  
 
     // See AN12413 // 4.10 Crypto operations EC 
 //fff
     // See AN12413 // 4.10 Crypto operations EC // 4.10.1 Signature generation // 4.10.1.2 EdDSASign P.77   
-    fn eddsa_sign(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: &[u8],inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn eddsa_sign(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: Se050EDSignatureAlgo, inputdata: &[u8], signature: &mut [u8; 64], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
   
     // See AN12413 // 4.10 Crypto operations EC // 4.10.1 Signature generation // 4.10.1.3 ECDAASign P.78
-    fn ecdaa_sign(&mut self, eckeyidentifier: &[u8;4], ecdaasignaturealgo: &[u8],hashedinputdata: &[u8;32], randomdata: &[u8;32], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn ecdaa_sign(&mut self, eckeyidentifier: &[u8;4], ecdaasignaturealgo: Se050ECDAASignatureAlgo,hashedinputdata: &[u8;32], randomdata: &[u8;32], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
  
     // See AN12413 // 4.10 Crypto operations EC // 4.10.2 Signature verification // 4.10.2.1 ECDSAVerify P.79
-    fn ecdsa_verify(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: &[u8],hashedcomparedata: &[u8], asn1signaturedata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn ecdsa_verify(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo,hashedcomparedata: &[u8], asn1signaturedata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
   
     // See AN12413 // 4.10 Crypto operations EC // 4.10.2 Signature verification // 4.10.2.2 EdDSAVerify P.80   
-    fn eddsa_verify(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: &[u8],plaincomparedata: &[u8], signaturedata: &[u8;64], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn eddsa_verify(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: Se050EDSignatureAlgo,plaincomparedata: &[u8], signaturedata: &[u8;64], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
     // See AN12413 // 4.10 Crypto operations EC //  4.10.3 Shared secret generation //  4.10.3.1 ECDHGenerateSharedSecret P.81
-    fn ecdh_generate_shared_secret(&mut self, eckeyidentifier: &[u8;4], eckey: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>;    
+    fn ecdh_generate_shared_secret(&mut self, eckeyidentifier: &[u8;4], curve: Se050ECCurveconstants, publickey: &[u8], sharedsecret: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
  
 
     // See AN12413 // 4.11 Crypto operations RSA
 
     // See AN12413 // 4.11 Crypto operations RSA // 4.11.1 Signature Generation //4.11.1.1 RSASign P.82 
-    fn rsa_sign(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: &[u8], inputdata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn rsa_sign(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: Se050RSASignatureAlgo, inputdata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
   
     // See AN12413 // 4.11 Crypto operations RSA // 4.11.2 Signature Verification  //4.11.2.1 RSAVerify P.82-83
-    fn rsa_verify(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: &[u8], datatobeverified: &[u8], asn1signature: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn rsa_verify(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: Se050RSASignatureAlgo, datatobeverified: &[u8], asn1signature: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
     // See AN12413 // 4.11 Crypto operations RSA // 4.11.3 Encryption // 4.11.3.1 RSAEncrypt P.83-84
-    fn rsa_encrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: &[u8], datatobeencrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn rsa_encrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: Se050RSAEncryptionAlgo, datatobeencrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
      
     // See AN12413 // 4.11 Crypto operations RSA // 4.11.3 Encryption // 4.11.3.2 RSADecrypt P.84
-    fn rsa_decrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: &[u8], datatobedecrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn rsa_decrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: Se050RSAEncryptionAlgo, datatobedecrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
 
     //See AN12413 //4.12 Crypto operations AES/DES
@@ -895,8 +1475,8 @@ pub trait Se050Device {
     fn cipher_final(&mut self, cryptoobjectidentifier: &[u8;2], inputdata: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
     //See AN12413 //4.12 Crypto operations AES/DES //4.12.4 CipherOneShot P.87      
-    fn cipher_one_shot_encrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: &[u8] ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
-    fn cipher_one_shot_decrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: &[u8] ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn cipher_one_shot_encrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn cipher_one_shot_decrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
  
     //4.12 Crypto operations AES/DES  //4.12.4 CipherOneShot - Encrypt or decrypt data in one shot mode //P.87
     /* 
@@ -913,10 +1493,10 @@ pub trait Se050Device {
 
 //NEW VERSION
     //fn encrypt_aes_oneshot(&mut self, objectid: &[u8;4], cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> ;
-    fn decrypt_aes_oneshot( &mut self,  objectid: &[u8;4],  cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
+    fn decrypt_aes_oneshot( &mut self,  objectid: &[u8;4],  cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
     
-    fn encrypt_des_oneshot( &mut self,   objectid: &[u8;4], cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
-    fn decrypt_des_oneshot( &mut self,   objectid: &[u8;4], cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
+    fn encrypt_des_oneshot( &mut self,   objectid: &[u8;4], cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
+    fn decrypt_des_oneshot( &mut self,   objectid: &[u8;4], cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper,) -> Result<(), Se050Error>;
   
 
     //See AN12413 //4.13 Message Authentication Codes 
@@ -931,13 +1511,13 @@ pub trait Se050Device {
     fn mac_final(&mut self, macdatainput: &[u8], cryptobjectidentifier: &[u8;2], mactovalidate: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
    
     //See AN12413 //4.13 Message Authentication Codes //4.13.4 MACOneShot P.90
-    fn mac_one_shot(&mut self, keyobjectidentifier: &[u8;4], macalgo: &[u8], datainputtomac: &[u8], mactoverify: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn mac_one_shot(&mut self, keyobjectidentifier: &[u8;4], macalgo: Se050MACAlgoconstants, datainputtomac: &[u8], mactoverify: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
    
 
     //See AN12413 //4.14 Key Derivation Functions
 
     //See AN12413 //4.14 Key Derivation Functions //4.14.1 HKDF P.90-91  
-    fn hkdf(&mut self, hmackeyidentifier: &[u8;4], digestmode: &[u8], salt: &[u8;64], info: &[u8;64],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn hkdf(&mut self, hmackeyidentifier: &[u8;4], digestmode: Se050DigestModeconstants, salt: &[u8;64], info: &[u8;64],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
     
     //See AN12413 //4.14 Key Derivation Functions //4.14.2 PBKDF2 P.91-92     
     fn pbkdf2derivekey(&mut self,  passwordidentifier: &[u8;4],  salt: &[u8;64], iterationcount: &[u8;2],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>;
@@ -946,7 +1526,7 @@ pub trait Se050Device {
     //See AN12413,// 4.15 MIFARE DESFire support 
 
     //See AN12413,// 4.15 MIFARE DESFire support //4.15.1 DFDiversifyKey P92-95         
-    fn dfdiversifykey(&mut self,  masterkeyidentifier: &[u8;4], diversifiedkeyidentifier: &[u8;4], divinput: &[u8;31], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+    fn dfdiversifykey(&mut self,  masterkeyidentifier: &[u8;4], diversifiedkeyidentifier: &[u8;4], divinput: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
    
     //See AN12413,// 4.15 MIFARE DESFire support //4.15.2 DFAuthenticateFirst  //4.15.2.1 DFAuthenticateFirstPart1 // P.95-96                    
     fn dfauthenticatefirstpart1(&mut self, keyidentifier: &[u8;4],diversifiedkeyidentifier: &[u8;16], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
@@ -982,13 +1562,13 @@ pub trait Se050Device {
     fn tls_calculate_pre_master_secret(&mut self,  pskidentifier: &[u8;4], keypairidentifier: &[u8;4],hmackeyidentifier:&[u8;4], inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
   
     //See AN12413, //4.16 TLS handshake support //  4.16.3 TLSPerformPRF P.101-102 
-    fn tls_perform_prf(&mut self,  hmackeyidentifier:&[u8;4], digestmode: &[u8],label: &[u8;64],random:&[u8;32], requestlenght:&[u8;2],delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn tls_perform_prf(&mut self,  hmackeyidentifier:&[u8;4], digestmode: Se050DigestModeconstants,label: &[u8;64],random:&[u8;32], requestlenght:&[u8;2],delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
     
 
     //See AN12413, //4.17 I2C controller support 
 
     //See AN12413, //4.17 I2C controller support //4.17.1 I2CM_ExecuteCommandSet //P.103-106  
-    fn i2cm_execute_command_set(&mut self, i2ccommand: &[u8], attestationobjectidentifier: &[u8], attestationalgo: &[u8], freshnessrandom: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn i2cm_execute_command_set(&mut self, i2ccommand: &[u8], attestationobjectidentifier: &[u8], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
 
 
     //See AN12413, //4.18 Digest operations
@@ -1003,29 +1583,81 @@ pub trait Se050Device {
     fn digest_final(&mut self, cryptoobjectidentifier: &[u8;2], datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
 
     //AN12413, //4.18 Digest operations //4.18.3 DigestFinal // P. 107-108    
-    fn digest_one_shot(&mut self, digestmode: &[u8], datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn digest_one_shot(&mut self, digestmode: Se050DigestModeconstants, datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+
+    //AN12413, //4.18 Digest operations //4.18.4 DigestOneShot // P.108
+    fn digest(&mut self, digestmode: Se050DigestModeconstants, datatobehashed: &[u8], digest: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> ;
 
 
     //See AN12413, // 4.19 Generic management commands
 
     //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109  
     fn get_version(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    fn is_fips_mode(&mut self, delay: &mut DelayWrapper) -> Result<bool, Se050Error>;
+
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    fn capabilities(&mut self, delay: &mut DelayWrapper) -> Result<Capabilities, Se050Error>;
+
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    fn rsa_key_size_range(&mut self, delay: &mut DelayWrapper) -> Result<(u16, u16), Se050Error>;
+
+    //AN12413, // 4.19 Generic management commands // P2_VARIANT
+    fn get_applet_variant(&mut self, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
+
+    //AN12413, // 4.19 Generic management commands // P2_PARAM
+    fn get_applet_param(&mut self, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>;
     
     //AN12413, // 4.19 Generic management commands //4.19.2 get_timestamp P.109
     fn get_timestamp(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
     
     //AN12413, // 4.19 Generic management commands //4.19.2 GetTimestamp P.109 
-    fn get_free_memory(&mut self, memoryconstant: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+    fn get_free_memory(&mut self, memoryconstant: Se050ApduMemoryType, delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
+
+    //AN12413, // 4.19 Generic management commands //4.19.3 GetFreeMemory P.109, for all three memory types
+    fn get_memory_info(&mut self, delay: &mut DelayWrapper) -> Result<MemoryInfo, Se050Error>;
 
     // See AN12413, //4.19 Generic management commands // P110-11
     //OLD VERSION
     fn get_random(&mut self, buf: &mut [u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
 
+    // 32 bytes from GetRandom, sized for seeding a software CSPRNG
+    fn seed_rng(&mut self, seed: &mut [u8; 32], delay: &mut DelayWrapper) -> Result<(), Se050Error>;
+
     //AN12413, // 4.19 Generic management commands //44.19.5 delete_all P.112
     fn delete_all(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> ;
  
     }
  
+// Outcome of a GP SELECT the applet answered with its version info.
+// AN12413 4.4 only documents SW_NO_ERROR; a card in the GP CARD_LOCKED state
+// (ISO 7816-4: selected file deactivated) answers 0x6283 with the same version
+// info and the applet still processes commands, so that SW is reported as a
+// warning instead of failing enable(). Any other SW fails the SELECT.
+pub const SW_SELECTED_FILE_DEACTIVATED: u16 = 0x6283;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SelectStatus {
+    Ok,
+    Warning(u16),
+}
+
+impl SelectStatus {
+    // None for a SW that fails the SELECT
+    pub fn from_sw(sw: u16) -> Option<Self> {
+        match sw {
+        0x9000 => Some(SelectStatus::Ok),
+        SW_SELECTED_FILE_DEACTIVATED => Some(SelectStatus::Warning(sw)),
+        _ => None,
+        }
+    }
+
+    pub fn is_warning(&self) -> bool {
+        matches!(self, SelectStatus::Warning(_))
+    }
+}
+
 //struct Se050AppInfo ->no further Implementation 20221026
 #[allow(dead_code)]
 #[derive(Debug)]
@@ -1034,6 +1666,275 @@ pub struct Se050AppInfo {
     features: u16,
     securebox_version: u16,
 }
+
+impl Se050AppInfo {
+    // version info as in the SELECT and GetVersion responses: 3-byte applet
+    // version, 2-byte AppletConfig, 2-byte SecureBox version
+    fn from_version_info(data: &[u8]) -> Option<Self> {
+        if data.len() < 7 {
+            return None;
+        }
+        Some(Se050AppInfo {
+            applet_version: BE::read_uint(&data[0..3], 3) as u32,
+            features: BE::read_u16(&data[3..5]),
+            securebox_version: BE::read_u16(&data[5..7]),
+        })
+    }
+
+    pub fn applet_version(&self) -> u32 {
+        self.applet_version
+    }
+
+    // AppletConfig bits, see Se050AppletConfig
+    pub fn features(&self) -> u16 {
+        self.features
+    }
+
+    // features() as flags, e.g. applet_features().contains(AppletFeatures::RSA_CRT)
+    pub fn applet_features(&self) -> AppletFeatures {
+        AppletFeatures::from_bits(self.features)
+    }
+
+    // 0 if the applet has no SecureBox
+    pub fn securebox_version(&self) -> u16 {
+        self.securebox_version
+    }
+
+    pub fn has_securebox(&self) -> bool {
+        self.securebox_version != 0
+    }
+
+    pub fn has_des(&self) -> bool {
+        self.applet_features().contains(AppletFeatures::DES)
+    }
+
+    // FIPS mode is on unless the applet reports CONFIG_FIPS_MODE_DISABLED
+    pub fn is_fips_mode(&self) -> bool {
+        !self.applet_features().contains(AppletFeatures::FIPS_MODE_DISABLED)
+    }
+
+    pub fn capabilities(&self) -> Capabilities {
+        Capabilities::from_features(self.applet_features())
+    }
+
+    // smallest and largest RSA modulus in bits, None without RSA
+    pub fn rsa_key_size_range(&self) -> Option<(u16, u16)> {
+        if !self.applet_features().intersects(AppletFeatures::RSA_ALL) {
+            return None;
+        }
+        let min = if self.is_fips_mode() { RSA_KEY_SIZE_MIN_FIPS } else { RSA_KEY_SIZE_MIN };
+        Some((min, RSA_KEY_SIZE_MAX))
+    }
+}
+
+// RSA moduli the SE050 generates and stores (AN12413, 4.7.1.2 WriteRSAKey, up to
+// 4096 bits); FIPS 186-4 key generation, and thereby FIPS mode, starts at 2048
+pub const RSA_KEY_SIZE_MIN: u16 = 512;
+pub const RSA_KEY_SIZE_MIN_FIPS: u16 = 2048;
+pub const RSA_KEY_SIZE_MAX: u16 = 4096;
+
+// AppletConfig (AN12413, 4.3.23 Table 40) as a set of flags, one constant per
+// Se050AppletConfig value; bits unknown to the driver are kept.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AppletFeatures(u16);
+
+impl AppletFeatures {
+    pub const ECDAA: Self = Self(Se050AppletConfig::ConfigEcdaa as u16);
+    pub const ECDSA_ECDH_ECDHE: Self = Self(Se050AppletConfig::ConfigEcdsaEcdhEcdhe as u16);
+    pub const EDDSA: Self = Self(Se050AppletConfig::ConfigEddsaA as u16);
+    pub const DH_MONT: Self = Self(Se050AppletConfig::ConfigDhMont as u16);
+    pub const HMAC: Self = Self(Se050AppletConfig::ConfigHmac as u16);
+    pub const RSA_PLAIN: Self = Self(Se050AppletConfig::ConfigRsaPlain as u16);
+    pub const RSA_CRT: Self = Self(Se050AppletConfig::ConfigRsaCrt as u16);
+    pub const AES: Self = Self(Se050AppletConfig::ConfigAes as u16);
+    pub const DES: Self = Self(Se050AppletConfig::ConfigDes as u16);
+    pub const PBKDF: Self = Self(Se050AppletConfig::ConfigPbkdf as u16);
+    pub const TLS: Self = Self(Se050AppletConfig::ConfigTls as u16);
+    pub const MIFARE: Self = Self(Se050AppletConfig::ConfigMifare as u16);
+    pub const FIPS_MODE_DISABLED: Self = Self(Se050AppletConfig::ConfigFipsModeDisabled as u16);
+    pub const I2CM: Self = Self(Se050AppletConfig::ConfigI2cm as u16);
+    pub const ECC_ALL: Self = Self(Se050AppletConfig::ConfigEccAll as u16);
+    pub const RSA_ALL: Self = Self(Se050AppletConfig::ConfigRsaAll as u16);
+    pub const ALL: Self = Self(Se050AppletConfig::ConfigAll as u16);
+
+    pub const fn from_bits(bits: u16) -> Self {
+        Self(bits)
+    }
+
+    pub const fn bits(&self) -> u16 {
+        self.0
+    }
+
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    // all flags of `other` are set
+    pub const fn contains(&self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    // any flag of `other` is set
+    pub const fn intersects(&self, other: Self) -> bool {
+        self.0 & other.0 != 0
+    }
+}
+
+impl core::ops::BitOr for AppletFeatures {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        Self(self.0 | rhs.0)
+    }
+}
+
+impl core::ops::BitAnd for AppletFeatures {
+    type Output = Self;
+
+    fn bitand(self, rhs: Self) -> Self {
+        Self(self.0 & rhs.0)
+    }
+}
+
+// The algorithm groups an applet offers, one flag per AppletConfig bit (AN12413,
+// 4.3.23 Table 40). An SKU without a group rejects its commands and keys.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    pub ecdaa: bool,
+    // ECDSA, ECDH and ECDHE
+    pub ecdsa: bool,
+    pub eddsa: bool,
+    // X25519/X448 key agreement
+    pub dh_mont: bool,
+    pub hmac: bool,
+    pub rsa_plain: bool,
+    pub rsa_crt: bool,
+    pub aes: bool,
+    pub des: bool,
+    pub pbkdf: bool,
+    pub tls: bool,
+    pub mifare: bool,
+    // I2C controller commands
+    pub i2cm: bool,
+}
+
+impl Capabilities {
+    pub fn from_features(features: AppletFeatures) -> Self {
+        Capabilities {
+            ecdaa: features.contains(AppletFeatures::ECDAA),
+            ecdsa: features.contains(AppletFeatures::ECDSA_ECDH_ECDHE),
+            eddsa: features.contains(AppletFeatures::EDDSA),
+            dh_mont: features.contains(AppletFeatures::DH_MONT),
+            hmac: features.contains(AppletFeatures::HMAC),
+            rsa_plain: features.contains(AppletFeatures::RSA_PLAIN),
+            rsa_crt: features.contains(AppletFeatures::RSA_CRT),
+            aes: features.contains(AppletFeatures::AES),
+            des: features.contains(AppletFeatures::DES),
+            pbkdf: features.contains(AppletFeatures::PBKDF),
+            tls: features.contains(AppletFeatures::TLS),
+            mifare: features.contains(AppletFeatures::MIFARE),
+            i2cm: features.contains(AppletFeatures::I2CM),
+        }
+    }
+}
+
+// Free bytes per memory type, see get_memory_info; the applet reports
+// with a granularity of 16 bytes and caps the value at 0x7fff
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct MemoryInfo {
+    pub persistent: u16,
+    pub transient_reset: u16,
+    pub transient_deselect: u16,
+}
+
+// Applet lifecycle as reported by ReadState, see get_applet_lifecycle. This is the
+// state of the applet as a whole, unlike the per-object lock of the policies.
+// locked: transport lock (LockState LOCKED), only RESERVED_ID_TRANSPORT sessions work.
+// restrict_mode: RestrictMode byte as reported, 0 when the applet is unrestricted.
+// platform_scp_required: commands must come through Platform SCP03.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LifecycleState {
+    pub locked: bool,
+    pub restrict_mode: u8,
+    pub platform_scp_required: bool,
+}
+
+impl LifecycleState {
+    // usable without a transport session or Platform SCP
+    pub fn is_operational(&self) -> bool {
+        !self.locked && !self.platform_scp_required
+    }
+}
+
+// Hardening checks of strict mode (see Se050::set_strict) that the device failed.
+// Whether the Platform SCP03 keys are still the defaults is not checked: the
+// driver has no SCP03 and cannot try an authentication with them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StrictFailures {
+    // CONFIG_FIPS_MODE_DISABLED is set in the AppletConfig
+    pub fips_disabled: bool,
+    // LockState is not LOCKED
+    pub transport_unlocked: bool,
+}
+
+impl StrictFailures {
+    pub fn any(&self) -> bool {
+        self.fips_disabled || self.transport_unlocked
+    }
+}
+
+// Creation-time settings of a Secure Object, see generate_ec_key_with_config and
+// write_symm_key_with_config (AN12413, 4.7.1 WriteSecureObject P.57).
+// The origin (4.3.8) is not a parameter: the applet records it, Internal for
+// generated keys and External for written ones, see read_object_attributes.
+// Transient objects are created in transient memory and lose their value on
+// reset or deselect; the object itself stays until deleted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct KeyConfig<'a> {
+    // TLV[TAG_POLICY]; None leaves the default policy
+    pub policy: Option<&'a [u8]>,
+    // INS_TRANSIENT
+    pub transient: bool,
+    // INS_AUTH_OBJECT with TLV[TAG_MAX_ATTEMPTS]: an authentication object that
+    // blocks after this many failed attempts (0 for unlimited)
+    pub max_attempts: Option<u16>,
+}
+
+impl<'a> KeyConfig<'a> {
+    pub(crate) fn instruction(&self) -> u8 {
+        let mut ins: u8 = Se050ApduInstruction::Write.into();
+        if self.transient {
+            ins |= APDU_INSTRUCTION_TRANSIENT;
+        }
+        if self.max_attempts.is_some() {
+            ins |= APDU_INSTRUCTION_AUTH_OBJECT;
+        }
+        ins
+    }
+
+    pub(crate) fn max_attempts_bytes(&self) -> Option<[u8; 2]> {
+        self.max_attempts.map(|n| n.to_be_bytes())
+    }
+}
+
+// UserID object created by write_user_id
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct UserIdObject {
+    pub id: ObjectId,
+    // TLV[TAG_MAX_ATTEMPTS] the object was created with; None for unlimited attempts
+    pub max_attempts: Option<u16>,
+}
+
+// SE050 IoT applet, AN12413 4.4 Applet selection
+pub const SE050_APPLET_AID: [u8; 16] = [
+    0xA0, 0x00, 0x00, 0x03, 0x96, 0x54, 0x53, 0x00, 0x00, 0x00, 0x01, 0x03, 0x00, 0x00,
+    0x00, 0x00,
+];
+
 //STRUCT SE050
 #[derive(Debug)]
 pub struct Se050<T>
@@ -1043,6 +1944,11 @@ where
     t1_proto: T,
     atr_info: Option<AnswerToReset>,
     app_info: Option<Se050AppInfo>,
+    session_state: SessionState,
+    policy_preflight: bool,
+    strict: bool,
+    require_session: bool,
+    applet_aid: heapless::Vec<u8, 16>,
 }
  
 //impl- > for struct SE050 ->new function
@@ -1055,62 +1961,1130 @@ where
             t1_proto: t1,
             atr_info: None,
             app_info: None,
+            session_state: SessionState::None,
+            policy_preflight: false,
+            strict: false,
+            require_session: false,
+            applet_aid: heapless::Vec::from_slice(&SE050_APPLET_AID).unwrap(),
         }
     }
-}
-//impl- > for struct SE050 ->functions
-impl<T> Se050Device for Se050<T>
-where
-    T: T1Proto,
-{ 
-    //###########################################################################
-    //###########################################################################
-//OLD VERSION
-    fn enable(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-        /* Step 1: perform interface soft reset, parse ATR */
-        let r = self.t1_proto.interface_soft_reset(delay);
-        if r.is_err() {
-            error!("SE050 Interface Reset Error");
-            return Err(Se050Error::UnknownError);
-        }
-        self.atr_info = r.ok();
+
+    // Opt-in: check the key's object policy before sign/encrypt/decrypt, costs one
+    // ReadObject round trip but fails with PolicyViolation instead of a generic SW
+    pub fn set_policy_preflight(&mut self, enable: bool) {
+        self.policy_preflight = enable;
+    }
+
+    // Opt-in: enable() fails with PolicyNotMet, and leaves the device unusable, if the
+    // applet runs without FIPS mode or the transport lock is open (StrictFailures)
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    // Opt-in: DeleteAll fails with NotAuthenticated outside a session opened through
+    // Se050Session; off by default, a PlatformSCP03 or UserID session established
+    // without it is not tracked in session_state
+    pub fn set_require_session(&mut self, enable: bool) {
+        self.require_session = enable;
+    }
+
+    fn check_strict(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        let failures = StrictFailures {
+            fips_disabled: !self.app_info.as_ref().is_some_and(|info| info.is_fips_mode()),
+            transport_unlocked: !self.get_applet_lifecycle(delay)?.locked,
+        };
+        if failures.any() {
+            error!("SE050 strict mode: {:?}", failures);
+            self.atr_info = None;
+            return Err(Se050Error::PolicyNotMet(failures));
+        }
+        Ok(())
+    }
+
+    fn preflight_policy(&mut self, objectidentifier: &[u8;4], required: Se050Objectpolicies, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        if self.policy_preflight {
+            self.check_object_policy(objectidentifier, required, delay)?;
+        }
+        Ok(())
+    }
+
+    pub fn session_state(&self) -> SessionState {
+        self.session_state
+    }
+
+    // from the SELECT response, None before enable()
+    pub fn app_info(&self) -> Option<&Se050AppInfo> {
+        self.app_info.as_ref()
+    }
+
+    pub fn has_securebox(&self) -> bool {
+        self.app_info.as_ref().is_some_and(|info| info.has_securebox())
+    }
+
+    // for operations relying on the SecureBox
+    pub fn require_securebox(&self) -> Result<(), Se050Error> {
+        if !self.has_securebox() {
+            error!("SE050 SecureBox not available");
+            return Err(Se050Error::SecureBoxUnavailable);
+        }
+        Ok(())
+    }
+
+    // true once the T=1 interface soft reset of enable() (or recover()) went through
+    pub fn is_enabled(&self) -> bool {
+        self.atr_info.is_some()
+    }
+
+    // for the Se050Device commands: without the interface reset the T=1 sequence
+    // numbers are unknown and the card answers with low-level errors
+    pub(crate) fn require_enabled(&self) -> Result<(), Se050Error> {
+        if !self.is_enabled() {
+            error!("SE050 not initialized, call enable() first");
+            return Err(Se050Error::NotInitialized);
+        }
+        Ok(())
+    }
+
+    // DES needs CONFIG_DES; before enable() the applet configuration is unknown
+    // and the check is left to the card
+    fn require_des(&self) -> Result<(), Se050Error> {
+        if self.app_info.as_ref().is_some_and(|info| !info.has_des()) {
+            error!("SE050 DES not available (CONFIG_DES disabled)");
+            return Err(Se050Error::DesUnavailable);
+        }
+        Ok(())
+    }
+
+    // as require_des, for CONFIG_MIFARE
+    pub(crate) fn require_mifare(&self) -> Result<(), Se050Error> {
+        if self.app_info.as_ref().is_some_and(|info| !info.capabilities().mifare) {
+            error!("SE050 MIFARE DESFire not available (CONFIG_MIFARE disabled)");
+            return Err(Se050Error::MifareUnavailable);
+        }
+        Ok(())
+    }
+
+    // As require_des, for the RSA key size: fails before a key generation that the
+    // applet would reject (after seconds of computation)
+    fn check_rsa_key_size(&self, bits: u16) -> Result<(), Se050Error> {
+        let Some(info) = self.app_info.as_ref() else {
+            return Ok(());
+        };
+        let (min, max) = info.rsa_key_size_range().ok_or_else(|| {
+            error!("SE050 RSA not available (CONFIG_RSA_PLAIN and CONFIG_RSA_CRT disabled)");
+            Se050Error::RsaUnavailable })?;
+        if bits < min || bits > max {
+            error!("SE050 RSA key size {} outside {}..={}", bits, min, max);
+            return Err(Se050Error::RsaKeySizeUnsupported);
+        }
+        Ok(())
+    }
+
+    // 8-byte single DES, 16- or 24-byte 3DES keys
+    pub(crate) fn check_des_key(&self, key: &[u8]) -> Result<DesKeyType, Se050Error> {
+        let keytype = DesKeyType::from_key_len(key.len()).ok_or_else(|| {
+            error!("SE050 DES key: {} bytes is not 8, 16 or 24", key.len());
+            Se050Error::UnknownError })?;
+        self.require_des()?;
+        if keytype == DesKeyType::SingleDes {
+            self.require_not_fips()?;
+        }
+        Ok(keytype)
+    }
+
+    pub(crate) fn check_des_mode(&self, ciphermode: Se050CipherModeconstants) -> Result<(), Se050Error> {
+        if ciphermode.is_des() {
+            self.require_des()?;
+        }
+        Ok(())
+    }
+
+    // Like the DES check, based on the SELECT response: before enable() the
+    // mode is unknown and the card has the last word.
+    fn require_not_fips(&self) -> Result<(), Se050Error> {
+        if self.app_info.as_ref().is_some_and(|info| info.is_fips_mode()) {
+            error!("SE050 algorithm not allowed in FIPS mode");
+            return Err(Se050Error::FipsRestricted);
+        }
+        Ok(())
+    }
+
+    // SHA-1 signature generation is not approved in FIPS mode
+    pub(crate) fn check_fips_signature_algo(&self, signaturealgo: u8) -> Result<(), Se050Error> {
+        let sha1 = [
+            Se050ECSignatureAlgo::SigEcdsaSha as u8,
+            Se050RSASignatureAlgo::RsaSha1Pkcs1 as u8,
+            Se050RSASignatureAlgo::RsaSha1Pkcs1Pss as u8,
+        ];
+        if sha1.contains(&signaturealgo) {
+            self.require_not_fips()?;
+        }
+        Ok(())
+    }
+
+    pub(crate) fn set_session_state(&mut self, state: SessionState) {
+        self.session_state = state;
+    }
+
+    //###########################################################################
+    // See AN12413, //4.19 Generic management commands //4.19.4 GetRandom P110-11
+    // Fixed-size nonce from get_random; N is checked at compile time against what
+    // a single GetRandom response can carry.
+
+    pub fn get_nonce<const N: usize>(&mut self, delay: &mut DelayWrapper) -> Result<[u8; N], Se050Error> {
+        const { assert!(N > 0 && N <= MAX_RANDOM_LEN, "nonce length must be 1..=MAX_RANDOM_LEN") };
+        let mut nonce = [0u8; N];
+        self.get_random(&mut nonce, delay)?;
+        Ok(nonce)
+    }
+
+    //###########################################################################
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    // Reads the version info again and stores it as app_info().
+    //Response TLV[TAG_1] 7-byte version info
+
+    #[inline(never)]
+    fn refresh_app_info(&mut self, delay: &mut DelayWrapper) -> Result<&Se050AppInfo, Se050Error> {
+        let capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Version.into(),
+            Some(0x00)
+        );
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 64] = [0; 64];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_version Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 get_version Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+        let info = Se050AppInfo::from_version_info(tlv1_ret.get_data()).ok_or_else(|| {
+            error!("SE050 get_version Length Mismatch");
+            Se050Error::MissingResponseTlv })?;
+
+        Ok(self.app_info.insert(info))
+    }
+
+    //###########################################################################
+    // See AN12413, //4.7.4 ManageSecureObject //4.7.4.4 CheckObjectExists P.69
+    // First identifier at or above `start` that holds no Secure Object, probing
+    // at most MAX_OBJECT_ID_PROBES identifiers and never entering the reserved
+    // range (0x7fff0000 and up). The id is only free at the time of the probe:
+    // nothing is reserved on the device until an object is written to it.
+
+    #[inline(never)]
+    pub fn find_free_object_id(&mut self, start: u32, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        let end = start.saturating_add(MAX_OBJECT_ID_PROBES).min(OBJECT_ID_RESERVED_START);
+        for id in start..end {
+            let mut objectid = [0u8; 4];
+            BE::write_u32(&mut objectid, id);
+            let mut exists: [u8; 1] = [0];
+            self.check_object_exists(&mut exists, &objectid, delay)?;
+            if exists[0] != Se050Resultconstants::ResultSuccess as u8 {
+                debug!("SE050 find_free_object_id OK");
+                return Ok(ObjectId(objectid));
+            }
+        }
+
+        error!("SE050 find_free_object_id: no free id in {:x}..{:x}", start, end);
+        Err(Se050Error::UnknownError)
+    }
+
+    //###########################################################################
+    //See AN12413 , 4.5 Session management // 4.5.1.2 ExchangeSessionData P.49 // 3.7.3 Session policies
+    // Sets several session policies at once: the entries are combined into the one
+    // session policy of TLV[TAG_1], see encode_session_policy.
+
+    #[inline(never)]
+    pub fn exchange_session_policies(&mut self, policies: &[SessionPolicy], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        let data = encode_session_policy(policies)?;
+        self.exchange_session_data(&data, delay)
+    }
+
+    //###########################################################################
+    // ISO 7816-4 GET DATA, handled by the card manager: the tag goes into P1/P2
+    // (e.g. 0x0066 card data, 0x00cf identification data) and the raw response
+    // data is copied into `out`. Returns the number of bytes written.
+
+    #[inline(never)]
+    pub fn get_data(&mut self, tag: u16, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        let get_data_apdu = RawCApdu {
+            cla: ApduClass::StandardPlain,
+            ins: ApduStandardInstruction::GetData.into(),
+            p1: (tag >> 8) as u8,
+            p2: tag as u8,
+            data: &[],
+            le: Some(0),
+        };
+        self.t1_proto.send_apdu_raw(&get_data_apdu, delay).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu_raw(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_data {:x} Failed: {:x}", tag, rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        if rapdu.data.len() > out.len() {
+            error!("SE050 get_data Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..rapdu.data.len()].copy_from_slice(rapdu.data);
+
+        debug!("SE050 get_data OK");
+        Ok(rapdu.data.len())
+    }
+
+    //###########################################################################
+    // OEF (orderable entity) identifier of the chip, the 2-byte code that tells the
+    // SE050 variants apart; NXP AN12436 (SE050 configurations) lists the values.
+    // Read with the card manager's GET DATA for the identification data (P1/P2
+    // 0x00fe, tag DF28):
+    // FE len DF28 len, then the identification data with the OEF at OEF_ID_OFFSET.
+
+    #[inline(never)]
+    pub fn get_oef(&mut self, delay: &mut DelayWrapper) -> Result<u16, Se050Error> {
+        self.require_enabled()?;
+        let get_data_apdu = RawCApdu {
+            cla: ApduClass::ProprietaryPlain,
+            ins: ApduStandardInstruction::GetData.into(),
+            p1: 0x00,
+            p2: 0xfe,
+            data: &[0xdf, 0x28],
+            le: Some(0),
+        };
+        self.t1_proto.send_apdu_raw(&get_data_apdu, delay).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu_raw(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_oef Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let oef = identification_oef(rapdu.data).ok_or_else(|| {
+            error!("SE050 get_oef Identification Data Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        debug!("SE050 get_oef OK: {:04x}", oef);
+        Ok(oef)
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 2-byte offset
+    //TLV[TAG_3] 2-byte length
+
+    #[inline(never)]
+    fn read_binary_chunk(&mut self, objectidentifier: &[u8;4], offset: usize, len: usize, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let mut file_offset: [u8; 2] = [0; 2];
+        BE::write_u16(&mut file_offset, offset as u16);
+        let mut length: [u8; 2] = [0; 2];
+        BE::write_u16(&mut length, len as u16);
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &file_offset).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, &length).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Read.into(),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            response_le(tlv_response_len(len))
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_binary_chunk Failed at {}: {:x}", offset, rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 read_binary_chunk Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let data = tlv1_ret.get_data();
+        if data.is_empty() || data.len() > len || data.len() > out.len() {
+            error!("SE050 read_binary_chunk Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+        out[0..data.len()].copy_from_slice(data);
+
+        Ok(data.len())
+    }
+
+    //###########################################################################
+    //See AN12413, // 4.19 Generic management commands //4.19.3 GetFreeMemory P.109
+    //TLV[TAG_1] 1-byte Memory
+    // Response TLV[TAG_1] 2-byte free memory
+
+    #[inline(never)]
+    fn read_free_memory(&mut self, memory: Se050ApduMemoryType, delay: &mut DelayWrapper) -> Result<u16, Se050Error> {
+        let memory = [memory as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &memory).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Memory.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_free_memory Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 get_free_memory Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+        if tlv1_ret.get_data().len() != 2 {
+            error!("SE050 get_free_memory Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+
+        Ok(BE::read_u16(tlv1_ret.get_data()))
+    }
+
+    //###########################################################################
+    //See AN12413, // 4.19 Generic management commands
+    // Management read without command data, TLV[TAG_1] of the response is copied to `out`
+
+    #[inline(never)]
+    fn read_mgmt_info(&mut self, p2: Se050ApduP2, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            p2.into(),
+            Some(0x00)
+        );
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_mgmt_info {:x} Failed: {:x}", capdu.p2, rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 read_mgmt_info Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let data = tlv1_ret.get_data();
+        if data.len() > out.len() {
+            error!("SE050 read_mgmt_info Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..data.len()].copy_from_slice(data);
+
+        Ok(data.len())
+    }
+
+    //###########################################################################
+    //See AN12413, 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60
+    //TLV[TAG_1] 4-byte identifier of the target key object.
+    //TLV[TAG_2] 4-byte KEK identifier, the value is RFC3394 wrapped with it.
+    //TLV[TAG_3] Byte array containing the wrapped key value.
+
+    #[inline(never)]
+    pub fn unwrap_key(&mut self, kek: ObjectId, target: ObjectId, keytype: SymmKeyType, wrapped: &[u8], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        check_key_wrap_len(wrapped.len().saturating_sub(KEY_WRAP_OVERHEAD))?;
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &target.0).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &kek.0).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, wrapped).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Write.into(),
+            keytype.p1().into(),
+            Se050ApduP2::Default.into(),
+            None
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 unwrap_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 unwrap_key OK");
+        Ok(ObjectId(target.0))
+    }
+
+    //###########################################################################
+    //See AN12413 //4.12 Crypto operations AES/DES //4.12.4 CipherOneShot P.87
+    // As cipher_one_shot_encrypt, but returns the output: the length written to `output`.
+    // Output is ciphertext only; no CipherMode yields an authentication tag.
+    //TLV[TAG_1] 4-byte identifier of the key object.
+    //TLV[TAG_2] 1-byte CipherMode
+    //TLV[TAG_3] Byte array containing input data.
+    //TLV[TAG_4] Byte array containing an initialization vector. [Optional, omitted if empty]
+
+    #[inline(never)]
+    pub(crate) fn cipher_one_shot_encrypt_into(&mut self, keyobjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants, inputdata: &[u8], initializationvector: &[u8], output: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.cipher_one_shot_into(Se050ApduP2::Encrypt, keyobjectidentifier, ciphermode, inputdata, initializationvector, output, delay)
+    }
+
+    // As cipher_one_shot_decrypt, but returns the length of the plaintext written to `output`.
+    #[inline(never)]
+    pub(crate) fn cipher_one_shot_decrypt_into(&mut self, keyobjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants, inputdata: &[u8], initializationvector: &[u8], output: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.cipher_one_shot_into(Se050ApduP2::Decrypt, keyobjectidentifier, ciphermode, inputdata, initializationvector, output, delay)
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn cipher_one_shot_into(&mut self, p2: Se050ApduP2, keyobjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants, inputdata: &[u8], initializationvector: &[u8], output: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let required = match p2 {
+        Se050ApduP2::Decrypt => Se050Objectpolicies::PolicyObjAllowDec,
+        _ => Se050Objectpolicies::PolicyObjAllowEnc,
+        };
+        let p2: u8 = p2.into();
+        check_cipher_iv(ciphermode, initializationvector)?;
+        self.check_des_mode(ciphermode)?;
+        self.preflight_policy(keyobjectidentifier, required, delay)?;
+        let ciphermode: &[u8] = &[ciphermode.into()];
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ciphermode).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, initializationvector).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Cipher.into(),
+            p2,
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+        if !initializationvector.is_empty() {
+            capdu.push(tlv4);
+        }
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 cipher_one_shot {:x} Failed: {:x}", p2, rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 cipher_one_shot {:x} Return TLV Missing", p2);
+            Se050Error::MissingResponseTlv })?;
+
+        let enc = tlv1_ret.get_data();
+        if enc.len() > output.len() {
+            error!("SE050 cipher_one_shot {:x} Buffer Too Small", p2);
+            return Err(Se050Error::UnknownError);
+        }
+        output[0..enc.len()].copy_from_slice(enc);
+
+        Ok(enc.len())
+    }
+
+    //###########################################################################
+    //See AN12413 //4.13 Message Authentication Codes //4.13.3 MACFinal P.89
+    // MACFinal on a crypto object set up with P2_GENERATE; writes the MAC to `mac`
+    // and returns its length.
+    //TLV[TAG_1] Byte array containing data to be taken as input to MAC.
+    //TLV[TAG_2] 2-byte Crypto Object identifier
+
+    #[inline(never)]
+    pub(crate) fn mac_final_into(&mut self, macdatainput: &[u8], cryptoobjectidentifier: &[u8;2], mac: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, macdatainput).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::MAC.into(),
+            Se050ApduP2::Final.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 mac_final Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        copy_mac(&rapdu, mac)
+    }
+
+    //###########################################################################
+    //See AN12413 //4.13 Message Authentication Codes //4.13.4 MACOneShot P.90
+    // MACOneShot with P2_GENERATE_ONESHOT; writes the MAC to `mac` and returns its length.
+    //TLV[TAG_1] 4-byte identifier of the key object.
+    //TLV[TAG_2] 1-byte MACAlgo
+    //TLV[TAG_3] Byte array containing data to be taken as input to MAC.
+
+    #[inline(never)]
+    pub fn mac_one_shot_into(&mut self, keyobjectidentifier: &[u8;4], macalgo: Se050MACAlgoconstants, datainputtomac: &[u8], mac: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let macalgo: &[u8] = &[macalgo.into()];
+        self.require_enabled()?;
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, macalgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, datainputtomac).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::MAC.into(),
+            Se050ApduP2::GenerateOneshot.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 mac_one_shot Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        copy_mac(&rapdu, mac)
+    }
+
+    // T=1 interface soft reset, stores the parsed ATR
+    pub(crate) fn soft_reset(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        let r = self.t1_proto.interface_soft_reset(delay);
+        if r.is_err() {
+            error!("SE050 Interface Reset Error");
+            return Err(Se050Error::UnknownError);
+        }
+        self.atr_info = r.ok();
         debug!("SE050 ATR: {:?}", self.atr_info.as_ref().unwrap());
+        Ok(())
+    }
+
+    //###########################################################################
+    //See AN12413, 4.5.1.5 CloseSession P.50: after a failed CloseSession the applet
+    // needs to be reselected. Call recover() when a command fails with a T1Error
+    // (checksum, timeout, unexpected block: the T=1 state is unknown), after a failed
+    // CloseSession or session authentication, or when commands that used to work
+    // keep failing with an unexpected SW. It performs the interface soft reset and
+    // GP SELECT (of the AID from select_applet, if any) again; any session is gone.
+
+    pub fn recover(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.session_state = SessionState::None;
+        self.soft_reset(delay)?;
+        self.select(delay)?;
+        debug!("SE050 recover OK");
+        Ok(())
+    }
+
+    //###########################################################################
+    //See AN12413, 4.4 Applet selection P.47-48
+    // GP SELECT of a custom applet or another instance AID (5 to 16 bytes, ISO 7816-4),
+    // e.g. a customer-specific applet instance. The AID is kept for later enable() calls.
+
+    pub fn select_applet(&mut self, aid: &[u8], delay: &mut DelayWrapper) -> Result<SelectStatus, Se050Error> {
+        if aid.len() < 5 {
+            error!("SE050 select_applet: AID too short");
+            return Err(Se050Error::UnknownError);
+        }
+        self.applet_aid = heapless::Vec::from_slice(aid).map_err(|_| {
+            error!("SE050 select_applet: AID too long");
+            Se050Error::UnknownError })?;
+        self.select(delay)
+    }
+
+    pub fn applet_aid(&self) -> &[u8] {
+        &self.applet_aid
+    }
+
+    //###########################################################################
+    //See AN12413, //  4.4 Applet selection P.47-48
+    // GP SELECT of the SE050 IoT applet, stores the version info from the response
+
+    #[inline(never)]
+    pub(crate) fn select(&mut self, delay: &mut DelayWrapper) -> Result<SelectStatus, Se050Error> {
+        let app_select_apdu = RawCApdu {
+            cla: ApduClass::StandardPlain,
+            ins: ApduStandardInstruction::SelectFile.into(),
+            p1: 0x04,
+            p2: 0x00,
+            data: &self.applet_aid,
+            le: Some(0),
+        };
+        self.t1_proto.send_apdu_raw(&app_select_apdu, delay).map_err(|_| Se050Error::UnknownError)?;
+
+        // newer applets append more app info, only the first 7 bytes are parsed
+        let mut appid_data: [u8; 64] = [0; 64];
+        let appid_apdu = self.t1_proto
+            .receive_apdu_raw(&mut appid_data, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let adata = appid_apdu.data;
+        let asw = appid_apdu.sw;
+        let status = SelectStatus::from_sw(asw);
+        if status.is_none() || adata.len() < 7 {
+            error!("SE050 GP SELECT Err: {:?} {:x}", delog::hex_str!(adata), asw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        self.app_info = Se050AppInfo::from_version_info(adata);
+        debug!("SE050 App: {:?}", self.app_info.as_ref().unwrap());
+
+        Ok(status.unwrap())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    // As read_secure_object, for a value of unknown length, e.g. the public key
+    // of an EC key pair: returns the length written to `out`.
+    //TLV[TAG_1] 4-byte object identifier
+
+    #[inline(never)]
+    pub(crate) fn read_object_into(&mut self, objectidentifier: &[u8;4], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Read.into(),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            response_le(tlv_response_len(out.len()))
+        );
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; LARGE_RESPONSE_BUF_LEN] = [0; LARGE_RESPONSE_BUF_LEN];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_object Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 read_object Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let data = tlv1_ret.get_data();
+        if data.len() > out.len() {
+            error!("SE050 read_object Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..data.len()].copy_from_slice(data);
+
+        Ok(data.len())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.2 ExportObject // P.67
+    // Returns the length of the serialized, encrypted object written to `out`.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte RSAKeyComponent [Conditional: only for RSA key pairs]
+
+    #[inline(never)]
+    pub(crate) fn export_object_into(&mut self, objectidentifier: &[u8;4], rsakeycomponent: Option<Se050RSAKeyComponent>, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let component = rsakeycomponent.map(|c| [c.into()]);
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Read.into(),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Export.into(),
+            response_le(tlv_response_len(out.len()))
+        );
+        capdu.push(tlv1);
+        if let Some(component) = component.as_ref() {
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, component).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlv2);
+        }
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; LARGE_RESPONSE_BUF_LEN] = [0; LARGE_RESPONSE_BUF_LEN];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 export_object Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 export_object Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let data = tlv1_ret.get_data();
+        if data.len() > out.len() {
+            error!("SE050 export_object Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..data.len()].copy_from_slice(data);
+
+        Ok(data.len())
+    }
+
+    //###########################################################################
+    // Hash-and-sign: `message` is the raw message, never a digest. It is hashed
+    // with DigestOneShot (AN12413, 4.18.4 P.108) using the digest `ecsignaturealgo`
+    // names, and the digest is signed with ECDSASign (4.10.1.1 P.76-77), so the
+    // algorithm and the hash always match and nothing is hashed twice.
+    // SigEcdsaPlain names no digest and is rejected; sign a caller-computed
+    // digest with ecdsa_sign instead.
+    // Returns the length of the ASN.1 signature written to `signature`.
+
+    #[inline(never)]
+    pub fn ecdsa_sign_message(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, message: &[u8], signature: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        let digestmode = ecsignaturealgo.digest_mode().ok_or_else(|| {
+            error!("SE050 ecdsa_sign_message: SigEcdsaPlain names no digest");
+            Se050Error::UnknownError })?;
+        // refuse SHA-1 before the message goes out for hashing
+        self.check_fips_signature_algo(ecsignaturealgo.into())?;
+
+        let mut digest = [0u8; 64];
+        let digest_len = self.digest(digestmode, message, &mut digest, delay)?;
+        let len = self.ecdsa_sign(eckeyidentifier, ecsignaturealgo, &digest[0..digest_len], signature, delay)?;
+
+        debug!("SE050 ecdsa_sign_message OK");
+        Ok(len)
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.10 Crypto operations EC // 4.10.2 Signature verification // 4.10.2.1 ECDSAVerify P.79
+    // As ecdsa_verify, but evaluates the Result in the response: Ok(false) for a bad signature.
+    //TLV[TAG_1]  4-byte identifier of EC key pair or public key.
+    //TLV[TAG_2] 1-byte ECSignatureAlgo
+    //TLV[TAG_3] Byte array containing hashed data to compare.
+    //TLV[TAG_5]  Byte array containing ASN.1 signature
+
+    #[inline(never)]
+    pub(crate) fn ecdsa_verify_result(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, hashedcomparedata: &[u8], asn1signaturedata: &[u8], delay: &mut DelayWrapper) -> Result<bool, Se050Error> {
+        let ecsignaturealgo: &[u8] = &[ecsignaturealgo.into()];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ecsignaturealgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, hashedcomparedata).map_err(|_| Se050Error::UnknownError)?;
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, asn1signaturedata).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Signature.into(),
+            Se050ApduP2::Verify.into(),
+            Some(0x03)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+        capdu.push(tlv5);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 ecdsa_verify Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 ecdsa_verify Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        Ok(tlv1_ret.get_data() == [Se050Resultconstants::ResultSuccess as u8])
+    }
+
+    // for commands that are only accepted within an authenticated session,
+    // checked only when set_require_session() is on
+    fn require_authenticated(&self) -> Result<(), Se050Error> {
+        if self.require_session && self.session_state == SessionState::None {
+            error!("SE050 no authenticated session");
+            return Err(Se050Error::NotAuthenticated);
+        }
+        Ok(())
+    }
 
-        /* Step 2: send GP SELECT to choose SE050 JCOP APP, parse APP version */
-        let app_id: [u8; 16] = [
-            0xA0, 0x00, 0x00, 0x03, 0x96, 0x54, 0x53, 0x00, 0x00, 0x00, 0x01, 0x03, 0x00, 0x00,
-            0x00, 0x00,
-        ];
-        let app_select_apdu = RawCApdu {
-            cla: ApduClass::StandardPlain,
-            ins: ApduStandardInstruction::SelectFile.into(),
-            p1: 0x04,
-            p2: 0x00,
-            data: &app_id,
-            le: Some(0),
-        };
-        self.t1_proto.send_apdu_raw(&app_select_apdu, delay).map_err(|_| Se050Error::UnknownError)?;
+    //###########################################################################
+    //See AN12413, 4.5 Session management // 4.5.1 Generic session commands //4.5.1.1 create_session P.48
+    // Creates a session and returns a handle for it; commands issued through the handle
+    // are wrapped in ProcessSessionCmd. The session is closed when the handle is
+    // closed or dropped.
+    // TLV[TAG_1] 8-byte session identifier (response)
 
-        let mut appid_data: [u8; 11] = [0; 11];
-        let appid_apdu = self.t1_proto
-            .receive_apdu_raw(&mut appid_data, delay)
+    #[inline(never)]
+    pub fn open_session<'a>(&'a mut self, authobjectidentifier: &[u8;4], delay: &'a mut DelayWrapper) -> Result<Se050Session<'a, T>, Se050Error> {
+        self.open_session_with_scratch(authobjectidentifier, delay)
+    }
+
+    // As open_session, with an N-byte response buffer in the handle; responses
+    // that don't fit fail to receive.
+    #[inline(never)]
+    pub fn open_session_with_scratch<'a, const N: usize>(&'a mut self, authobjectidentifier: &[u8;4], delay: &'a mut DelayWrapper) -> Result<Se050Session<'a, T, N>, Se050Error> {
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, authobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::SessionCreate.into(),
+            Some(0x0C)
+        );
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
             .map_err(|_| Se050Error::UnknownError)?;
 
-        let adata = appid_apdu.data;
-        let asw = appid_apdu.sw;
-        if asw != 0x9000 || adata.len() != 7 {
-            error!("SE050 GP SELECT Err: {:?} {:x}", delog::hex_str!(adata), asw);
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 open_session Failed: {:x}", rapdu.sw);
             return Err(Se050Error::UnknownError);
         }
 
-        self.app_info = Some(Se050AppInfo {
-            applet_version: BE::read_uint(&adata[0..3], 3) as u32,
-            features: BE::read_u16(&adata[3..5]),
-            securebox_version: BE::read_u16(&adata[5..7]),
-        });
-        debug!("SE050 App: {:?}", self.app_info.as_ref().unwrap());
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 open_session Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
 
-        Ok(())
+        let session_id: [u8; 8] = tlv1_ret.get_data().try_into().map_err(|_| {
+            error!("SE050 open_session Session ID Length Mismatch");
+            Se050Error::MissingResponseTlv })?;
+
+        debug!("SE050 open_session OK");
+        Ok(Se050Session::new(self, delay, session_id))
+    }
+
+    //###########################################################################
+    //See AN12413 , 4.5 Session management // 4.5.1 Generic session commands /4.5.1.3 process_session_cmd P.49-50
+    // Sends `inner` as is, or with a session wrapped in ProcessSessionCmd:
+    //TLV[SESSION_ID] 8-byte session identifier
+    //TLV[TAG_1] the serialized inner command
+    // The returned RApdu is the response of the inner command; the caller checks its SW.
+
+    #[inline(never)]
+    pub fn process<'b>(&mut self, inner: &CApdu, session: Option<&SessionId>, rapdu_buf: &'b mut [u8], delay: &mut DelayWrapper) -> Result<RApdu<'b>, Se050Error> {
+        let Some(session) = session else {
+            self.t1_proto
+                .send_apdu(inner, delay)
+                .map_err(|_| Se050Error::UnknownError)?;
+
+            return self.t1_proto
+                .receive_apdu(rapdu_buf, delay)
+                .map_err(|_| Se050Error::UnknownError);
+        };
+
+        let mut apducommand: heapless::Vec<u8, MAX_SESSION_APDU_LEN> = heapless::Vec::new();
+        for b in inner.byte_iter() {
+            apducommand.push(b).map_err(|_| {
+                error!("SE050 process Command Too Long");
+                Se050Error::UnknownError })?;
+        }
+
+        let tlvtgsid = SimpleTlv::with_tag(Se050TlvTag::SessionID, &session.0).map_err(|_| Se050Error::UnknownError)?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &apducommand).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Process) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            Some(0)
+        );
+
+        capdu.push(tlvtgsid);
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        self.t1_proto
+            .receive_apdu(rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)
+    }
+
+    pub(crate) fn process_in_session<'b>(&mut self, session_id: &[u8;8], capdu: &CApdu, rapdu_buf: &'b mut [u8], delay: &mut DelayWrapper) -> Result<RApdu<'b>, Se050Error> {
+        self.process(capdu, Some(&SessionId(*session_id)), rapdu_buf, delay)
+    }
+}
+
+// OEF identifier in the identification data (tag DF28) returned by get_oef
+pub(crate) const OEF_ID_OFFSET: usize = 2;
+
+// value of DF28 inside FE, both with one-byte BER lengths
+pub(crate) fn identification_oef(data: &[u8]) -> Option<u16> {
+    let (&tag, rest) = data.split_first()?;
+    let (&len, rest) = rest.split_first()?;
+    if tag != 0xfe || rest.len() < len as usize {
+        return None;
+    }
+    let rest = &rest[0..len as usize];
+    if rest.len() < 3 || rest[0..2] != [0xdf, 0x28] || rest.len() < 3 + rest[2] as usize {
+        return None;
+    }
+    let id = &rest[3..3 + rest[2] as usize];
+    let oef = id.get(OEF_ID_OFFSET..OEF_ID_OFFSET + 2)?;
+    Some(BE::read_u16(oef))
+}
+
+// identifiers find_free_object_id probes before giving up
+pub const MAX_OBJECT_ID_PROBES: u32 = 64;
+// AN12413, Object identifiers: 0x7fff0000 - 0x7fffffff are reserved for the applet
+const OBJECT_ID_RESERVED_START: u32 = 0x7fff_0000;
+
+// Upper bound for a command wrapped in ProcessSessionCmd
+const MAX_SESSION_APDU_LEN: usize = 512;
+// GetRandom response in the 260-byte receive buffer: TLV header (3), data, SW (2),
+// and the CRC of the last T=1 frame, which is read in behind the data (2)
+pub const MAX_RANDOM_LEN: usize = 253;
+// ReadObject data per response, same bound as for GetRandom
+const READ_BINARY_CHUNK: usize = MAX_RANDOM_LEN;
+// Receive buffer of the commands whose response may not fit a short Le (ReadObject,
+// ExportObject, attestation); 4 bytes of it go to the SW and the trailing CRC
+const LARGE_RESPONSE_BUF_LEN: usize = 1024;
+
+// Le for a response of up to `len` data bytes: the short Le 0x00 while 256 bytes
+// cover it, else an extended Le (making the whole command extended, ISO7816-4 5.1),
+// without which the card truncates the response to 256 bytes.
+// Capped to what LARGE_RESPONSE_BUF_LEN holds.
+pub(crate) fn response_le(len: usize) -> Option<usize> {
+    let len = len.min(LARGE_RESPONSE_BUF_LEN - 4);
+    Some(if len <= 256 { 0 } else { len })
+}
+
+// response data of a single TLV carrying `len` bytes: tag, length field, value
+pub(crate) fn tlv_response_len(len: usize) -> usize {
+    let length_field = match len {
+    0..=127 => 1,
+    128..=255 => 2,
+    _ => 3,
+    };
+    1 + length_field + len
+}
+
+//impl- > for struct SE050 ->functions
+impl<T> Se050Device for Se050<T>
+where
+    T: T1Proto,
+{ 
+    //###########################################################################
+    //###########################################################################
+//OLD VERSION
+    fn enable(&mut self, delay: &mut DelayWrapper) -> Result<SelectStatus, Se050Error> {
+        /* Step 1: perform interface soft reset, parse ATR */
+        self.soft_reset(delay)?;
+
+        /* Step 2: send GP SELECT to choose SE050 JCOP APP (or the AID from select_applet), parse APP version */
+        let status = self.select(delay)?;
+
+        /* Step 3: in strict mode, check the configuration */
+        if self.strict {
+            self.check_strict(delay)?;
+        }
+        Ok(status)
     }
 
 
@@ -1158,7 +3132,8 @@ where
     #[inline(never)]
     fn create_session(&mut self,  authobjectidentifier:  &[u8;4],   delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), authobjectidentifier);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, authobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
     
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1194,7 +3169,8 @@ where
 
     #[inline(never)]
     fn exchange_session_data(&mut self, session_policies: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &session_policies);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, session_policies).map_err(|_| Se050Error::UnknownError)?;
     
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1230,9 +3206,10 @@ where
 
      #[inline(never)]     
      fn process_session_cmd(&mut self,apducommand : &[u8], session_id : &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+         self.require_enabled()?;
 
-         let tlvtgsid = SimpleTlv::new(Se050TlvTag::SessionID.into(), &session_id);	
-         let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &apducommand);
+         let tlvtgsid = SimpleTlv::with_tag(Se050TlvTag::SessionID, session_id).map_err(|_| Se050Error::UnknownError)?;	
+         let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, apducommand).map_err(|_| Se050Error::UnknownError)?;
         
          let mut capdu = CApdu::new(
              ApduClass::ProprietaryPlain,
@@ -1265,11 +3242,15 @@ where
  
     //###########################################################################
     //See AN12413 , 4.5 Session management // 4.5.1 Generic session commands //4.5.1.4 refresh_session P.50
+    // The applet answers with the SW only: there is no policy or validity window to
+    // return, the new policy applies as sent. Response data means the command was not
+    // taken as RefreshSession and is rejected.
 
     #[inline(never)]        
     fn refresh_session(&mut self,policy: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-    let tlvtgsid = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
+    let tlvtgsid = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
     
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1287,7 +3268,7 @@ where
 
         let mut rapdu_buf: [u8; 16] = [0; 16];
         let rapdu = self.t1_proto
-            .receive_apdu(&mut rapdu_buf, delay)
+            .receive_apdu_raw(&mut rapdu_buf, delay)
             .map_err(|_| Se050Error::UnknownError)?;
 
         if rapdu.sw != 0x9000 {
@@ -1295,6 +3276,11 @@ where
             return Err(Se050Error::UnknownError);
         }
 
+        if !rapdu.data.is_empty() {
+            error!("SE050 refresh_session: {} unexpected response bytes", rapdu.data.len());
+            return Err(Se050Error::UnknownError);
+        }
+
         debug!("SE050 refresh_session OK");
         Ok(())
     }
@@ -1309,6 +3295,7 @@ where
     #[inline(never)]
     #[allow(unused_mut)]
     fn close_session(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
     
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1332,6 +3319,7 @@ where
             return Err(Se050Error::UnknownError);
         }
 
+        self.session_state = SessionState::None;
         debug!("SE050close_session OK");
         Ok(())
     }
@@ -1343,7 +3331,8 @@ where
 
     #[inline(never)] 
     fn verify_session_user_id(&mut self, user_idvalue: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &user_idvalue);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, user_idvalue).map_err(|_| Se050Error::UnknownError)?;
         
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1365,9 +3354,10 @@ where
 
         if rapdu.sw != 0x9000 {
             error!("SE050 verify_session_user_id Failed: {:x}", rapdu.sw);
-            return Err(Se050Error::UnknownError);
+            return Err(verification_error(rapdu.sw));
         }
 
+        self.session_state = SessionState::UserId;
         debug!("SE050 verify_session_user_id OK");
         Ok(())
     }
@@ -1405,6 +3395,33 @@ where
 
     */ 
 
+    //###########################################################################
+    //GPC_SPE_034 11.8 PUT KEY // [SCP03] key rotation
+    // Replaces the static ENC/MAC/DEK key set, e.g. to move away from the default
+    // SCP03 keys before deployment. Must be sent over an authenticated SCP03 channel
+    // (C-MAC and C-DECRYPTION), with each new key encrypted under the DEK and
+    // followed by its 3-byte key check value.
+    // Blocked on SCPInitializeUpdate/SCPExternalAuthenticate and on secure messaging
+    // in the transport: only plain APDUs can be sent today.
+
+    /*
+    TO-DO
+    CLA 0x84
+    INS 0xD8
+    P1  KVN to replace (0x00 to add a new key set)
+    P2  0x81 (key identifier 1, multiple keys)
+    Data: new KVN, then for ENC, MAC, DEK: key type 0x88 (AES), length, encrypted key, KCV length 0x03, KCV
+    Response: new KVN followed by the three KCVs
+
+    */ 
+
+    //###########################################################################
+    //[SCP03] 6.2.2 Encryption counter // remaining sequence counter values
+    // Secure messaging increments a counter per command (it feeds the ICV of the
+    // C-DECRYPTION padding); the session has to be re-established before it wraps.
+    // A getter for the values left belongs on the SCP03 session type, which does
+    // not exist yet: it comes with SCPInitializeUpdate/SCPExternalAuthenticate above.
+
 
     //###########################################################################
     //###########################################################################
@@ -1424,8 +3441,9 @@ where
 
     #[inline(never)]    
     fn eckey_session_internal_authenticate(&mut self, input_data: &[u8;2], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-            let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), input_data);
+            let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, input_data).map_err(|_| Se050Error::UnknownError)?;
             
             let mut capdu = CApdu::new(
                 ApduClass::ProprietarySecure,
@@ -1451,6 +3469,7 @@ where
                 return Err(Se050Error::UnknownError);
             }
 
+            self.session_state = SessionState::EcKey;
             debug!("SE050 eckey_session_internal_authenticate OK");
             Ok(())
         }
@@ -1467,8 +3486,9 @@ where
     #[inline(never)]
     
     fn eckey_session_get_eckapublic_key(&mut self, input_data: &[u8;2], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), input_data);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, input_data).map_err(|_| Se050Error::UnknownError)?;
                 
         let mut capdu = CApdu::new(
             ApduClass::ProprietarySecure,
@@ -1522,10 +3542,13 @@ where
     */
 
     #[inline(never)]    
-    fn set_lock_state(&mut self, lockindicator: &[u8], lockstate: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+    fn set_lock_state(&mut self, lockindicator: Se050LockIndicatorconstants, lockstate: Se050LockStateconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        let lockindicator: &[u8] = &[lockindicator.into()];
+        let lockstate: &[u8] = &[lockstate.into()];
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &lockindicator);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &lockstate); 
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, lockindicator).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, lockstate).map_err(|_| Se050Error::UnknownError)?; 
         
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1556,6 +3579,79 @@ where
         Ok(())
     }
 
+    //###########################################################################
+    //See AN12413 , 4.6 Module management //   4.6.1 SetLockState P.54-55
+    // Sets the persistent transport lock. A locked applet refuses commands outside a
+    // session opened with RESERVED_ID_TRANSPORT, which is also needed to unlock it again.
+
+    #[inline(never)]
+    fn set_transport_state(&mut self, lock: bool, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        let lockstate = if lock { Se050LockStateconstants::LOCKED } else { Se050LockStateconstants::UNLOCKED };
+        self.set_lock_state(Se050LockIndicatorconstants::PersistentLock, lockstate, delay)
+    }
+
+    //###########################################################################
+    //See SE05x applet 3.x , Module management // ReadState (P2_STATE, not in AN12413)
+    // Returns whether the applet is transport locked, see get_applet_lifecycle.
+
+    #[inline(never)]
+    fn get_transport_state(&mut self, delay: &mut DelayWrapper) -> Result<bool, Se050Error> {
+        self.require_enabled()?;
+        let state = self.get_applet_lifecycle(delay)?;
+
+        debug!("SE050 get_transport_state OK");
+        Ok(state.locked)
+    }
+
+    //###########################################################################
+    //See SE05x applet 3.x , Module management // ReadState (P2_STATE, not in AN12413)
+    // Lifecycle of the applet: transport lock, restrict mode and Platform SCP requirement.
+    //TLV[TAG_1] 3-byte response: LockState, RestrictMode, PlatformSCPRequest
+
+    #[inline(never)]
+    fn get_applet_lifecycle(&mut self, delay: &mut DelayWrapper) -> Result<LifecycleState, Se050Error> {
+        self.require_enabled()?;
+        let capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::State.into(),
+            Some(0x00)
+        );
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_applet_lifecycle Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 get_applet_lifecycle Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let state = tlv1_ret.get_data();
+        if state.len() != 3 {
+            error!("SE050 get_applet_lifecycle Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+
+        debug!("SE050 get_applet_lifecycle OK");
+        Ok(LifecycleState {
+            locked: state[0] == Se050LockStateconstants::LOCKED as u8,
+            restrict_mode: state[1],
+            platform_scp_required: state[2] == Se050PlatformSCPRequestconstants::ScpRequired as u8,
+        })
+    }
+
     //###########################################################################
     //See AN12413 , 4.6 Module management //   4.6.2 SetPlatformSCPRequest P.55-56
     /* 
@@ -1576,6 +3672,7 @@ where
     #[inline(never)]
     #[allow(unused_mut)]
     fn set_platform_scp_request(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
         
         
         let mut capdu = CApdu::new(
@@ -1612,7 +3709,8 @@ where
 
     #[inline(never)]    
     fn set_applet_features(&mut self,applet_config: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &applet_config);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, applet_config).map_err(|_| Se050Error::UnknownError)?;
     
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1668,9 +3766,10 @@ where
 
     //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey    P.58
     //P1_EC 4.3.19 ECCurve P.42
-    fn generate_eccurve_key(&mut self,  eccurve: &[u8],delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x51, 0xae, 0x51]);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &eccurve );	// Se050ECCurveconstants
+    fn generate_eccurve_key(&mut self,  eccurve: Se050ECCurveconstants,delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        let eccurve: &[u8] = &[eccurve.into()];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &[0xae, 0x51, 0xae, 0x51]).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &eccurve ).map_err(|_| Se050Error::UnknownError)?;	// Se050ECCurveconstants
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
@@ -1706,13 +3805,14 @@ where
     //P1_EC 4.3.19 ECCurve P.42
     /*  
     #[inline(never)]
-    fn write_ec_key(&mut self,policy: &[u8],  objectid: &[u8;4], eccurve: &[u8], private_key_value: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>  
+    fn write_ec_key(&mut self,policy: &[u8],  objectid: &[u8;4], eccurve: Se050ECCurveconstants, private_key_value: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>  
         
     { 
-        let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);        
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);        
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), & eccurve);	// Se050ECCurveconstants
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &private_key_value );	 
+        let eccurve: &[u8] = &[eccurve.into()];
+        let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, &policy).map_err(|_| Se050Error::UnknownError)?;        
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;        
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, & eccurve).map_err(|_| Se050Error::UnknownError)?;	// Se050ECCurveconstants
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, &private_key_value ).map_err(|_| Se050Error::UnknownError)?;	 
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1768,10 +3868,10 @@ where
     fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
         //let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x51, 0xae, 0x51]);
        //let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x51, 0xae, 0x51]);
-     let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(),   &[ 0x01, 0xA0, 0xE8,  0x20 ] );
+     let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1,   &[ 0x01, 0xA0, 0xE8,  0x20 ] ).map_err(|_| Se050Error::UnknownError)?;
       // let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[ 0x21, 0xa0,  0xe8, 0x20] );
        //let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x51, 0xae, 0x51]);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x03]);	// NIST P-256
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x03]).map_err(|_| Se050Error::UnknownError)?;	// NIST P-256
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
@@ -1832,12 +3932,12 @@ where
      //   let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);    
        // let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(),&[0xae, 0x52, 0xae, 0x52]);     
      //   let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(),&[ 0x22, 0xa0, 0xe8, 0x20]);   
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(),   &[ 0x02, 0xA0, 0xE8,  0x20 ] );
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1,   &[ 0x02, 0xA0, 0xE8,  0x20 ] ).map_err(|_| Se050Error::UnknownError)?;
      
      
        // let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), & eccurve);	// Se050ECCurveconstants
         //let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &ID_ECC_ED_25519  );	// Se050ECCurveconstants
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x40]  );	// Se050ECCurveconstants
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x40]  ).map_err(|_| Se050Error::UnknownError)?;	// Se050ECCurveconstants
         //let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &private_key_value );	 
 
         let mut capdu = CApdu::new(
@@ -1909,10 +4009,10 @@ where
     fn generate_p256_key(&mut self,policy: &[u8],  objectid: &[u8;4],   private_key_value: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error> 
     {
 
-        let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x03]);	// NIST P-256
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &private_key_value );
+        let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, &policy).map_err(|_| Se050Error::UnknownError)?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x03]).map_err(|_| Se050Error::UnknownError)?;	// NIST P-256
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, &private_key_value ).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -1991,11 +4091,13 @@ where
    
     #[inline(never)]
     fn write_rsa_key(&mut self,policy: &[u8],  objectid: &[u8;4], keysize: &[u8;2],   delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        self.check_rsa_key_size(BE::read_u16(keysize))?;
         
-    let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
+    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
     
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), keysize );	 
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, keysize ).map_err(|_| Se050Error::UnknownError)?;	 
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -2044,10 +4146,10 @@ where
             todo!();
         }
 
-        let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), kekid);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), key);
+        let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, &policy).map_err(|_| Se050Error::UnknownError)?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, kekid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, key).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -2082,13 +4184,15 @@ where
  
 #[inline(never)]
 /* NOTE: hardcoded Object ID 0xae50ae50! */
-/* no support yet for rfc3394 key wrappings, policies or max attempts */
+/* no policies or max attempts; for an rfc3394 wrapped key see import_wrapped_aes_key */
 fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+    self.require_enabled()?;
     if key.len() != 16 {
-        todo!();
+        error!("SE050 WriteAESKey: {} byte key, only 16 supported", key.len());
+        return Err(Se050Error::UnknownError);
     }
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x50, 0xae, 0x50]);
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), key);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &[0xae, 0x50, 0xae, 0x50]).map_err(|_| Se050Error::UnknownError)?;
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, key).map_err(|_| Se050Error::UnknownError)?;
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
         Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
@@ -2122,63 +4226,16 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //NEW VERSION
  
     //########################################################################### 
-    /* NOTE: hardcoded Object ID 0xae50ae50! */
-    /* no support yet for rfc3394 key wrappings, policies or max attempts */
     //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60 
-    //P1_AES //template for 
+    //P1_AES
+    // WriteSymmKey only takes P2_DEFAULT with the key value: AN12413 specifies no way
+    // to have the SE050 generate a symmetric key. Nothing is sent; use GetRandom and
+    // write_aes_key, or import_wrapped_aes_key to keep the key off the bus.
     #[inline(never)]
-    //fn write_aes_key(&mut self,policy: &[u8], objectid: &[u8;4],kekid: &[u8;4],key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-     
-    //fn generate_aes_key(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-        fn generate_aes_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
-
-       /*   if key.len() != 16 {
-            todo!();
-        }
-*/
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xE8, 0xA0, 0x02]);
-       // let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-      // let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-      // let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), kekid);
-     // let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), key);
-
-   
-  
-   
-        let mut capdu = CApdu::new(
-            ApduClass::ProprietaryPlain,
-            Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
-            Se050ApduP1CredType::AES.into(),
-            Se050ApduP2::Default.into(),
-            Some(0)
-        );
-      //  capdu.push(tlvp);
-        capdu.push(tlv1);
-   //     capdu.push(tlv2);
-    //   capdu.push(tlv3);
-
-        self.t1_proto
-            .send_apdu(&capdu, delay)
-            .map_err(|_| Se050Error::UnknownError)?;
-
-        let mut rapdu_buf: [u8; 260] = [0; 260];
-
-        let rapdu = self.t1_proto
-            .receive_apdu(&mut rapdu_buf, delay)
-            .map_err(|_| Se050Error::UnknownError)?;
-
-        if rapdu.sw != 0x9000 {
-            error!("SE050 WriteAESKey Failed: {:x}", rapdu.sw);
-            return Err(Se050Error::UnknownError);
-        }
-
-      //  Ok(())
-
-        debug!("SE050 GenAES OK");
-        Ok(ObjectId([0x20, 0xE8, 0xA0, 0x02]))
-
-
-
+    fn generate_aes_key(&mut self, _objectid: &[u8;4], _bits: u16, _delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        self.require_enabled()?;
+        error!("SE050 GenAES: not supported by the applet");
+        Err(Se050Error::Unsupported)
     }
  
 
@@ -2206,16 +4263,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     /* no support yet for rfc3394 key wrappings, policies or max attempts */
     //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60 
     //P1_DES
+    // the key length selects single DES (8 bytes), 2-key (16) or 3-key (24) 3DES
     fn write_des_key(&mut self,policy: &[u8], objectid: &[u8;4],kekid: &[u8;4],key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
-         
-        if key.len() != 16 {
-            todo!();
-        }
+        self.require_enabled()?;
+        self.check_des_key(key)?;
 
-        let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), kekid);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), key);
+        let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, kekid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, key).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -2256,16 +4312,18 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //P1_HMAC
     fn write_hmac_key(&mut self,policy: &[u8], objectid: &[u8;4],kekid: &[u8;4],key: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
      {  
+         self.require_enabled()?;
      
 
     if key.len() != 16 {
-        todo!();
+        error!("SE050 WriteHMACKey: {} byte key, only 16 supported", key.len());
+        return Err(Se050Error::UnknownError);
     }
 
-    let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), kekid);
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), key);
+    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, kekid).map_err(|_| Se050Error::UnknownError)?;
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, key).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -2300,12 +4358,13 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.4 WriteBinary  //P.61
     fn write_binary(&mut self,policy: &[u8], objectid: &[u8;4],file_offset: &[u8;2], file_length: &[u8;2], data1: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {          
+        self.require_enabled()?;
 
-    let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), file_offset);
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), file_length);
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(),&data1);
+    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, file_offset).map_err(|_| Se050Error::UnknownError)?;
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, file_length).map_err(|_| Se050Error::UnknownError)?;
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4,data1).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -2324,17 +4383,167 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         .send_apdu(&capdu, delay)
         .map_err(|_| Se050Error::UnknownError)?;
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-        .receive_apdu(&mut rapdu_buf, delay)
-        .map_err(|_| Se050Error::UnknownError)?;
+    let mut rapdu_buf: [u8; 260] = [0; 260];
+    let rapdu = self.t1_proto
+        .receive_apdu(&mut rapdu_buf, delay)
+        .map_err(|_| Se050Error::UnknownError)?;
+
+    if rapdu.sw != 0x9000 {
+        error!("SE050 write_binary Failed: {:x}", rapdu.sw);
+        return Err(Se050Error::UnknownError);
+    }
+
+    Ok(())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.4 WriteBinary  //P.61
+    // Creates the binary file sized to data.len() if it doesn't exist yet and writes `data` from offset 0,
+    // in as few WriteBinary commands as fit in single T=1 frames. The policy only applies on creation.
+    //TLV[TAG_POLICY] Byte array containing the object policy. [Optional, creation only]
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 2-byte file offset
+    //TLV[TAG_3] 2-byte file length [creation only]
+    //TLV[TAG_4] Data to be written
+
+    #[inline(never)]
+    fn write_binary_object(&mut self, objectid: &[u8;4], data: &[u8], policy: Option<&[u8]>, delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    {
+        self.require_enabled()?;
+        // APDU header and Lc, TAG_1, TAG_2, TAG_3 and the TAG_4 header with a 3-byte length
+        const WRITE_BINARY_OVERHEAD: usize = 5 + 6 + 4 + 4 + 4;
+
+        if data.len() > 0x7fff {
+            error!("SE050 write_binary_object Data Too Long");
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mut exists: [u8; 1] = [0];
+        self.check_object_exists(&mut exists, objectid, delay)?;
+        let mut create = exists[0] != Se050Resultconstants::ResultSuccess as u8;
+
+        let mut offset: usize = 0;
+        while create || offset < data.len() {
+            let extra = match policy {
+                Some(policy) if create => 2 + policy.len(),
+                _ => 0,
+            };
+            let chunk = MAX_IFSC.checked_sub(WRITE_BINARY_OVERHEAD + extra).ok_or_else(|| {
+                error!("SE050 write_binary_object Policy Too Long");
+                Se050Error::UnknownError })?;
+            let end = core::cmp::min(offset + chunk, data.len());
+
+            let mut file_offset: [u8; 2] = [0; 2];
+            BE::write_u16(&mut file_offset, offset as u16);
+            let mut file_length: [u8; 2] = [0; 2];
+            BE::write_u16(&mut file_length, data.len() as u16);
+
+            let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &file_offset).map_err(|_| Se050Error::UnknownError)?;
+            let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, &file_length).map_err(|_| Se050Error::UnknownError)?;
+            let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, &data[offset..end]).map_err(|_| Se050Error::UnknownError)?;
+
+            let mut capdu = CApdu::new(
+                ApduClass::ProprietaryPlain,
+                Into::<u8>::into(Se050ApduInstruction::Write),
+                Se050ApduP1CredType::Binary.into(),
+                Se050ApduP2::Default.into(),
+                None
+            );
+            if create {
+                if let Some(policy) = policy {
+                    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+                    capdu.push(tlvp);
+                }
+            }
+            capdu.push(tlv1);
+            capdu.push(tlv2);
+            if create {
+                capdu.push(tlv3);
+            }
+            if end > offset {
+                capdu.push(tlv4);
+            }
+
+            self.t1_proto
+                .send_apdu(&capdu, delay)
+                .map_err(|_| Se050Error::UnknownError)?;
+
+            let mut rapdu_buf: [u8; 16] = [0; 16];
+            let rapdu = self.t1_proto
+                .receive_apdu(&mut rapdu_buf, delay)
+                .map_err(|_| Se050Error::UnknownError)?;
+
+            if rapdu.sw != 0x9000 {
+                error!("SE050 write_binary_object Failed at {}: {:x}", offset, rapdu.sw);
+                return Err(Se050Error::UnknownError);
+            }
+
+            create = false;
+            offset = end;
+        }
+
+        debug!("SE050 write_binary_object OK");
+        Ok(())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management //4.7.1.4 WriteBinary //P.61
+    // Stores a DER-encoded certificate in a binary file sized to the certificate,
+    // see write_binary_object. Trailing bytes after the outer SEQUENCE are rejected
+    // so that load_certificate can recover the exact length from the DER header.
+
+    #[inline(never)]
+    fn store_certificate(&mut self, objectid: &[u8;4], der: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    {
+        self.require_enabled()?;
+        if der.first() != Some(&0x30) || der_element_len(der) != Some(der.len()) {
+            error!("SE050 store_certificate Not A DER SEQUENCE");
+            return Err(Se050Error::UnknownError);
+        }
 
-    if rapdu.sw != 0x9000 {
-        error!("SE050 write_binary Failed: {:x}", rapdu.sw);
-        return Err(Se050Error::UnknownError);
+        self.write_binary_object(objectid, der, None, delay)?;
+
+        debug!("SE050 store_certificate OK");
+        Ok(())
     }
 
-    Ok(())
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    // Reads back a certificate written by store_certificate. The file is exactly as
+    // long as the DER, so only its header is read first; the length it gives is
+    // then read in chunks of up to READ_BINARY_CHUNK bytes.
+    // Returns the certificate length written to `out`.
+
+    #[inline(never)]
+    fn load_certificate(&mut self, objectid: &[u8;4], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        if out.len() < CERTIFICATE_HEADER_LEN {
+            error!("SE050 load_certificate Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        let first = self.read_binary_chunk(objectid, 0, CERTIFICATE_HEADER_LEN, out, delay)?;
+        if out[0] != 0x30 {
+            error!("SE050 load_certificate Not A DER SEQUENCE");
+            return Err(Se050Error::UnknownError);
+        }
+        let total = der_element_len(&out[0..first]).ok_or_else(|| {
+            error!("SE050 load_certificate Bad DER Header");
+            Se050Error::UnknownError })?;
+        if total > out.len() {
+            error!("SE050 load_certificate Buffer Too Small ({})", total);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mut offset = core::cmp::min(first, total);
+        while offset < total {
+            let len = core::cmp::min(total - offset, READ_BINARY_CHUNK);
+            offset += self.read_binary_chunk(objectid, offset, len, &mut out[offset..], delay)?;
+        }
+
+        debug!("SE050 load_certificate OK");
+        Ok(total)
     }
 
    
@@ -2345,6 +4554,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         //4.12 Crypto operations AES/DES // 4.12.4 CipherOneShot // ENCRYPT//  4.3.21 CipherMode // AES CBC NOPAD
         fn encrypt_aes_oneshot(&mut self, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
         {
+            self.require_enabled()?;
             if data.len() > 240 || (data.len() % 16 != 0) {
                 error!("Input data too long or unaligned");
                 return Err(Se050Error::UnknownError);
@@ -2353,9 +4563,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
                 error!("Insufficient output buffer");
                 return Err(Se050Error::UnknownError);
             }
-            let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x50, 0xae, 0x50]);
-            let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x0d]);	// AES CBC NOPAD
-            let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), data);
+            let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &[0xae, 0x50, 0xae, 0x50]).map_err(|_| Se050Error::UnknownError)?;
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x0d]).map_err(|_| Se050Error::UnknownError)?;	// AES CBC NOPAD
+            let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, data).map_err(|_| Se050Error::UnknownError)?;
             let mut capdu = CApdu::new(
                 ApduClass::ProprietaryPlain,
                 Se050ApduInstruction::Crypto.into(),
@@ -2382,11 +4592,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
             let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
                 error!("SE050 EncryptAESOneshot Return TLV Missing");
-                Se050Error::UnknownError })?;
+                Se050Error::MissingResponseTlv })?;
 
             if tlv1_ret.get_data().len() != enc.len() {
                 error!("SE050 EncryptAESOneshot Length Mismatch");
-                return Err(Se050Error::UnknownError);
+                return Err(Se050Error::MissingResponseTlv);
             }
             enc.copy_from_slice(tlv1_ret.get_data());
             debug!("SE050 EncryptAESOneshot OK");
@@ -2399,23 +4609,36 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //###########################################################################
     #[inline(never)]
     //WriteUserID 0x80 0x01 0x07 0x00
-    /* NOTE: hardcoded Object ID 0xae51ae51! */
     // See AN12413 // 4.7 Secure Object management //4.7.1 WriteSecureObject P.57 //4.7.1.5 WriteUserID  //P.62
-    fn write_user_id(&mut self, policy: &[u8],objectid: &[u8;4],user_identifier_value : &[u8;16], delay: &mut DelayWrapper) -> Result<(), Se050Error> 
+    //TLV[TAG_POLICY] Byte array containing the object policy.
+    //TLV[TAG_MAX_ATTEMPTS] 2-byte maximum number of authentication attempts. [Optional, sets INS_AUTH_OBJECT]
+    //TLV[TAG_1] 4-byte object identifier.
+    //TLV[TAG_2] Byte array containing the 4 to 16 bytes UserID value.
+    fn write_user_id(&mut self, policy: &[u8],objectid: &[u8;4],user_identifier_value : &[u8;16], max_attempts: Option<u16>, delay: &mut DelayWrapper) -> Result<UserIdObject, Se050Error> 
     {
+        self.require_enabled()?;
 
-        let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
+        let attempts = max_attempts.map(u16::to_be_bytes);
+        let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), user_identifier_value );	 
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, user_identifier_value ).map_err(|_| Se050Error::UnknownError)?;	 
+        let mut ins = Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT;
+        if attempts.is_some() {
+            ins |= APDU_INSTRUCTION_AUTH_OBJECT;
+        }
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
-            Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
+            ins,
             Se050ApduP1CredType::UserID.into(),
             Se050ApduP2::Default.into(),
             None
         );
         capdu.push(tlvp);
+        if let Some(attempts) = attempts.as_ref() {
+            let tlvm = SimpleTlv::with_tag(Se050TlvTag::MaxAttempts, attempts).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlvm);
+        }
 
         capdu.push(tlv1);
 
@@ -2435,7 +4658,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         }
 
         debug!("SE050 write_user_id OK");
-        Ok(())
+        Ok(UserIdObject { id: ObjectId(*objectid), max_attempts })
     }
 
     //###########################################################################
@@ -2453,11 +4676,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn write_counter(&mut self,policy: &[u8], counterid: &[u8;4],countersize: &[u8;2], counterfile: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), counterid);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), countersize);
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &counterfile);
+    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, counterid).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, countersize).map_err(|_| Se050Error::UnknownError)?;
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, counterfile).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2506,11 +4730,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn write_pcr(&mut self,policy: &[u8], pcrid: &[u8;4],initial_hash_value: &[u8],ext: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), pcrid);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), initial_hash_value);
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &ext);
+    let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, pcrid).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, initial_hash_value).map_err(|_| Se050Error::UnknownError)?;
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, ext).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2553,47 +4778,50 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         To-DO
         
         TLV[TAG_1] 4-byte identifier.    
-        TLV[TAG_2] 1-byte RSAKeyComponent        
+        TLV[TAG_2] 1-byte RSAKeyComponent [Conditional: only for RSA key pairs]
         TLV[TAG_3] Serialized object (encrypted).
 
     */
 
     #[inline(never)]
-    fn import_object(&mut self,identifier: &[u8;4], rsakeycomponent: &[u8],serializedobjectencrypted: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn import_object(&mut self,identifier: &[u8;4], rsakeycomponent: Option<Se050RSAKeyComponent>,serializedobjectencrypted: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), identifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsakeycomponent);    
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &serializedobjectencrypted); 
-        
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Write) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::Default.into(),
-    Se050ApduP2::Import.into(),
-    None
-    );
-
-    capdu.push(tlv1);
-    capdu.push(tlv2);
-    capdu.push(tlv3);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, identifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, serializedobjectencrypted).map_err(|_| Se050Error::UnknownError)?;
+        let component = rsakeycomponent.map(|c| [c.into()]);
 
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Write.into(),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Import.into(),
+            None
+        );
+        capdu.push(tlv1);
+        if let Some(component) = component.as_ref() {
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, component).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlv2);
+        }
+        capdu.push(tlv3);
 
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 import_object Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
-    }
+        if rapdu.sw != 0x9000 {
+            error!("SE050 import_object Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-    Ok(())
+        debug!("SE050 import_object OK");
+        Ok(())
     }
 
  
@@ -2620,10 +4848,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn import_external_object(&mut self,authdata: &[u8],  hostpublickeyidentifier: &[u8],writesecureobjectcommand: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlva = SimpleTlv::new(Se050TlvTag::ImportAuthData.into(), &authdata);
-    let tlvb = SimpleTlv::new(Se050TlvTag::ImportAuthKeyID.into(),  &hostpublickeyidentifier);    
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &writesecureobjectcommand); 
+    let tlva = SimpleTlv::with_tag(Se050TlvTag::ImportAuthData, authdata).map_err(|_| Se050Error::UnknownError)?;
+    let tlvb = SimpleTlv::with_tag(Se050TlvTag::ImportAuthKeyID,  hostpublickeyidentifier).map_err(|_| Se050Error::UnknownError)?;    
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, writesecureobjectcommand).map_err(|_| Se050Error::UnknownError)?; 
         
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2674,122 +4903,407 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
 
     //###########################################################################
-    //###########################################################################
-    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject 
-    /*  
-    ReadObject 
-    ExportObject  
-    */
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject 
+    /*  
+    ReadObject 
+    ExportObject  
+    */
+
+    //###########################################################################
+    /* 
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    #[inline(never)]
+    fn read_secure_object(&mut self,objectidentifier: &[u8;4], offset: &[u8;2],length: &[u8;2], rsakeycomponent : &[u8],  attobjectidentifier: &[u8;4],  attlogo: &[u8],   freshnessrandom: &[u8;16],     delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    {   
+    
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  offset).map_err(|_| Se050Error::UnknownError)?;  
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  length).map_err(|_| Se050Error::UnknownError)?;  
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4,  &rsakeycomponent).map_err(|_| Se050Error::UnknownError)?;  
+
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag1, attobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv6 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  &attlogo).map_err(|_| Se050Error::UnknownError)?;  
+        let tlv7 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  freshnessrandom).map_err(|_| Se050Error::UnknownError)?;  
+        
+    let mut capdu = CApdu::new(
+    ApduClass::ProprietaryPlain,
+    Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
+    Se050ApduP1CredType::Default.into(),
+    Se050ApduP2::Default.into(),
+    Some(0x00)
+    );
+
+    capdu.push(tlv1);
+    capdu.push(tlv2);
+    capdu.push(tlv3);
+    capdu.push(tlv4);
+    capdu.push(tlv5);
+    capdu.push(tlv6);
+    capdu.push(tlv7);
+
+    self.t1_proto
+    .send_apdu(&capdu, delay)
+    .map_err(|_| Se050Error::UnknownError)?;
+
+    let mut rapdu_buf: [u8; 260] = [0; 260];
+    let rapdu = self.t1_proto
+    .receive_apdu(&mut rapdu_buf, delay)
+    .map_err(|_| Se050Error::UnknownError)?;
+
+    if rapdu.sw != 0x9000 {
+    error!("SE050 read_secure_object Failed: {:x}", rapdu.sw);
+    return Err(Se050Error::UnknownError);
+    }
+
+    Ok(())
+    }
+
+*/
+
+ //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    #[inline(never)]
+    //fn read_secure_object(&mut self,objectidentifier: &[u8;4], offset: &[u8;2],length: &[u8;2], rsakeycomponent : &[u8],  attobjectidentifier: &[u8;4],  attlogo: &[u8],   freshnessrandom: &[u8;16],     delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn read_secure_object(&mut self, buf: &mut [u8], objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+       
+    {   
+        self.require_enabled()?;
+    
+    debug!("Se050 crate: SE050 read_secure_object DEBUG \n");
+
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+       
+    let mut capdu = CApdu::new(
+    ApduClass::ProprietaryPlain,
+    Into::<u8>::into(Se050ApduInstruction::Read) ,
+    //Into::<u8>::into(Se050ApduInstruction::Read),
+    Se050ApduP1CredType::Default.into(),
+    Se050ApduP2::Default.into(),
+    response_le(tlv_response_len(buf.len()))
+    );
+
+    capdu.push(tlv1);
+
+    debug!("Se050 crate: SE050 read_secure_object tlv1 push DEBUG \n");
+
+    self.t1_proto
+    .send_apdu(&capdu, delay)
+    .map_err(|_| Se050Error::UnknownError)?;
+
+    let mut rapdu_buf: [u8; LARGE_RESPONSE_BUF_LEN] = [0; LARGE_RESPONSE_BUF_LEN];
+
+    let rapdu = self.t1_proto
+    .receive_apdu(&mut rapdu_buf, delay)
+    .map_err(|_| Se050Error::UnknownError)?;
+
+    if rapdu.sw != 0x9000 {
+    error!("SE050 read_secure_object Failed: {:x}", rapdu.sw);
+    return Err(Se050Error::UnknownError);
+    }
+
+
+    let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+        error!("Se050 crate: SE050 Read_secure_object TLV Missing\n");
+        Se050Error::MissingResponseTlv })?;
+ 
+    if tlv1_ret.get_data().len() != buf.len() {
+        error!("SE050 read_secure_object Length Mismatch");
+        return Err(Se050Error::MissingResponseTlv);
+    }
+    buf.copy_from_slice(tlv1_ret.get_data());
+
+   
+    debug!("Se050 crate: SE050 read_secure_object buf : {:#?}\n", buf);
+ 
+    debug!("Se050 crate: SE050 read_secure_object tlv1_ret : {:#?} \n", tlv1_ret);
+
+    debug!("Se050 crate: SE050 read_secure_object tlv1_ret.get_data() : {:#?}\n",tlv1_ret.get_data());
+  
+    debug!("Se050 crate:  SE050 read_secure_object OK \n ");
+  
+    Ok(())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
+    // Reads the modulus (RSA_COMP_MOD) or public exponent (RSA_COMP_PUB_EXP) of an RSA key.
+    // Returns the component length, e.g. 256/384/512 bytes for a 2048/3072/4096-bit modulus.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_4] 1-byte RSAKeyComponent
+
+    #[inline(never)]
+    fn read_rsa_public_key(&mut self, objectidentifier: &[u8;4], component: Se050RSAKeyComponent, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        match component {
+        Se050RSAKeyComponent::RsaCompMod | Se050RSAKeyComponent::RsaCompPubExp => {},
+        _ => {
+            error!("SE050 read_rsa_public_key: not a public component: {:?}", component);
+            return Err(Se050Error::UnknownError);
+        }
+        }
+
+        let comp = [component as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, &comp).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            Some(0x00)
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv4);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        // 4096-bit modulus, TLV header and SW
+        let mut rapdu_buf: [u8; 520] = [0; 520];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_rsa_public_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 read_rsa_public_key Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let data = tlv1_ret.get_data();
+        if data.len() > out.len() {
+            error!("SE050 read_rsa_public_key Buffer Too Small: {} > {}", data.len(), out.len());
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..data.len()].copy_from_slice(data);
+
+        debug!("SE050 read_rsa_public_key OK");
+        Ok(data.len())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (INS_ATTEST) // P.65-66
+    // Attests the object with the NXP attestation key matching the AttestationAlgo:
+    // FACTORY_ATTESTATION_KEY_EC for ECSignatureAlgo, FACTORY_ATTESTATION_KEY_RSA for RSASignatureAlgo.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_5] 4-byte attestation object identifier
+    //TLV[TAG_6] 1-byte AttestationAlgo
+    //TLV[TAG_7] 16-byte freshness random
+    // The response TLVs (object data, attributes, timestamp, freshness, chip unique ID, signature)
+    // are copied to `out` as received; returns their length.
+
+    #[inline(never)]
+    fn attest_with_factory_key(&mut self, objectidentifier: &[u8;4], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8;16], out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        let attestationkey = match attestationalgo {
+        Se050AttestationAlgo::Ec(_) => &FACTORY_ATTESTATION_KEY_EC,
+        Se050AttestationAlgo::Rsa(_) => &FACTORY_ATTESTATION_KEY_RSA,
+        };
+        let attestationalgo: &[u8] = &[attestationalgo.into()];
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, attestationkey).map_err(|_| Se050Error::UnknownError)?;
+        let tlv6 = SimpleTlv::with_tag(Se050TlvTag::Tag6, attestationalgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv7 = SimpleTlv::with_tag(Se050TlvTag::Tag7, freshnessrandom).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_ATTEST,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            response_le(out.len())
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv5);
+        capdu.push(tlv6);
+        capdu.push(tlv7);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; LARGE_RESPONSE_BUF_LEN] = [0; LARGE_RESPONSE_BUF_LEN];
+        let rapdu = self.t1_proto
+            .receive_apdu_raw(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 attest_with_factory_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        if rapdu.data.len() > out.len() {
+            error!("SE050 attest_with_factory_key Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        out[0..rapdu.data.len()].copy_from_slice(rapdu.data);
+
+        debug!("SE050 attest_with_factory_key OK");
+        Ok(rapdu.data.len())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (INS_ATTEST) // P.65-66
+    // Reads the object data together with a signature by `attestationkey` (any key pair
+    // allowed to attest, not only the factory keys) in one command.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_5] 4-byte attestation object identifier
+    //TLV[TAG_6] 1-byte AttestationAlgo
+    //TLV[TAG_7] 16-byte freshness random
+    // The response is copied to `out` and returned parsed; its signed_data (object data,
+    // attributes, timestamp, freshness, chip unique ID) is what the signature covers,
+    // see AttestationResult::verify.
+
+    #[inline(never)]
+    fn read_object_attested<'o>(&mut self, objectidentifier: &[u8;4], attestationkey: &[u8;4], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8;16], out: &'o mut [u8], delay: &mut DelayWrapper) -> Result<AttestationResult<'o>, Se050Error>
+    {
+        self.require_enabled()?;
+        let attestationalgo: &[u8] = &[attestationalgo.into()];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, attestationkey).map_err(|_| Se050Error::UnknownError)?;
+        let tlv6 = SimpleTlv::with_tag(Se050TlvTag::Tag6, attestationalgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv7 = SimpleTlv::with_tag(Se050TlvTag::Tag7, freshnessrandom).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_ATTEST,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            response_le(out.len())
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv5);
+        capdu.push(tlv6);
+        capdu.push(tlv7);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; LARGE_RESPONSE_BUF_LEN] = [0; LARGE_RESPONSE_BUF_LEN];
+        let rapdu = self.t1_proto
+            .receive_apdu_raw(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_object_attested Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        if rapdu.data.len() > out.len() {
+            error!("SE050 read_object_attested Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        let len = rapdu.data.len();
+        out[0..len].copy_from_slice(rapdu.data);
+
+        let att = AttestationResult::parse(&out[0..len])?;
+        if att.freshness != freshnessrandom {
+            error!("SE050 read_object_attested Freshness Mismatch");
+            return Err(Se050Error::VerificationFailed);
+        }
+
+        debug!("SE050 read_object_attested OK");
+        Ok(att)
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (P2_ATTRIBUTES) // P.65-66
+    //TLV[TAG_1] 4-byte object identifier
+    //Response TLV[TAG_2] Object attributes
 
-    //###########################################################################
-    /* 
-    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
     #[inline(never)]
-    fn read_secure_object(&mut self,objectidentifier: &[u8;4], offset: &[u8;2],length: &[u8;2], rsakeycomponent : &[u8],  attobjectidentifier: &[u8;4],  attlogo: &[u8],   freshnessrandom: &[u8;16],     delay: &mut DelayWrapper) -> Result<(), Se050Error>
-    {   
-    
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  offset);  
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  length);  
-        let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(),  &rsakeycomponent);  
-
-        let tlv5 = SimpleTlv::new(Se050TlvTag::Tag1.into(), attobjectidentifier);
-        let tlv6 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &attlogo);  
-        let tlv7 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  freshnessrandom);  
-        
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::Default.into(),
-    Se050ApduP2::Default.into(),
-    Some(0x00)
-    );
-
-    capdu.push(tlv1);
-    capdu.push(tlv2);
-    capdu.push(tlv3);
-    capdu.push(tlv4);
-    capdu.push(tlv5);
-    capdu.push(tlv6);
-    capdu.push(tlv7);
-
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+    fn read_object_attributes(&mut self, objectidentifier: &[u8;4], attributes: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Attributes.into(),
+            Some(0x00)
+        );
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 read_secure_object Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
-    }
+        capdu.push(tlv1);
 
-    Ok(())
-    }
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-*/
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
- //###########################################################################
-    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject // P.65-66
-    #[inline(never)]
-    //fn read_secure_object(&mut self,objectidentifier: &[u8;4], offset: &[u8;2],length: &[u8;2], rsakeycomponent : &[u8],  attobjectidentifier: &[u8;4],  attlogo: &[u8],   freshnessrandom: &[u8;16],     delay: &mut DelayWrapper) -> Result<(), Se050Error>
-    fn read_secure_object(&mut self, buf: &mut [u8], objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>
-       
-    {   
-    
-    debug!("Se050 crate: SE050 read_secure_object DEBUG \n");
+        if rapdu.sw != 0x9000 {
+            error!("SE050 read_object_attributes Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);
-       
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Read) ,
-    //Into::<u8>::into(Se050ApduInstruction::Read),
-    Se050ApduP1CredType::Default.into(),
-    Se050ApduP2::Default.into(),
-    Some(0x00)
-    );
+        let tlv2_ret = rapdu.get_tlv(Se050TlvTag::Tag2.into()).ok_or_else(|| {
+            error!("SE050 read_object_attributes Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
 
-    capdu.push(tlv1);
+        let data = tlv2_ret.get_data();
+        if data.len() > attributes.len() {
+            error!("SE050 read_object_attributes Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        attributes[0..data.len()].copy_from_slice(data);
 
-    debug!("Se050 crate: SE050 read_secure_object tlv1 push DEBUG \n");
+        debug!("SE050 read_object_attributes OK");
+        Ok(data.len())
+    }
 
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+    //###########################################################################
+    // See AN12413 // 3.7 Policies
+    // Reads the object attributes and returns PolicyViolation if no policy of the
+    // object grants `required`.
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
+    #[inline(never)]
+    fn check_object_policy(&mut self, objectidentifier: &[u8;4], required: Se050Objectpolicies, delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    {
+        self.require_enabled()?;
+        let mut attributes: [u8; 256] = [0; 256];
+        let len = self.read_object_attributes(objectidentifier, &mut attributes, delay)?;
 
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        if !object_policy_allows(&attributes[0..len], required as u32)? {
+            error!("SE050 check_object_policy: {:?} not allowed for {:?}", required, objectidentifier);
+            return Err(Se050Error::PolicyViolation);
+        }
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 read_secure_object Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
+        Ok(())
     }
 
+    //###########################################################################
+    // See AN12413 // 4.7 Secure Object management // 4.7.3 ReadSecureObject //4.7.3.1 ReadObject (P2_ATTRIBUTES) // P.65-66
+    // Attempts left before a UserID (or other authentication object with MaxAttempts)
+    // is locked, read from the object attributes without authenticating.
+    // u16::MAX if the object has no attempt limit.
 
-    let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
-        error!("Se050 crate: SE050 Read_secure_object TLV Missing\n");
-        Se050Error::UnknownError })?;
- 
- 
-    buf.copy_from_slice(tlv1_ret.get_data());
-
-   
-    debug!("Se050 crate: SE050 read_secure_object buf : {:#?}\n", buf);
- 
-    debug!("Se050 crate: SE050 read_secure_object tlv1_ret : {:#?} \n", tlv1_ret);
+    #[inline(never)]
+    fn get_userid_attempts(&mut self, objectid: ObjectId, delay: &mut DelayWrapper) -> Result<u16, Se050Error>
+    {
+        self.require_enabled()?;
+        let mut attributes: [u8; 256] = [0; 256];
+        let len = self.read_object_attributes(&objectid.0, &mut attributes, delay)?;
 
-    debug!("Se050 crate: SE050 read_secure_object tlv1_ret.get_data() : {:#?}\n",tlv1_ret.get_data());
-  
-    debug!("Se050 crate:  SE050 read_secure_object OK \n ");
-  
-    Ok(())
+        let remaining = auth_attempts_remaining(&attributes[0..len])?;
+        debug!("SE050 get_userid_attempts OK: {}", remaining);
+        Ok(remaining)
     }
 
 
@@ -2812,9 +5326,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn export_secure_object(&mut self,objectidentifier: &[u8;4] , rsakeycomponent : &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);    
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsakeycomponent);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;    
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  rsakeycomponent).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2865,8 +5380,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn read_type(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);        
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;        
         
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2902,8 +5418,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn read_size(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
                 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2937,9 +5454,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn read_id_list(&mut self,offset: &[u8;2] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), offset);        
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xFF]);  
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, offset).map_err(|_| Se050Error::UnknownError)?;        
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0xFF]).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -2968,6 +5486,69 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     Ok(())
     }
+
+    //###########################################################################
+    // See AN12413// 4.7 Secure Object management  //4.7.4 ManageSecureObject // 4.7.4.3 ReadIDList P.69
+    // The applet has no count command: pages through ReadIDList (filter 0xFF, all types)
+    // until MoreIndicator is NO_MORE and counts the returned identifiers.
+    //TLV[TAG_1] 2-byte offset
+    //TLV[TAG_2] 1-byte type filter
+    #[inline(never)]
+    fn object_count(&mut self, delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        let mut count: usize = 0;
+        loop {
+            let mut offset: [u8; 2] = [0, 0];
+            BE::write_u16(&mut offset, count as u16);
+
+            let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &offset).map_err(|_| Se050Error::UnknownError)?;
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0xFF]).map_err(|_| Se050Error::UnknownError)?;
+
+            let mut capdu = CApdu::new(
+                ApduClass::ProprietaryPlain,
+                Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
+                Se050ApduP1CredType::Default.into(),
+                Se050ApduP2::List.into(),
+                Some(0x00)
+            );
+            capdu.push(tlv1);
+            capdu.push(tlv2);
+
+            self.t1_proto
+                .send_apdu(&capdu, delay)
+                .map_err(|_| Se050Error::UnknownError)?;
+
+            let mut rapdu_buf: [u8; 260] = [0; 260];
+            let rapdu = self.t1_proto
+                .receive_apdu(&mut rapdu_buf, delay)
+                .map_err(|_| Se050Error::UnknownError)?;
+
+            if rapdu.sw != 0x9000 {
+                error!("SE050 object_count Failed: {:x}", rapdu.sw);
+                return Err(Se050Error::UnknownError);
+            }
+
+            let more = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+                error!("SE050 object_count MoreIndicator Missing");
+                Se050Error::MissingResponseTlv })?;
+            let more = more.get_data().first() == Some(&(Se050MoreIndicatorconstants::MORE as u8));
+            let page = rapdu.get_tlv(Se050TlvTag::Tag2.into()).map_or(0, |ids| ids.get_data().len() / 4);
+
+            count += page;
+            if !more {
+                break;
+            }
+            // a MORE page without identifiers would never advance the offset
+            if page == 0 {
+                error!("SE050 object_count Empty Page");
+                return Err(Se050Error::UnknownError);
+            }
+        }
+
+        debug!("SE050 object_count OK");
+        Ok(count)
+    }
  
     //###########################################################################
     // See AN12413// 4.7 Secure Object management  //4.7.4 ManageSecureObject // 4.7.4.4 CheckObjectExists P.70 
@@ -2976,7 +5557,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     fn check_object_exists(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
         
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3047,7 +5628,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
        let b : [u8; 1] = [0x01];
 
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xE8, 0xA0, 0x01]);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &[0x20, 0xE8, 0xA0, 0x01]).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3076,13 +5657,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
         error!("SE050 check_object_exists_p256 Return TLV Missing");
-        Se050Error::UnknownError })?;
-/* 
+        Se050Error::MissingResponseTlv })?;
     if tlv1_ret.get_data().len() != buf.len() {
         error!("SE050 check_object_exists_p256 Length Mismatch");
-        return Err(Se050Error::UnknownError);
+        return Err(Se050Error::MissingResponseTlv);
     }
-*/
 
 //Ok(());
 
@@ -3116,7 +5695,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     fn delete_secure_object(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result< (), Se050Error>
     {   
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3163,10 +5742,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_1] 1-byte curve identifier (from ECCurve) 
 
     #[inline(never)]
-    fn create_eccurve(&mut self, eccurve: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn create_eccurve(&mut self, eccurve: Se050ECCurveconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let eccurve: &[u8] = &[eccurve.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &eccurve);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eccurve).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3202,12 +5783,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_3] Bytestring containing curve parameter value.
    
     #[inline(never)]
-    fn set_eccurve_param(&mut self, eccurve: &[u8], eccurveparam: &[u8], curveparametervalue: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn set_eccurve_param(&mut self, eccurve: Se050ECCurveconstants, eccurveparam: Se050ECCurveParamconstants, curveparametervalue: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let eccurve: &[u8] = &[eccurve.into()];
+        let eccurveparam: &[u8] = &[eccurveparam.into()];
+        self.require_enabled()?;
         
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &eccurve);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &eccurveparam); 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &curveparametervalue);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eccurve).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, eccurveparam).map_err(|_| Se050Error::UnknownError)?; 
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, curveparametervalue).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3240,24 +5824,76 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     //###########################################################################
     // See AN12413//   4.8 EC curve management //  4.8.3 GetECCurveID Get the curve associated with an EC key.. P.72-73
-    //TLV[TAG_1] 4-byte identifier 
+    // The curve the EC key object actually uses, e.g. to confirm a reused slot holds a
+    // key on the expected curve before signing with it.
+    //TLV[TAG_1] 4-byte identifier
+    // Response: TLV[TAG_1] 1-byte ECCurve identifier
 
     #[inline(never)]
-    fn get_eccurve_id(&mut self, identifier: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>
-    {   
-        
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), identifier);  
+    fn get_eccurve_id(&mut self, identifier: &[u8;4], delay: &mut DelayWrapper) -> Result<Se050ECCurveconstants, Se050Error> {
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, identifier).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Curve.into(),
+            Se050ApduP2::ID.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 get_eccurve_id Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 get_eccurve_id Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let id = match tlv1_ret.get_data() {
+            [id] => *id,
+            _ => {
+                error!("SE050 get_eccurve_id Length Mismatch");
+                return Err(Se050Error::MissingResponseTlv);
+            }
+        };
+        let curve = Se050ECCurveconstants::from_u8(id).ok_or_else(|| {
+            error!("SE050 get_eccurve_id: unknown curve {:x}", id);
+            Se050Error::UnknownError })?;
+
+        debug!("SE050 get_eccurve_id OK");
+        Ok(curve)
+    }
+
+    //###########################################################################
+    // See AN12413//   4.8 EC curve management //  4.8.3 GetECCurveID Get the curve associated with an EC key.. P. 73
+    //TLV[TAG_1] Byte array listing all curve identifiers in ECCurve (excluding UNUSED) 
+    //where the curve identifier < 0x40; for each curve, a 1-byte SetIndicator is returned.
  
+    #[inline(never)]
+    #[allow(unused_mut)]
+    fn read_eccurve_list(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    {    
+        self.require_enabled()?;
+
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
     Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
     Se050ApduP1CredType::Curve.into(),
-    Se050ApduP2::ID.into(),
-    None
-    );
-
-    capdu.push(tlv1);
-     
+    Se050ApduP2::List.into(),
+    Some(0x00)
+    ); 
 
     self.t1_proto
     .send_apdu(&capdu, delay)
@@ -3269,7 +5905,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     .map_err(|_| Se050Error::UnknownError)?;
 
     if rapdu.sw != 0x9000 {
-    error!("SE050 get_eccurve_id Failed: {:x}", rapdu.sw);
+    error!("SE050 read_eccurve_list Failed: {:x}", rapdu.sw);
     return Err(Se050Error::UnknownError);
     }
 
@@ -3277,39 +5913,55 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     }    
 
     //###########################################################################
-    // See AN12413//   4.8 EC curve management //  4.8.3 GetECCurveID Get the curve associated with an EC key.. P. 73
-    //TLV[TAG_1] Byte array listing all curve identifiers in ECCurve (excluding UNUSED) 
-    //where the curve identifier < 0x40; for each curve, a 1-byte SetIndicator is returned.
- 
+    // See AN12413//   4.8 EC curve management //  4.8.4 ReadECCurveList P. 73
+    // TLV[TAG_1] holds one SetIndicator per curve identifier, starting at 0x01.
+    // Only curves below 0x40 are listed: ID_ECC_ED_25519 and ID_ECC_MONT_DH_25519
+    // are never reported here, whether they exist depends on the applet variant.
+
     #[inline(never)]
-    #[allow(unused_mut)]
-    fn read_eccurve_list(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
-    {    
+    fn supported_curves(&mut self, delay: &mut DelayWrapper) -> Result<heapless::Vec<Se050ECCurveconstants, 32>, Se050Error>
+    {
+        self.require_enabled()?;
+        let capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Curve.into(),
+            Se050ApduP2::List.into(),
+            Some(0x00)
+        );
 
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Read) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::Curve.into(),
-    Se050ApduP2::List.into(),
-    Some(0x00)
-    ); 
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        if rapdu.sw != 0x9000 {
+            error!("SE050 supported_curves Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 supported_curves Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 read_eccurve_list Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
-    }
+        let mut curves = heapless::Vec::new();
+        for (i, indicator) in tlv1_ret.get_data().iter().enumerate() {
+            if *indicator != Se050SetIndicatorconstants::SET as u8 {
+                continue;
+            }
+            // identifiers the driver doesn't know are skipped
+            if let Some(curve) = Se050ECCurveconstants::from_u8(i as u8 + 1) {
+                curves.push(curve).map_err(|_| Se050Error::UnknownError)?;
+            }
+        }
 
-    Ok(())
-    }    
+        debug!("SE050 supported_curves OK");
+        Ok(curves)
+    }
  
     //###########################################################################
     // See AN12413//   4.8 EC curve management // 4.8.5 DeleteECCurve - Deletes an EC curve P.74
@@ -3317,10 +5969,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
  
     #[inline(never)]
   
-    fn delete_eccurve(&mut self,  eccurve: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn delete_eccurve(&mut self,  eccurve: Se050ECCurveconstants, delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let eccurve: &[u8] = &[eccurve.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &eccurve);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eccurve).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3367,14 +6021,18 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_1] 2-byte Crypto Object identifier
     //TLV[TAG_2] 1-byte CryptoContext
     //TLV[TAG_3] 1-byte Crypto Object subtype, either from DigestMode, CipherMode or MACAlgo (depending on TAG_2).
+    // The CryptoContext follows from the subtype, see Se050CryptoObjectSubtype.
    
     #[inline(never)]  
-    fn create_crypto_object(&mut self,  cryptoobjectidentifier: &[u8;2], cryptocontext: &[u8], cryptoobjectsubtype: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn create_crypto_object(&mut self,  cryptoobjectidentifier: &[u8;2], cryptoobjectsubtype: Se050CryptoObjectSubtype, delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
+        let cryptocontext: &[u8] = &[cryptoobjectsubtype.context().into()];
+        let cryptoobjectsubtype: &[u8] = &[cryptoobjectsubtype.into()];
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), cryptoobjectidentifier);  
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &cryptocontext);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &cryptoobjectsubtype);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptocontext).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, cryptoobjectsubtype).map_err(|_| Se050Error::UnknownError)?;  
   
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3413,6 +6071,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[allow(unused_mut)]
     fn read_crypto_object_list(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3450,8 +6109,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
   
    fn delete_crypto_object(&mut self, cryptoobjectidentifier: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>
    {   
+       self.require_enabled()?;
 
-   let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), cryptoobjectidentifier);  
+   let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
 
    let mut capdu = CApdu::new(
    ApduClass::ProprietaryPlain,
@@ -3506,43 +6166,59 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_1]  4-byte identifier of EC key pair or private key.
     //TLV[TAG_2] 1-byte ECSignatureAlgo.
     //TLV[TAG_3] Byte array containing input data. 
+    // Returns the length of the ASN.1 signature written to `signature`.
 
     #[inline(never)]    
-    fn ecdsa_sign(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: &[u8],inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn ecdsa_sign(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, inputdata: &[u8], signature: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
     {   
+        self.require_enabled()?;
+        let ecsignaturealgo: &[u8] = &[ecsignaturealgo.into()];
+        self.check_fips_signature_algo(ecsignaturealgo[0])?;
+        self.preflight_policy(eckeyidentifier, Se050Objectpolicies::PolicyObjAllowSign, delay)?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &ecsignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  inputdata);      
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ecsignaturealgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
 
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::Signature.into(),
-    Se050ApduP2::Sign.into(),
-    Some(0x00)
-    ); 
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Signature.into(),
+            Se050ApduP2::Sign.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
 
-    capdu.push(tlv1);
-    capdu.push(tlv2);
-    capdu.push(tlv3);
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        if rapdu.sw != 0x9000 {
+            error!("SE050 ecdsa_sign Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 ecdsa_sign Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
-    }
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 ecdsa_sign Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
 
-    Ok(())
-    }    
+        let sig = tlv1_ret.get_data();
+        if sig.len() > signature.len() {
+            error!("SE050 ecdsa_sign Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        signature[0..sig.len()].copy_from_slice(sig);
+
+        debug!("SE050 ecdsa_sign OK");
+        Ok(sig.len())
+    }
  
     //###########################################################################
     // See AN12413 // 4.10 Crypto operations EC // 4.10.1 Signature generation // 4.10.1.2 EdDSASign P.77-78
@@ -3552,14 +6228,19 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_1]  4-byte identifier of EC key pair or private key.
     //TLV[TAG_2] 1-byte EDSignatureAlgo.
     //TLV[TAG_3] Byte array containing plain input data. 
+    // Ed25519 hashes the whole message itself, so it cannot be pre-hashed on the host:
+    // a message too long for one T=1 frame goes out in chained I-blocks, up to the
+    // extended APDU limit of the applet. The 64-byte signature R || S is written to `signature`.
 
     #[inline(never)]    
-    fn eddsa_sign(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: &[u8],inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn eddsa_sign(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: Se050EDSignatureAlgo, inputdata: &[u8], signature: &mut [u8; 64], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let edsignaturealgo: &[u8] = &[edsignaturealgo.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &edsignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  inputdata);      
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  edsignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  inputdata).map_err(|_| Se050Error::UnknownError)?;      
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3587,6 +6268,17 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     return Err(Se050Error::UnknownError);
     }
 
+    let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+        error!("SE050 eddsa_sign Return TLV Missing");
+        Se050Error::MissingResponseTlv })?;
+
+    if tlv1_ret.get_data().len() != signature.len() {
+        error!("SE050 eddsa_sign Length Mismatch");
+        return Err(Se050Error::MissingResponseTlv);
+    }
+    signature.copy_from_slice(tlv1_ret.get_data());
+
+    debug!("SE050 eddsa_sign OK");
     Ok(())
     }    
 
@@ -3602,13 +6294,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
  
     #[inline(never)]
     
-    fn ecdaa_sign(&mut self, eckeyidentifier: &[u8;4], ecdaasignaturealgo: &[u8],hashedinputdata: &[u8;32], randomdata: &[u8;32], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn ecdaa_sign(&mut self, eckeyidentifier: &[u8;4], ecdaasignaturealgo: Se050ECDAASignatureAlgo,hashedinputdata: &[u8;32], randomdata: &[u8;32], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let ecdaasignaturealgo: &[u8] = &[ecdaasignaturealgo.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &ecdaasignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  hashedinputdata);  
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(),  randomdata);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  ecdaasignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  hashedinputdata).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4,  randomdata).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3651,13 +6345,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
  
     #[inline(never)]
   
-    fn ecdsa_verify(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: &[u8],hashedcomparedata: &[u8], asn1signaturedata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn ecdsa_verify(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo,hashedcomparedata: &[u8], asn1signaturedata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let ecsignaturealgo: &[u8] = &[ecsignaturealgo.into()];
+        self.require_enabled()?;
  
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &ecsignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &hashedcomparedata);  
-    let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(),  &asn1signaturedata);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  ecsignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  hashedcomparedata).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5,  asn1signaturedata).map_err(|_| Se050Error::UnknownError)?;  
  
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3707,13 +6403,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
  
     #[inline(never)]
   
-    fn eddsa_verify(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: &[u8],plaincomparedata: &[u8], signaturedata: &[u8;64], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn eddsa_verify(&mut self, eckeyidentifier: &[u8;4], edsignaturealgo: Se050EDSignatureAlgo,plaincomparedata: &[u8], signaturedata: &[u8;64], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let edsignaturealgo: &[u8] = &[edsignaturealgo.into()];
+        self.require_enabled()?;
  
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &edsignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &plaincomparedata);  
-    let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(),  signaturedata);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  edsignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  plaincomparedata).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5,  signaturedata).map_err(|_| Se050Error::UnknownError)?;  
  
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3757,45 +6455,62 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     */
 
     //TLV[TAG_1]  4-byte identifier of EC key pair or private key.
-    //TLV[TAG_2]  External public key (see ECKey).
-     
- 
+    //TLV[TAG_2]  External public key (see ECKey), checked against `curve` before sending:
+    //            0x04 || X || Y for Weierstrass curves, 32 raw bytes for ID_ECC_MONT_DH_25519.
+    // Returns the length of the shared secret written to `sharedsecret`.
+
     #[inline(never)]
-  
-    fn ecdh_generate_shared_secret(&mut self, eckeyidentifier: &[u8;4], eckey: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>
-    {   
- 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), eckeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &eckey);  
-     
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::EC.into(),
-    Se050ApduP2::DH.into(),
-    Some(0x00)
-    ); 
- 
-    capdu.push(tlv1);
-    capdu.push(tlv2);
-    
- 
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
- 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
- 
-    if rapdu.sw != 0x9000 {
-    error!("SE050 ecdh_generate_shared_secret Failed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
+    fn ecdh_generate_shared_secret(&mut self, eckeyidentifier: &[u8;4], curve: Se050ECCurveconstants, publickey: &[u8], sharedsecret: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error>
+    {
+        self.require_enabled()?;
+        if curve == Se050ECCurveconstants::IdEccEd25519 {
+            error!("SE050 ecdh_generate_shared_secret: ID_ECC_ED_25519 does not support ECDH");
+            return Err(Se050Error::UnknownError);
+        }
+        curve.check_public_key(publickey)?;
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, publickey).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::EC.into(),
+            Se050ApduP2::DH.into(),
+            Some(0x00)
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 ecdh_generate_shared_secret Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 ecdh_generate_shared_secret Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let secret = tlv1_ret.get_data();
+        if secret.len() > sharedsecret.len() {
+            error!("SE050 ecdh_generate_shared_secret Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        sharedsecret[0..secret.len()].copy_from_slice(secret);
+
+        debug!("SE050 ecdh_generate_shared_secret OK");
+        Ok(secret.len())
     }
- 
-    Ok(())
-    }    
 
 
     //###########################################################################
@@ -3823,12 +6538,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
   
-    fn rsa_sign(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: &[u8], inputdata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn rsa_sign(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: Se050RSASignatureAlgo, inputdata: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let rsasignaturealgo: &[u8] = &[rsasignaturealgo.into()];
+        self.require_enabled()?;
+        self.check_fips_signature_algo(rsasignaturealgo[0])?;
  
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), rsakeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsasignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &inputdata); 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, rsakeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  rsasignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  inputdata).map_err(|_| Se050Error::UnknownError)?; 
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3869,13 +6587,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
     
-    fn rsa_verify(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: &[u8], datatobeverified: &[u8], asn1signature: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn rsa_verify(&mut self, rsakeyidentifier: &[u8;4], rsasignaturealgo: Se050RSASignatureAlgo, datatobeverified: &[u8], asn1signature: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let rsasignaturealgo: &[u8] = &[rsasignaturealgo.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), rsakeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsasignaturealgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &datatobeverified);  
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &asn1signature); 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, rsakeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  rsasignaturealgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  datatobeverified).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag5,  asn1signature).map_err(|_| Se050Error::UnknownError)?; 
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3915,12 +6635,14 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_3] Byte array containing data to be encrypted.
 
     #[inline(never)]  
-    fn rsa_encrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: &[u8], datatobeencrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn rsa_encrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: Se050RSAEncryptionAlgo, datatobeencrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let rsaencryptionalgo: &[u8] = &[rsaencryptionalgo.into()];
+        self.require_enabled()?;
  
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), rsakeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsaencryptionalgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &datatobeencrypted);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, rsakeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  rsaencryptionalgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  datatobeencrypted).map_err(|_| Se050Error::UnknownError)?;  
   
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -3958,12 +6680,14 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     //TLV[TAG_3] Byte array containing data to be decrypted.
 
     #[inline(never)]  
-    fn rsa_decrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: &[u8], datatobedecrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn rsa_decrypt(&mut self, rsakeyidentifier: &[u8;4], rsaencryptionalgo: Se050RSAEncryptionAlgo, datatobedecrypted: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let rsaencryptionalgo: &[u8] = &[rsaencryptionalgo.into()];
+        self.require_enabled()?;
  
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), rsakeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  &rsaencryptionalgo);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &datatobedecrypted);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, rsakeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  rsaencryptionalgo).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  datatobedecrypted).map_err(|_| Se050Error::UnknownError)?;  
   
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4035,10 +6759,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]    
     fn cipher_init_encrypt(&mut self, keyobjectidentifier: &[u8;4], cryptoobjectidentifier: &[u8;2], initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keyobjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  cryptoobjectidentifier);  
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(),  &initializationvector);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4,  initializationvector).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4073,10 +6798,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]    
     fn cipher_init_decrypt(&mut self, keyobjectidentifier: &[u8;4], cryptoobjectidentifier: &[u8;2], initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keyobjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  cryptoobjectidentifier);  
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(),  &initializationvector); 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4,  initializationvector).map_err(|_| Se050Error::UnknownError)?; 
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4116,9 +6842,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]    
     fn cipher_update(&mut self, cryptoobjectidentifier: &[u8;2], inputdata: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  cryptoobjectidentifier);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &inputdata);  
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  inputdata).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4156,9 +6883,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]    
     fn cipher_final(&mut self, cryptoobjectidentifier: &[u8;2], inputdata: &[u8] , delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  cryptoobjectidentifier);  
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(),  &inputdata);  
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;  
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3,  inputdata).map_err(|_| Se050Error::UnknownError)?;  
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4202,13 +6930,18 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     equals CC_CIPHER and subtype is not including     ECB]
     */
     #[inline(never)]    
-    fn cipher_one_shot_encrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: &[u8] ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn cipher_one_shot_encrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
+    check_cipher_iv(ciphermode, initializationvector)?;
+    self.check_des_mode(ciphermode)?;
+    self.preflight_policy(keybjectidentifier, Se050Objectpolicies::PolicyObjAllowEnc, delay)?;
+    let ciphermode: &[u8] = &[ciphermode.into()];
     
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keybjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &ciphermode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &inputdata);
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), &initializationvector);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keybjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ciphermode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, initializationvector).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4221,7 +6954,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     capdu.push(tlv1);  
     capdu.push(tlv2);    
     capdu.push(tlv3);
-    capdu.push(tlv4);  
+    if !initializationvector.is_empty() {
+    capdu.push(tlv4);
+    }
 
     self.t1_proto
     .send_apdu(&capdu, delay)
@@ -4242,13 +6977,18 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
 
     #[inline(never)]    
-    fn cipher_one_shot_decrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: &[u8] ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn cipher_one_shot_decrypt(&mut self, keybjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants ,inputdata: &[u8],initializationvector: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
+    check_cipher_iv(ciphermode, initializationvector)?;
+    self.check_des_mode(ciphermode)?;
+    self.preflight_policy(keybjectidentifier, Se050Objectpolicies::PolicyObjAllowDec, delay)?;
+    let ciphermode: &[u8] = &[ciphermode.into()];
     
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keybjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &ciphermode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &inputdata);
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), &initializationvector);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keybjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ciphermode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, initializationvector).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4260,7 +7000,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     capdu.push(tlv1);  
     capdu.push(tlv2);    
     capdu.push(tlv3);
-    capdu.push(tlv4);  
+    if !initializationvector.is_empty() {
+    capdu.push(tlv4);
+    }
 
     self.t1_proto
     .send_apdu(&capdu, delay)
@@ -4295,9 +7037,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("Insufficient output buffer");
             return Err(Se050Error::UnknownError);
         }
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), & cipher_mode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), data);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, & cipher_mode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, data).map_err(|_| Se050Error::UnknownError)?;
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Se050ApduInstruction::Crypto.into(),
@@ -4327,11 +7069,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
         let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
             error!("SE050 EncryptAESOneshot Return TLV Missing");
-            Se050Error::UnknownError })?;
+            Se050Error::MissingResponseTlv })?;
 
         if tlv1_ret.get_data().len() != enc.len() {
             error!("SE050 EncryptAESOneshot Length Mismatch");
-            return Err(Se050Error::UnknownError);
+            return Err(Se050Error::MissingResponseTlv);
         }
         enc.copy_from_slice(tlv1_ret.get_data());
       //  debug!("SE050 EncryptAESOneshot {:x} OK",  cipher_mode );
@@ -4349,8 +7091,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     /* NOTE: hardcoded Object ID 0xae50ae50! */
     //4.12 Crypto operations AES/DES // 4.12.4 CipherOneShot // DECRYPT P.87
     //  4.3.21 CipherMode // 4.3.21 CipherMode Table 39. CipherMode constants P.43
-    fn decrypt_aes_oneshot(&mut self,  objectid: &[u8;4], cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
+    fn decrypt_aes_oneshot(&mut self,  objectid: &[u8;4], cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
     {
+        let cipher_mode: &[u8] = &[cipher_mode.into()];
+        self.require_enabled()?;
         if data.len() > 240 || (data.len() % 16 != 0) {
             error!("Input data too long or unaligned");
             return Err(Se050Error::UnknownError);
@@ -4359,9 +7103,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("Insufficient output buffer");
             return Err(Se050Error::UnknownError);
         }
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(),  objectid );
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  & cipher_mode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), data);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1,  objectid ).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,   cipher_mode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, data).map_err(|_| Se050Error::UnknownError)?;
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Se050ApduInstruction::Crypto.into(),
@@ -4394,14 +7138,14 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("SE050 DecryptAESOneshot {:x?} Return TLV Missing",   cipher_mode);
 
 
-            Se050Error::UnknownError })?;
+            Se050Error::MissingResponseTlv })?;
 
         if tlv1_ret.get_data().len() != enc.len() {
           //  error!("SE050 DecryptAESOneshot {:x} Length Mismatch",  cipher_mode );
             //error!("SE050 DecryptAESOneshot  Length Mismatch" );
             error!("SE050 DecryptAESOneshot {:x?} Length Mismatch",  cipher_mode );
 
-            return Err(Se050Error::UnknownError);
+            return Err(Se050Error::MissingResponseTlv);
         }
         enc.copy_from_slice(tlv1_ret.get_data());
        // debug!("SE050 DecryptAESOneshot {:x} OK", cipher_mode );
@@ -4419,8 +7163,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     /* NOTE: hardcoded Object ID 0xae50ae50! */
     //4.12 Crypto operations AES/DES // 4.12.4 CipherOneShot // ENCRYPT  P.87
     //  4.3.21 CipherMode // 4.3.21 CipherMode Table 39. CipherMode constants P.43
-    fn encrypt_des_oneshot(&mut self,  objectid: &[u8;4],cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
+    fn encrypt_des_oneshot(&mut self,  objectid: &[u8;4],cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
     {
+        let cipher_mode: &[u8] = &[cipher_mode.into()];
+        self.require_enabled()?;
         if data.len() > 240 || (data.len() % 16 != 0) {
             error!("Input data too long or unaligned");
             return Err(Se050Error::UnknownError);
@@ -4429,9 +7175,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("Insufficient output buffer");
             return Err(Se050Error::UnknownError);
         }
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), & cipher_mode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), data);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  cipher_mode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, data).map_err(|_| Se050Error::UnknownError)?;
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Se050ApduInstruction::Crypto.into(),
@@ -4462,11 +7208,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
         let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
             error!("SE050 EncryptDESOneshot Return TLV Missing");
-            Se050Error::UnknownError })?;
+            Se050Error::MissingResponseTlv })?;
 
         if tlv1_ret.get_data().len() != enc.len() {
             error!("SE050 EncryptDESOneshot Length Mismatch");
-            return Err(Se050Error::UnknownError);
+            return Err(Se050Error::MissingResponseTlv);
         }
         enc.copy_from_slice(tlv1_ret.get_data());
        // debug!("SE050 EncryptDESOneshot {:x} OK",  cipher_mode );
@@ -4481,8 +7227,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     /* NOTE: hardcoded Object ID 0xae50ae50! */
     //4.12 Crypto operations AES/DES // 4.12.4 CipherOneShot // DECRYPT P.87 
     //4.3.21 CipherMode // 4.3.21 CipherMode Table 39. CipherMode constants P.43
-    fn decrypt_des_oneshot(&mut self,  objectid: &[u8;4], cipher_mode: &[u8], data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
+    fn decrypt_des_oneshot(&mut self,  objectid: &[u8;4], cipher_mode: Se050CipherModeconstants, data: &[u8],  enc: &mut [u8], delay: &mut DelayWrapper, ) -> Result<(), Se050Error> 
     {
+        let cipher_mode: &[u8] = &[cipher_mode.into()];
+        self.require_enabled()?;
         if data.len() > 240 || (data.len() % 16 != 0) {
             error!("Input data too long or unaligned");
             return Err(Se050Error::UnknownError);
@@ -4491,9 +7239,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("Insufficient output buffer");
             return Err(Se050Error::UnknownError);
         }
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(),  & cipher_mode);	// 4.3.21 CipherMode Table 39. CipherMode constants
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), data);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,   cipher_mode).map_err(|_| Se050Error::UnknownError)?;	// 4.3.21 CipherMode Table 39. CipherMode constants
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, data).map_err(|_| Se050Error::UnknownError)?;
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
             Se050ApduInstruction::Crypto.into(),
@@ -4529,7 +7277,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
 
             
-            Se050Error::UnknownError })?;
+            Se050Error::MissingResponseTlv })?;
 
         if tlv1_ret.get_data().len() != enc.len() {
             //error!("SE050 DecryptDESOneshot {:x} Length Mismatch",  cipher_mode );
@@ -4537,7 +7285,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             error!("SE050 DecryptDESOneshot {:x?} Length Mismatch",  cipher_mode );
 
 
-            return Err(Se050Error::UnknownError);
+            return Err(Se050Error::MissingResponseTlv);
         }
         enc.copy_from_slice(tlv1_ret.get_data());
       //  debug!("SE050 DecryptDESOneshot {:x} OK", cipher_mode );
@@ -4577,9 +7325,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]        
     fn mac_init(&mut self, mackeybjectidentifier: &[u8;4], cryptobjectidentifier: &[u8;2], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), mackeybjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptobjectidentifier);	 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, mackeybjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptobjectidentifier).map_err(|_| Se050Error::UnknownError)?;	 
      
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4619,9 +7368,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             
     fn mac_update(&mut self, macdatainput: &[u8], cryptobjectidentifier: &[u8;2], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &macdatainput);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptobjectidentifier);	 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, macdatainput).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptobjectidentifier).map_err(|_| Se050Error::UnknownError)?;	 
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4663,10 +7413,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]            
     fn mac_final(&mut self, macdatainput: &[u8], cryptobjectidentifier: &[u8;2], mactovalidate: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &macdatainput);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptobjectidentifier);	 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &mactovalidate);	
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, macdatainput).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptobjectidentifier).map_err(|_| Se050Error::UnknownError)?;	 
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, mactovalidate).map_err(|_| Se050Error::UnknownError)?;	
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4711,13 +7462,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     */
 
     #[inline(never)]            
-    fn mac_one_shot(&mut self, keyobjectidentifier: &[u8;4], macalgo: &[u8], datainputtomac: &[u8], mactoverify: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn mac_one_shot(&mut self, keyobjectidentifier: &[u8;4], macalgo: Se050MACAlgoconstants, datainputtomac: &[u8], mactoverify: &[u8],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let macalgo: &[u8] = &[macalgo.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keyobjectidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &macalgo);	 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &datainputtomac);	
-    let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(), &mactoverify);	
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, macalgo).map_err(|_| Se050Error::UnknownError)?;	 
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, datainputtomac).map_err(|_| Se050Error::UnknownError)?;	
+    let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, mactoverify).map_err(|_| Se050Error::UnknownError)?;	
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4780,14 +7533,16 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
             
-    fn hkdf(&mut self, hmackeyidentifier: &[u8;4], digestmode: &[u8], salt: &[u8;64], info: &[u8;64],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn hkdf(&mut self, hmackeyidentifier: &[u8;4], digestmode: Se050DigestModeconstants, salt: &[u8;64], info: &[u8;64],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        let digestmode: &[u8] = &[digestmode.into()];
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), hmackeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &digestmode);	 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), salt);	
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), info);	
-    let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(), requestedlength);	 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, hmackeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, digestmode).map_err(|_| Se050Error::UnknownError)?;	 
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, salt).map_err(|_| Se050Error::UnknownError)?;	
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, info).map_err(|_| Se050Error::UnknownError)?;	
+    let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, requestedlength).map_err(|_| Se050Error::UnknownError)?;	 
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4841,11 +7596,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]            
     fn pbkdf2derivekey(&mut self,  passwordidentifier: &[u8;4],  salt: &[u8;64], iterationcount: &[u8;2],requestedlength: &[u8;4],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), passwordidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), salt);	 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), iterationcount);	
-    let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), requestedlength);	
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, passwordidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, salt).map_err(|_| Se050Error::UnknownError)?;	 
+    let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, iterationcount).map_err(|_| Se050Error::UnknownError)?;	
+    let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, requestedlength).map_err(|_| Se050Error::UnknownError)?;	
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -4920,42 +7676,50 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     TLV[TAG_2] 4-byte diversified key identifier.
     TLV[TAG_3] Byte array containing divInput (up to 31 bytes). 
     */
+    // Derives into the existing AES key object `diversifiedkeyidentifier`, divinput
+    // is checked for 1 to 31 bytes (AN10922). The diversified value never leaves the SE050.
 
     #[inline(never)]                
-    fn dfdiversifykey(&mut self,  masterkeyidentifier: &[u8;4], diversifiedkeyidentifier: &[u8;4], divinput: &[u8;31], delay: &mut DelayWrapper) -> Result<(), Se050Error>
+    fn dfdiversifykey(&mut self,  masterkeyidentifier: &[u8;4], diversifiedkeyidentifier: &[u8;4], divinput: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
+        self.require_mifare()?;
+        if divinput.is_empty() || divinput.len() > DESFIRE_MAX_DIV_INPUT_LEN {
+            error!("SE050 dfdiversifykey: divInput of {} bytes", divinput.len());
+            return Err(Se050Error::UnknownError);
+        }
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), masterkeyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), diversifiedkeyidentifier);	 
-    let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), divinput);	
-     
-    let mut capdu = CApdu::new(
-    ApduClass::ProprietaryPlain,
-    Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
-    Se050ApduP1CredType::Default.into(),
-    Se050ApduP2::Diversify.into(),
-    None
-    ); 
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, masterkeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, diversifiedkeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, divinput).map_err(|_| Se050Error::UnknownError)?;
 
-    capdu.push(tlv1);  
-    capdu.push(tlv2);   
-    capdu.push(tlv3); 
-     
-    self.t1_proto
-    .send_apdu(&capdu, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Diversify.into(),
+            None
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
 
-    let mut rapdu_buf: [u8; 260] = [0; 260];
-    let rapdu = self.t1_proto
-    .receive_apdu(&mut rapdu_buf, delay)
-    .map_err(|_| Se050Error::UnknownError)?;
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    if rapdu.sw != 0x9000 {
-    error!("SE050  dfdiversifykeyFailed: {:x}", rapdu.sw);
-    return Err(Se050Error::UnknownError);
-    }
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    Ok(())
+        if rapdu.sw != 0x9000 {
+            error!("SE050 dfdiversifykey Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 dfdiversifykey OK");
+        Ok(())
     }  
 
     //###########################################################################
@@ -4972,9 +7736,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
                     
     fn dfauthenticatefirstpart1(&mut self, keyidentifier: &[u8;4],diversifiedkeyidentifier: &[u8;16], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), diversifiedkeyidentifier);	 
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, diversifiedkeyidentifier).map_err(|_| Se050Error::UnknownError)?;	 
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5017,8 +7782,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
                         
     fn dfauthenticatefirstpart2(&mut self, input: &[u8;32], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), input);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, input).map_err(|_| Se050Error::UnknownError)?;
       
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5060,9 +7826,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]              
     fn dfauthenticatenonfirstpart1(&mut self, keyidentifier: &[u8;4], encryptedcardchallenge: &[u8;16],delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), keyidentifier);
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), encryptedcardchallenge);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyidentifier).map_err(|_| Se050Error::UnknownError)?;
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, encryptedcardchallenge).map_err(|_| Se050Error::UnknownError)?;
       
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5107,8 +7874,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]              
     fn dfauthenticatenonfirstpart2(&mut self, edata: &[u8;16],  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), edata);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, edata).map_err(|_| Se050Error::UnknownError)?;
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5148,6 +7916,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[allow(unused_mut)]              
     fn dfdumpdsessionkeys(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5202,11 +7971,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]             
     fn dfchangekeypart1(&mut self, oldkey:&[u8;4], newkey:&[u8;4], setnumber:&[u8], desfirekeynumber:&[u8],keyversion:&[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {       
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), oldkey);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), newkey);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &setnumber);
-        let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), &desfirekeynumber);
-        let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(), &keyversion);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, oldkey).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, newkey).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, setnumber).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, desfirekeynumber).map_err(|_| Se050Error::UnknownError)?;
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, keyversion).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -5254,7 +8024,8 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]             
     fn dfchangekeypart2(&mut self, mac:&[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {       
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &mac);
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, mac).map_err(|_| Se050Error::UnknownError)?;
           
         let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -5296,6 +8067,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[allow(unused_mut)]             
     fn dfkillauthentication(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {       
+        self.require_enabled()?;
         let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
         Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
@@ -5339,6 +8111,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[allow(unused_mut)]
     fn tls_generate_random(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> 
     {
+        self.require_enabled()?;
  
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -5390,11 +8163,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[allow(unused_mut)]
     fn tls_calculate_pre_master_secret(&mut self,  pskidentifier: &[u8;4], keypairidentifier: &[u8;4],hmackeyidentifier:&[u8;4], inputdata: &[u8;4], delay: &mut DelayWrapper) -> Result<(), Se050Error> 
     {
+        self.require_enabled()?;
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), pskidentifier);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), keypairidentifier);	 
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), hmackeyidentifier);
-        let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), inputdata);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, pskidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, keypairidentifier).map_err(|_| Se050Error::UnknownError)?;	 
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, hmackeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, inputdata).map_err(|_| Se050Error::UnknownError)?;
 
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -5461,13 +8235,15 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     */
 
     #[inline(never)]    
-    fn tls_perform_prf(&mut self,  hmackeyidentifier:&[u8;4], digestmode: &[u8],label: &[u8;64],random:&[u8;32], requestlenght:&[u8;2],delay: &mut DelayWrapper) -> Result<(), Se050Error> 
+    fn tls_perform_prf(&mut self,  hmackeyidentifier:&[u8;4], digestmode: Se050DigestModeconstants,label: &[u8;64],random:&[u8;32], requestlenght:&[u8;2],delay: &mut DelayWrapper) -> Result<(), Se050Error> 
     { 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), hmackeyidentifier);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &digestmode);	 
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), label);
-        let tlv4 = SimpleTlv::new(Se050TlvTag::Tag4.into(), random);
-        let tlv5 = SimpleTlv::new(Se050TlvTag::Tag5.into(), requestlenght);
+        let digestmode: &[u8] = &[digestmode.into()];
+        self.require_enabled()?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, hmackeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, digestmode).map_err(|_| Se050Error::UnknownError)?;	 
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, label).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, random).map_err(|_| Se050Error::UnknownError)?;
+        let tlv5 = SimpleTlv::with_tag(Se050TlvTag::Tag5, requestlenght).map_err(|_| Se050Error::UnknownError)?;
  
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
@@ -5536,12 +8312,14 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
          */
 
         #[inline(never)]
-        fn i2cm_execute_command_set(&mut self, i2ccommand: &[u8], attestationobjectidentifier: &[u8], attestationalgo: &[u8], freshnessrandom: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        fn i2cm_execute_command_set(&mut self, i2ccommand: &[u8], attestationobjectidentifier: &[u8], attestationalgo: Se050AttestationAlgo, freshnessrandom: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+            self.require_enabled()?;
+        let attestationalgo: &[u8] = &[attestationalgo.into()];
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &i2ccommand);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &attestationobjectidentifier);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &attestationalgo);
-        let tlv7 = SimpleTlv::new(Se050TlvTag::Tag7.into(), &freshnessrandom);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, i2ccommand).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, attestationobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, attestationalgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv7 = SimpleTlv::with_tag(Se050TlvTag::Tag7, freshnessrandom).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5605,8 +8383,9 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
     fn digest_init(&mut self, cryptoobjectidentifier: &[u8;2], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptoobjectidentifier);
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5648,9 +8427,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
     fn digest_update(&mut self, cryptoobjectidentifier: &[u8;2], datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptoobjectidentifier);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &datatobehashed);
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, datatobehashed).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5692,9 +8472,10 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     #[inline(never)]
     fn digest_final(&mut self, cryptoobjectidentifier: &[u8;2], datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), cryptoobjectidentifier);
-        let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &datatobehashed);
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, cryptoobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, datatobehashed).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5735,10 +8516,12 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     // TLV[TAG_2] Data to be hashed.
 
     #[inline(never)]
-    fn digest_one_shot(&mut self, digestmode: &[u8], datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+    fn digest_one_shot(&mut self, digestmode: Se050DigestModeconstants, datatobehashed: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        let digestmode: &[u8] = &[digestmode.into()];
+        self.require_enabled()?;
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &digestmode);
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &datatobehashed);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, digestmode).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, datatobehashed).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5768,6 +8551,67 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         debug!("SE050 digest_one_shot OK");
         Ok(())
     }
+
+    //###########################################################################
+    //See AN12413, //4.18 Digest operations //4.18.4 DigestOneShot // P.108
+    // Same command as digest_one_shot, but returns the digest. The output buffer must be able
+    // to hold the digest of the selected mode (e.g. 20 bytes for SHA-1, 28 for SHA-224).
+    //TLV[TAG_1] 1-byte DigestMode (except DIGEST_NO_HASH)
+    //TLV[TAG_2] Data to be hashed.
+
+    #[inline(never)]
+    fn digest(&mut self, digestmode: Se050DigestModeconstants, datatobehashed: &[u8], digest: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        let digest_len = digestmode.output_len().ok_or_else(|| {
+            error!("SE050 digest: DigestNoHash not allowed");
+            Se050Error::UnknownError })?;
+        if digest.len() < digest_len {
+            error!("SE050 digest Buffer Too Small: {} < {}", digest.len(), digest_len);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let mode: &[u8] = &[digestmode.into()];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, mode).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, datatobehashed).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Oneshot.into(),
+            Some(0x00)
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 80] = [0; 80];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 digest Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 digest Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        if tlv1_ret.get_data().len() != digest_len {
+            error!("SE050 digest Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+        digest[0..digest_len].copy_from_slice(tlv1_ret.get_data());
+
+        debug!("SE050 digest OK");
+        Ok(digest_len)
+    }
    
 
     //###########################################################################
@@ -5793,6 +8637,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     #[allow(unused_mut)]
     fn get_version(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
         
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5816,8 +8661,70 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
             return Err(Se050Error::UnknownError);
         }
 
-        debug!("SE050 get_version OK");
-        Ok(())
+        debug!("SE050 get_version OK");
+        Ok(())
+    }
+
+    //###########################################################################
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    // Reads the AppletConfig again (SetAppletFeatures may have changed it since the
+    // SELECT) and refreshes app_info(). In FIPS mode the applet only offers FIPS
+    // approved algorithms and blocks, among others, single DES keys and signatures
+    // over SHA-1; the driver rejects those with FipsRestricted before sending.
+    //Response TLV[TAG_1] 7-byte version info
+
+    #[inline(never)]
+    fn is_fips_mode(&mut self, delay: &mut DelayWrapper) -> Result<bool, Se050Error> {
+        self.require_enabled()?;
+        let fips = self.refresh_app_info(delay)?.is_fips_mode();
+        debug!("SE050 is_fips_mode OK");
+        Ok(fips)
+    }
+
+    //###########################################################################
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    // The algorithms of this SKU, from the AppletConfig read fresh with GetVersion.
+
+    #[inline(never)]
+    fn capabilities(&mut self, delay: &mut DelayWrapper) -> Result<Capabilities, Se050Error> {
+        self.require_enabled()?;
+        let capabilities = self.refresh_app_info(delay)?.capabilities();
+        debug!("SE050 capabilities OK");
+        Ok(capabilities)
+    }
+
+    //###########################################################################
+    //AN12413, // 4.19 Generic management commands //4.19.1 GetVersion  P.108 -109
+    // RSA key sizes in bits (smallest, largest) the applet accepts, for its AppletConfig
+    // read fresh with GetVersion; write_rsa_key checks against the SELECT response.
+
+    #[inline(never)]
+    fn rsa_key_size_range(&mut self, delay: &mut DelayWrapper) -> Result<(u16, u16), Se050Error> {
+        self.require_enabled()?;
+        let range = self.refresh_app_info(delay)?.rsa_key_size_range().ok_or_else(|| {
+            error!("SE050 rsa_key_size_range: RSA not available");
+            Se050Error::RsaUnavailable })?;
+        debug!("SE050 rsa_key_size_range OK");
+        Ok(range)
+    }
+
+    //###########################################################################
+    //See AN12413, // 4.19 Generic management commands // P2_VARIANT, P2_PARAM (Table 23)
+    // AN12413 defines the P2 constants but not the responses: TLV[TAG_1] is copied
+    // into `out` as is. The variant identifies the product (SE050A1, A2, B1, C1, ...),
+    // for feature checks prefer Se050AppInfo::features() from the SELECT response.
+    // Returns the number of bytes written.
+
+    #[inline(never)]
+    fn get_applet_variant(&mut self, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        self.read_mgmt_info(Se050ApduP2::Variant, out, delay)
+    }
+
+    #[inline(never)]
+    fn get_applet_param(&mut self, out: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        self.read_mgmt_info(Se050ApduP2::Param, out, delay)
     }
 
 
@@ -5829,6 +8736,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     #[allow(unused_mut)]
     fn get_timestamp(&mut self, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
 
         
         let mut capdu = CApdu::new(
@@ -5870,9 +8778,11 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     // TLV[TAG_1] Memory
 
     #[inline(never)]
-    fn get_free_memory(&mut self, memoryconstant: &[u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+    fn get_free_memory(&mut self, memoryconstant: Se050ApduMemoryType, delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        let memoryconstant: &[u8] = &[memoryconstant.into()];
 
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &memoryconstant);
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, memoryconstant).map_err(|_| Se050Error::UnknownError)?;
 
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5901,6 +8811,19 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
         debug!("SE050 get_free_memory OK");
         Ok(())
     }
+
+    #[inline(never)]
+    fn get_memory_info(&mut self, delay: &mut DelayWrapper) -> Result<MemoryInfo, Se050Error> {
+        self.require_enabled()?;
+        let info = MemoryInfo {
+            persistent: self.read_free_memory(Se050ApduMemoryType::Persistent, delay)?,
+            transient_reset: self.read_free_memory(Se050ApduMemoryType::TransientReset, delay)?,
+            transient_deselect: self.read_free_memory(Se050ApduMemoryType::TransientDeselect, delay)?,
+        };
+
+        debug!("SE050 get_memory_info OK");
+        Ok(info)
+    }
  
     
     //###########################################################################
@@ -5922,6 +8845,8 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
     #[inline(never)]
     #[allow(unused_mut)]
     fn delete_all(&mut self,  delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        self.require_authenticated()?;
         
         let mut capdu = CApdu::new(
             ApduClass::ProprietaryPlain,
@@ -5976,7 +8901,7 @@ fn write_aes_key(&mut self, key: &[u8], delay: &mut DelayWrapper) -> Result<(),
 
     debug!("Se050 crate: SE050 delete_secure_object DEBUG  ");
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
 
@@ -6032,10 +8957,10 @@ fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se
     
     debug!("Se050 crate: SE050 GenP256 DEBUG  tlv1");
     //let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0xae, 0x59, 0xae, 0x59]);
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xe8, 0xa0, 0x06]);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &[0x20, 0xe8, 0xa0, 0x06]).map_err(|_| Se050Error::UnknownError)?;
   
     debug!("Se050 crate: SE050 GenP256 DEBUG  tlv2");
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x03]);	// NIST P-256
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x03]).map_err(|_| Se050Error::UnknownError)?;	// NIST P-256
     let mut capdu = CApdu::new(
         ApduClass::ProprietaryPlain,
 
@@ -6100,10 +9025,11 @@ fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se
    #[inline(never)]
    fn get_random(&mut self, buf: &mut [u8], delay: &mut DelayWrapper) -> Result<(), Se050Error> 
    {
+       self.require_enabled()?;
        let mut buflen: [u8; 2] = [0, 0];
        BE::write_u16(&mut buflen, buf.len() as u16);
 
-       let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &buflen);
+       let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &buflen).map_err(|_| Se050Error::UnknownError)?;
 
        let mut capdu = CApdu::new(
            ApduClass::ProprietaryPlain,
@@ -6129,11 +9055,11 @@ fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se
 
        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
            error!("Se050 crate: SE050 GetRandom Return TLV Missing");
-           Se050Error::UnknownError })?;
+           Se050Error::MissingResponseTlv })?;
 
        if tlv1_ret.get_data().len() != buf.len() {
            error!("Se050 crate: SE050 GetRandom Length Mismatch");
-           return Err(Se050Error::UnknownError);
+           return Err(Se050Error::MissingResponseTlv);
        }
 
        buf.copy_from_slice(tlv1_ret.get_data());
@@ -6145,6 +9071,24 @@ fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se
 
        Ok(())
    }
+
+    //###########################################################################
+    // Seed for a host-side CSPRNG (e.g. rand_chacha::ChaCha20Rng::from_seed), drawn
+    // from the SE050 TRNG with GetRandom (AN12413, 4.19.4 P.110).
+    // Call it once at startup and let the software RNG stretch the seed: every
+    // GetRandom is an I2C round trip, so it is a poor source for bulk randomness.
+    // On error `seed` is left untouched and must not be used.
+
+    #[inline(never)]
+    fn seed_rng(&mut self, seed: &mut [u8; 32], delay: &mut DelayWrapper) -> Result<(), Se050Error> {
+        self.require_enabled()?;
+        let mut buf = [0u8; 32];
+        self.get_random(&mut buf, delay)?;
+        seed.copy_from_slice(&buf);
+
+        debug!("SE050 seed_rng OK");
+        Ok(())
+    }
  
 
  
@@ -6162,16 +9106,17 @@ see NXP AN12413 -> Secure Objects -> Default Configuration */
 //fn generate_p256_key(&mut self, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
    
    fn generate_p256_key(&mut self,objectidentifier: &[u8;4] , delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+       self.require_enabled()?;
   
     debug!("Se050 crate: SE050 GenP256 DEBUG  tlv1");
      
    // let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xe8, 0xa1, 0x01]);
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
   
     debug!("Se050 crate: SE050 GenP256 DEBUG  tlv2");
 
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x03]);	// NIST P-256
+    let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &[0x03]).map_err(|_| Se050Error::UnknownError)?;	// NIST P-256
    
     let mut capdu = CApdu::new(
     
@@ -6226,69 +9171,363 @@ see NXP AN12413 -> Secure Objects -> Default Configuration */
 
 //##############################################################################
 //###########################################################################
-    /* ASSUMPTION: SE050 is provisioned with an instantiated ECC curve object; */
-    
     //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey    P.58
-    //P1_EC 4.3.19 ECCurve P.42
+    // Kept for existing callers; same as generate_ed25519_key.
+    #[inline(never)]
+    fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
+        self.generate_ed25519_key(objectidentifier, delay)
+    }
 
- 
+//###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey    P.58
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte curve identifier, ID_ECC_MONT_DH_25519
+    #[inline(never)]
+    fn generate_x25519_key(&mut self, objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+        let curve = [Se050ECCurveconstants::IdEccMontDh25519 as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &curve).map_err(|_| Se050Error::UnknownError)?;
 
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Write),
+            Se050ApduP1CredType::EC | Se050ApduP1KeyType::KeyPair,
+            Se050ApduP2::Default.into(),
+            None
+        );
 
+        capdu.push(tlv1);
+        capdu.push(tlv2);
 
-#[inline(never)]
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error> {
-   // let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xe8, 0xa0, 0x02]);
-  //  let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), &[0x20, 0xe8, 0xa1, 0x02]);
-      let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);
-   
-    debug!("Se050 crate: SE050 Gened255 DEBUG  tlv1");
-   
-    let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), &[0x40]);	// Se050ECCurveconstants //ED255
-   
-    debug!("Se050 crate: SE050 Gened255 DEBUG  tlv2");
-   
-    let mut capdu = CApdu::new(
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-        ApduClass::ProprietaryPlain,
-       
-        Into::<u8>::into(Se050ApduInstruction::Write),
+        if rapdu.sw != 0x9000 {
+            error!("SE050 generate_x25519_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-        Se050ApduP1CredType::EC | Se050ApduP1KeyType::KeyPair,
-      
-        Se050ApduP2::Default.into(),
-       
-        None
-    );
+        debug!("SE050 generate_x25519_key OK");
+        Ok(ObjectId(*objectidentifier))
+    }
 
-    capdu.push(tlv1);
+//###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey    P.58
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte curve identifier, ID_ECC_ED_25519
+    // The public key is read back with ReadObject (4.7.3.1, P.65) and must be 32 bytes;
+    // sign with eddsa_sign and EdSignatureAlgo SIG_ED25519PURE.
+    #[inline(never)]
+    fn generate_ed25519_key(&mut self, objectidentifier: &[u8;4], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+        let curve = [Se050ECCurveconstants::IdEccEd25519 as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &curve).map_err(|_| Se050Error::UnknownError)?;
 
-    debug!("Se050 crate: SE050 Gened255 DEBUG pushtlv1");
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Write),
+            Se050ApduP1CredType::EC | Se050ApduP1KeyType::KeyPair,
+            Se050ApduP2::Default.into(),
+            None
+        );
 
-    capdu.push(tlv2);
+        capdu.push(tlv1);
+        capdu.push(tlv2);
 
-    debug!("Se050 crate: SE050 Gened255 DEBUG pushtlv2");
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    self.t1_proto
-        .send_apdu(&capdu, delay)
-        .map_err(|_| Se050Error::UnknownError)?;
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
 
-    let mut rapdu_buf: [u8; 16] = [0; 16];
+        if rapdu.sw != 0x9000 {
+            error!("SE050 generate_ed25519_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
 
-    let rapdu = self.t1_proto
-        .receive_apdu(&mut rapdu_buf, delay)
-        .map_err(|_| Se050Error::UnknownError)?;
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
 
-    if rapdu.sw != 0x9000 {
-        error!("Se050 crate: Generation ED255 Failed: {:x}", rapdu.sw);
-        return Err(Se050Error::UnknownError);
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Read),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Default.into(),
+            Some(0x00)
+        );
+
+        capdu.push(tlv1);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 260] = [0; 260];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 generate_ed25519_key Readback Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 generate_ed25519_key Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        if tlv1_ret.get_data().len() != Se050ECCurveconstants::IdEccEd25519.public_key_len() {
+            error!("SE050 generate_ed25519_key Public Key Length Mismatch: {}", tlv1_ret.get_data().len());
+            return Err(Se050Error::MissingResponseTlv);
+        }
+
+        debug!("SE050 generate_ed25519_key OK");
+        Ok(ObjectId(*objectidentifier))
     }
 
-    debug!("Se050 crate: SE050 ED255 OK");
-    
-    Ok(ObjectId([0x20, 0xe8, 0xa1, 0x02]))
+//###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey    P.58
+    // Imports an external public key only, e.g. to verify signatures with ecdsa_verify
+    // or to keep a peer key for repeated ECDH. The key is checked against the curve first.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte curve identifier
+    //TLV[TAG_4] public key, see Se050ECCurveconstants::public_key_len
+    #[inline(never)]
+    fn write_ec_public_key(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, publickey: &[u8], delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+        curve.check_public_key(publickey)?;
 
-}
+        let curveid = [curve as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &curveid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, publickey).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Write),
+            Se050ApduP1CredType::EC | Se050ApduP1KeyType::PublicKey,
+            Se050ApduP2::Default.into(),
+            None
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv4);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 write_ec_public_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 write_ec_public_key OK");
+        Ok(ObjectId(*objectidentifier))
+    }
+
+    //###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey P.58-59
+    // Imports a known private key, e.g. to check signatures against published test vectors.
+    // The scalar is range checked before sending (curves with a known order only, see
+    // Se050ECCurveconstants::order). The driver does no EC arithmetic: without `publickey`
+    // only the private key (P1_PRIVATE) is written, with it the object is a key pair.
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte curve identifier
+    //TLV[TAG_3] private key value
+    //TLV[TAG_4] public key value [Optional]
+    #[inline(never)]
+    fn write_ec_private_key(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, privatekey: &[u8], publickey: Option<&[u8]>, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+        curve.check_private_key(privatekey)?;
+        if let Some(publickey) = publickey {
+            curve.check_public_key(publickey)?;
+        }
+
+        let curveid = [curve as u8];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &curveid).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, privatekey).map_err(|_| Se050Error::UnknownError)?;
+
+        let keytype = match publickey {
+            Some(_) => Se050ApduP1KeyType::KeyPair,
+            None => Se050ApduP1KeyType::PrivateKey,
+        };
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Write),
+            Se050ApduP1CredType::EC | keytype,
+            Se050ApduP2::Default.into(),
+            None
+        );
+
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+        if let Some(publickey) = publickey {
+            let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, publickey).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlv4);
+        }
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 write_ec_private_key Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 write_ec_private_key OK");
+        Ok(ObjectId(*objectidentifier))
+    }
+
+    //###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.1 WriteECKey P.58-59
+    // Generates a key pair on `curve` inside the SE with the policy, memory and
+    // authentication settings of `config`. A transient key (INS_TRANSIENT) is lost on
+    // reset and its private part is never written to flash, e.g. for ephemeral ECDH.
+    //TLV[TAG_POLICY] policy [Optional]
+    //TLV[TAG_MAX_ATTEMPTS] 2-byte maximum number of attempts [Optional: authentication objects]
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 1-byte curve identifier
+    #[inline(never)]
+    fn generate_ec_key_with_config(&mut self, objectidentifier: &[u8;4], curve: Se050ECCurveconstants, config: &KeyConfig, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+
+        let curveid = [curve as u8];
+        let attempts = config.max_attempts_bytes();
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, &curveid).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            config.instruction(),
+            Se050ApduP1CredType::EC | Se050ApduP1KeyType::KeyPair,
+            Se050ApduP2::Default.into(),
+            None
+        );
+
+        if let Some(policy) = config.policy {
+            let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlvp);
+        }
+        if let Some(attempts) = attempts.as_ref() {
+            let tlvm = SimpleTlv::with_tag(Se050TlvTag::MaxAttempts, attempts).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlvm);
+        }
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if config.transient && rapdu.sw == SW_FILE_FULL {
+            error!("SE050 generate_ec_key_with_config: no transient memory");
+            return Err(Se050Error::TransientUnavailable);
+        }
+        if rapdu.sw != 0x9000 {
+            error!("SE050 generate_ec_key_with_config Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 generate_ec_key_with_config OK");
+        Ok(ObjectId(*objectidentifier))
+    }
+
+    //###########################################################################
+    //AN12413 //4.7 Secure Object management //4.7.1 WriteSecureObject //4.7.1.3 WriteSymmKey P.60-61
+    // Writes an AES, DES or HMAC key with the settings of `config`; with `kek` the
+    // value is RFC3394 wrapped and unwrapped by the applet, see unwrap_key.
+    //TLV[TAG_POLICY] policy [Optional]
+    //TLV[TAG_MAX_ATTEMPTS] 2-byte maximum number of attempts [Optional: authentication objects]
+    //TLV[TAG_1] 4-byte object identifier
+    //TLV[TAG_2] 4-byte KEK identifier [Optional]
+    //TLV[TAG_3] key value
+    #[inline(never)]
+    fn write_symm_key_with_config(&mut self, objectidentifier: &[u8;4], keytype: SymmKeyType, kek: Option<&[u8;4]>, key: &[u8], config: &KeyConfig, delay: &mut DelayWrapper) -> Result<ObjectId, Se050Error>
+    {
+        self.require_enabled()?;
+        if keytype == SymmKeyType::Des && kek.is_none() {
+            self.check_des_key(key)?;
+        }
+
+        let attempts = config.max_attempts_bytes();
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, key).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            config.instruction(),
+            keytype.p1().into(),
+            Se050ApduP2::Default.into(),
+            None
+        );
+
+        if let Some(policy) = config.policy {
+            let tlvp = SimpleTlv::with_tag(Se050TlvTag::Policy, policy).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlvp);
+        }
+        if let Some(attempts) = attempts.as_ref() {
+            let tlvm = SimpleTlv::with_tag(Se050TlvTag::MaxAttempts, attempts).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlvm);
+        }
+        capdu.push(tlv1);
+        if let Some(kek) = kek {
+            let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, kek).map_err(|_| Se050Error::UnknownError)?;
+            capdu.push(tlv2);
+        }
+        capdu.push(tlv3);
+
+        self.t1_proto
+            .send_apdu(&capdu, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        let mut rapdu_buf: [u8; 16] = [0; 16];
+        let rapdu = self.t1_proto
+            .receive_apdu(&mut rapdu_buf, delay)
+            .map_err(|_| Se050Error::UnknownError)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 write_symm_key_with_config Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 write_symm_key_with_config OK");
+        Ok(ObjectId(*objectidentifier))
+    }
 
 
 //###########################################################################
@@ -6296,10 +9535,11 @@ fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut Del
     #[inline(never)]
     fn delete_secure_object(&mut self,objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result< (), Se050Error>
     {   
+        self.require_enabled()?;
 
     debug!("Se050 crate: SE050 delete_secure_object DEBUG\n ");
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
 
@@ -6348,9 +9588,10 @@ fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut Del
     #[inline(never)]
     fn check_object_exists(&mut self,buf: &mut [u8], objectidentifier: &[u8;4] ,  delay: &mut DelayWrapper) -> Result<(), Se050Error>
     {   
+        self.require_enabled()?;
         debug!("Se050 crate: SE050 check_object_exist DEBUG \n");
 
-    let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectidentifier);  
+    let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectidentifier).map_err(|_| Se050Error::UnknownError)?;  
     
     let mut capdu = CApdu::new(
     ApduClass::ProprietaryPlain,
@@ -6381,10 +9622,12 @@ fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut Del
 
     let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
         error!("Se050 crate: SE050 Check object exist TLV Missing\n");
-        Se050Error::UnknownError })?;
+        Se050Error::MissingResponseTlv })?;
  
- 
-
+    if tlv1_ret.get_data().len() != buf.len() {
+        error!("SE050 check_object_exists Length Mismatch");
+        return Err(Se050Error::MissingResponseTlv);
+    }
     buf.copy_from_slice(tlv1_ret.get_data());
 
 //buf.clone_from_slice(tlv1_ret.get_data());
@@ -6428,12 +9671,14 @@ fn generate_ed255_key_pair(&mut self, objectidentifier: &[u8;4] ,delay: &mut Del
     //P1_EC 4.3.19 ECCurve P.42
     #[inline(never)]
     //fn write_ec_key(&mut self,policy: &[u8],  objectid: &[u8;4], eccurve: &[u8], private_key_value: &[u8],  delay: &mut DelayWrapper) -> Result<(), Se050Error>  
-    fn write_ec_key(&mut self,   objectid: &[u8;4], eccurve: &[u8],    delay: &mut DelayWrapper) -> Result<(), Se050Error>  
+    fn write_ec_key(&mut self,   objectid: &[u8;4], eccurve: Se050ECCurveconstants,    delay: &mut DelayWrapper) -> Result<(), Se050Error>  
          
     { 
+        let eccurve: &[u8] = &[eccurve.into()];
+        self.require_enabled()?;
        // let tlvp = SimpleTlv::new(Se050TlvTag::Policy.into(), &policy);        
-        let tlv1 = SimpleTlv::new(Se050TlvTag::Tag1.into(), objectid);        
-        let tlv2 = SimpleTlv::new(Se050TlvTag::Tag2.into(), & eccurve);	// Se050ECCurveconstants
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, objectid).map_err(|_| Se050Error::UnknownError)?;        
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2,  eccurve).map_err(|_| Se050Error::UnknownError)?;	// Se050ECCurveconstants
       //  let tlv3 = SimpleTlv::new(Se050TlvTag::Tag3.into(), &private_key_value );	 
 
         let mut capdu = CApdu::new(
@@ -0,0 +1,151 @@
+use crate::types::*;
+use crate::se050::*;
+use crate::keywrap::SymmKeyType;
+use crate::transient::{EPHEMERAL_OBJECT_ID_START, TransientObject};
+
+//////////////////////////////////////////////////////////////////////////////
+// One-call health check for field diagnostics: interface soft reset, GP SELECT,
+// GetVersion, GetRandom and optionally an ECDSA P-256 sign/verify round trip on a
+// transient key pair generated for the test and deleted afterwards. Each step only runs if
+// the previous ones passed; the report records how far it got.
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SelfTestReport {
+    pub reset: bool,
+    pub select: bool,
+    pub version: bool,
+    pub random: bool,
+    // None if no key slot was given for the round trip
+    pub sign_verify: Option<bool>,
+}
+
+// Wall time of representative commands, measured with the Clock of the DelayWrapper,
+// for choosing application timeouts and whether to batch operations. Each figure
+// covers one command, from sending it to receiving the response.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BenchReport {
+    // GetRandom of 32 bytes
+    pub get_random_ms: u32,
+    // ECDSASign of a SHA-256 digest with a P-256 key pair
+    pub p256_sign_ms: u32,
+    // CipherOneShot AES_ECB_NOPAD of BENCH_AES_LEN bytes with a 128-bit key
+    pub aes_encrypt_ms: u32,
+}
+
+pub const BENCH_AES_LEN: usize = 64;
+
+impl SelfTestReport {
+    pub fn passed(&self) -> bool {
+        self.reset && self.select && self.version && self.random && self.sign_verify != Some(false)
+    }
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    // `sign_key`: object identifier for the temporary P-256 key pair, must be unused
+    #[inline(never)]
+    pub fn self_test(&mut self, sign_key: Option<&[u8; 4]>, delay: &mut DelayWrapper) -> Result<SelfTestReport, Se050Error> {
+        let mut report = SelfTestReport { reset: self.soft_reset(delay).is_ok(), ..Default::default() };
+        if !report.reset {
+            return Ok(report);
+        }
+        report.select = self.select(delay).is_ok();
+        if !report.select {
+            return Ok(report);
+        }
+        report.version = self.get_version(delay).is_ok();
+        if !report.version {
+            return Ok(report);
+        }
+        let mut random: [u8; 16] = [0; 16];
+        report.random = self.get_random(&mut random, delay).is_ok();
+        if !report.random {
+            return Ok(report);
+        }
+
+        if let Some(sign_key) = sign_key {
+            report.sign_verify = Some(self.self_test_sign_verify(sign_key, &random, delay).is_ok_and(|ok| ok));
+        }
+
+        debug!("SE050 self_test: {:?}", report);
+        Ok(report)
+    }
+
+    fn self_test_sign_verify(&mut self, sign_key: &[u8; 4], digest: &[u8; 16], delay: &mut DelayWrapper) -> Result<bool, Se050Error> {
+        let algo = Se050ECSignatureAlgo::SigEcdsaSha256;
+        // the "digest" is padded to the 32 bytes the algorithm expects
+        let mut hash: [u8; 32] = [0; 32];
+        hash[0..16].copy_from_slice(digest);
+
+        let config = KeyConfig { transient: true, ..Default::default() };
+        self.generate_ec_key_with_config(sign_key, Se050ECCurveconstants::NistP256, &config, delay)?;
+        let mut key = TransientObject::new(self, delay, *sign_key);
+        let (se050, delay, id) = key.parts();
+
+        let mut signature: [u8; 80] = [0; 80];
+        let len = se050.ecdsa_sign(id, algo, &hash, &mut signature, delay)?;
+        se050.ecdsa_verify_result(id, algo, &hash, &signature[0..len], delay)
+    }
+
+    // The keys are created in transient memory under a free identifier from
+    // EPHEMERAL_OBJECT_ID_START on and deleted again; their setup is not timed.
+    #[inline(never)]
+    pub fn benchmark(&mut self, delay: &mut DelayWrapper) -> Result<BenchReport, Se050Error> {
+        self.require_enabled()?;
+        if !delay.has_clock() {
+            error!("SE050 benchmark: no clock");
+            return Err(Se050Error::ClockUnavailable);
+        }
+        let mut report = BenchReport::default();
+
+        let mut random: [u8; 32] = [0; 32];
+        let start = bench_start(delay);
+        self.get_random(&mut random, delay)?;
+        report.get_random_ms = bench_elapsed(delay, start);
+
+        let id = self.find_free_object_id(EPHEMERAL_OBJECT_ID_START, delay)?;
+        let config = KeyConfig { transient: true, ..Default::default() };
+
+        report.p256_sign_ms = self.bench_p256_sign(&id.0, &random, &config, delay)?;
+        report.aes_encrypt_ms = self.bench_aes_encrypt(&id.0, &random[0..16], &config, delay)?;
+
+        debug!("SE050 benchmark: {:?}", report);
+        Ok(report)
+    }
+
+    fn bench_p256_sign(&mut self, objectid: &[u8; 4], digest: &[u8; 32], config: &KeyConfig, delay: &mut DelayWrapper) -> Result<u32, Se050Error> {
+        self.generate_ec_key_with_config(objectid, Se050ECCurveconstants::NistP256, config, delay)?;
+        let mut key = TransientObject::new(self, delay, *objectid);
+        let (se050, delay, id) = key.parts();
+
+        let mut signature: [u8; 80] = [0; 80];
+        let start = bench_start(delay);
+        se050.ecdsa_sign(id, Se050ECSignatureAlgo::SigEcdsaSha256, digest, &mut signature, delay)?;
+        let elapsed = bench_elapsed(delay, start);
+        key.delete()?;
+        Ok(elapsed)
+    }
+
+    fn bench_aes_encrypt(&mut self, objectid: &[u8; 4], keyvalue: &[u8], config: &KeyConfig, delay: &mut DelayWrapper) -> Result<u32, Se050Error> {
+        self.write_symm_key_with_config(objectid, SymmKeyType::Aes, None, keyvalue, config, delay)?;
+        let mut key = TransientObject::new(self, delay, *objectid);
+        let (se050, delay, id) = key.parts();
+
+        let mut block: [u8; BENCH_AES_LEN] = [0; BENCH_AES_LEN];
+        let start = bench_start(delay);
+        se050.cipher_one_shot_encrypt_into(id, Se050CipherModeconstants::AesEcbNopad, &[0; BENCH_AES_LEN], &[], &mut block, delay)?;
+        let elapsed = bench_elapsed(delay, start);
+        key.delete()?;
+        Ok(elapsed)
+    }
+}
+
+fn bench_start(delay: &mut DelayWrapper) -> u32 {
+    delay.now_ms().unwrap_or(0)
+}
+
+fn bench_elapsed(delay: &mut DelayWrapper, start: u32) -> u32 {
+    delay.now_ms().unwrap_or(start).wrapping_sub(start)
+}
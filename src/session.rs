@@ -0,0 +1,263 @@
+use crate::types::*;
+use crate::se050::*;
+use byteorder::{ByteOrder, BE};
+
+//////////////////////////////////////////////////////////////////////////////
+//See AN12413, 4.5 Session management
+// Handle for an open session, created by Se050::open_session().
+// Every command issued through the handle is wrapped in ProcessSessionCmd
+// with the session identifier returned by CreateSession.
+// Dropping the handle sends CloseSession; use close() to observe its result.
+// Responses are received into a buffer of N bytes owned by the handle, shared by
+// all of its commands instead of a stack array per call; the default fits every
+// response of the commands below. Pick a size with Se050::open_session_with_scratch.
+
+pub const SESSION_SCRATCH_LEN: usize = 260;
+
+pub struct Se050Session<'a, T, const N: usize = SESSION_SCRATCH_LEN>
+where
+    T: T1Proto,
+{
+    se050: &'a mut Se050<T>,
+    delay: &'a mut DelayWrapper,
+    session_id: [u8; 8],
+    open: bool,
+    scratch: [u8; N],
+}
+
+impl<'a, T, const N: usize> Se050Session<'a, T, N>
+where
+    T: T1Proto,
+{
+    pub(crate) fn new(se050: &'a mut Se050<T>, delay: &'a mut DelayWrapper, session_id: [u8; 8]) -> Self {
+        Self { se050, delay, session_id, open: true, scratch: [0; N] }
+    }
+
+    pub fn session_id(&self) -> &[u8; 8] {
+        &self.session_id
+    }
+
+    //###########################################################################
+    //See AN12413 , 4.5 Session management //4.5.2 UserID session operations // 4.5.2.1 VerifySessionUserID P.51-52
+    //TLV[TAG_1] UserID value
+
+    #[inline(never)]
+    pub fn verify_user_id(&mut self, user_idvalue: &[u8]) -> Result<(), Se050Error> {
+        let sw = self.send_verify_user_id(user_idvalue)?;
+        if sw != 0x9000 {
+            error!("SE050 session verify_user_id Failed: {:x}", sw);
+            return Err(verification_error(sw));
+        }
+
+        debug!("SE050 session verify_user_id OK");
+        Ok(())
+    }
+
+    // VerifySessionUserID returning the status word, for callers that interpret failures
+    pub(crate) fn send_verify_user_id(&mut self, user_idvalue: &[u8]) -> Result<u16, Se050Error> {
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, user_idvalue).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::SessionUserID.into(),
+            None
+        );
+        capdu.push(tlv1);
+
+        let rapdu = self.se050.process_in_session(&self.session_id, &capdu, &mut self.scratch, self.delay)?;
+
+        if rapdu.sw == 0x9000 {
+            self.se050.set_session_state(SessionState::UserId);
+        }
+        Ok(rapdu.sw)
+    }
+
+    //###########################################################################
+    //See AN12413, 4.19 Generic management commands //4.19.4 GetRandom P.110
+    //TLV[TAG_1] 2-byte requested size.
+
+    #[inline(never)]
+    pub fn get_random(&mut self, buf: &mut [u8]) -> Result<(), Se050Error> {
+        let mut buflen: [u8; 2] = [0, 0];
+        BE::write_u16(&mut buflen, buf.len() as u16);
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, &buflen).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Se050ApduInstruction::Mgmt.into(),
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::Random.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+
+        let rapdu = self.se050.process_in_session(&self.session_id, &capdu, &mut self.scratch, self.delay)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 session get_random Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 session get_random Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        if tlv1_ret.get_data().len() != buf.len() {
+            error!("SE050 session get_random Length Mismatch");
+            return Err(Se050Error::MissingResponseTlv);
+        }
+        buf.copy_from_slice(tlv1_ret.get_data());
+
+        debug!("SE050 session get_random OK");
+        Ok(())
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.10 Crypto operations EC // 4.10.1 Signature generation // 4.10.1.1 ECDSASign P.76-77
+    //TLV[TAG_1]  4-byte identifier of EC key pair or private key.
+    //TLV[TAG_2] 1-byte ECSignatureAlgo.
+    //TLV[TAG_3] Byte array containing input data.
+    // Returns the length of the signature written to `signature`.
+
+    #[inline(never)]
+    pub fn ecdsa_sign(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, inputdata: &[u8], signature: &mut [u8]) -> Result<usize, Se050Error> {
+        let ecsignaturealgo: &[u8] = &[ecsignaturealgo.into()];
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, eckeyidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ecsignaturealgo).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Signature.into(),
+            Se050ApduP2::Sign.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+
+        let rapdu = self.se050.process_in_session(&self.session_id, &capdu, &mut self.scratch, self.delay)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 session ecdsa_sign Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 session ecdsa_sign Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let sig = tlv1_ret.get_data();
+        if sig.len() > signature.len() {
+            error!("SE050 session ecdsa_sign Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        signature[0..sig.len()].copy_from_slice(sig);
+
+        debug!("SE050 session ecdsa_sign OK");
+        Ok(sig.len())
+    }
+
+    //###########################################################################
+    //See AN12413 //4.12 Crypto operations AES/DES //4.12.4 CipherOneShot P.87
+    //TLV[TAG_1] 4-byte identifier of the key object.
+    //TLV[TAG_2] 1-byte CipherMode
+    //TLV[TAG_3] Byte array containing input data.
+    //TLV[TAG_4] Byte array containing an initialization vector. [Optional, omitted if empty]
+    // Returns the length of the ciphertext written to `output`.
+
+    #[inline(never)]
+    pub fn cipher_one_shot_encrypt(&mut self, keyobjectidentifier: &[u8;4], ciphermode: Se050CipherModeconstants, inputdata: &[u8], initializationvector: &[u8], output: &mut [u8]) -> Result<usize, Se050Error> {
+        check_cipher_iv(ciphermode, initializationvector)?;
+        self.se050.check_des_mode(ciphermode)?;
+        let ciphermode: &[u8] = &[ciphermode.into()];
+
+        let tlv1 = SimpleTlv::with_tag(Se050TlvTag::Tag1, keyobjectidentifier).map_err(|_| Se050Error::UnknownError)?;
+        let tlv2 = SimpleTlv::with_tag(Se050TlvTag::Tag2, ciphermode).map_err(|_| Se050Error::UnknownError)?;
+        let tlv3 = SimpleTlv::with_tag(Se050TlvTag::Tag3, inputdata).map_err(|_| Se050Error::UnknownError)?;
+        let tlv4 = SimpleTlv::with_tag(Se050TlvTag::Tag4, initializationvector).map_err(|_| Se050Error::UnknownError)?;
+
+        let mut capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Crypto) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Cipher.into(),
+            Se050ApduP2::Encrypt.into(),
+            Some(0x00)
+        );
+        capdu.push(tlv1);
+        capdu.push(tlv2);
+        capdu.push(tlv3);
+        if !initializationvector.is_empty() {
+            capdu.push(tlv4);
+        }
+
+        let rapdu = self.se050.process_in_session(&self.session_id, &capdu, &mut self.scratch, self.delay)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 session cipher_one_shot_encrypt Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        let tlv1_ret = rapdu.get_tlv(Se050TlvTag::Tag1.into()).ok_or_else(|| {
+            error!("SE050 session cipher_one_shot_encrypt Return TLV Missing");
+            Se050Error::MissingResponseTlv })?;
+
+        let enc = tlv1_ret.get_data();
+        if enc.len() > output.len() {
+            error!("SE050 session cipher_one_shot_encrypt Buffer Too Small");
+            return Err(Se050Error::UnknownError);
+        }
+        output[0..enc.len()].copy_from_slice(enc);
+
+        debug!("SE050 session cipher_one_shot_encrypt OK");
+        Ok(enc.len())
+    }
+
+    //###########################################################################
+    //See AN12413 , 4.5 Session management // 4.5.1 Generic session commands 4.5.1.5 CloseSession P.50
+    //If CloseSession returns a Status Word different from SW_NO_ERROR, the applet immediately needs to be reselected.
+
+    pub fn close(mut self) -> Result<(), Se050Error> {
+        self.send_close()
+    }
+
+    #[inline(never)]
+    fn send_close(&mut self) -> Result<(), Se050Error> {
+        // the session is gone after the first attempt, whatever its outcome
+        self.open = false;
+        self.se050.set_session_state(SessionState::None);
+
+        let capdu = CApdu::new(
+            ApduClass::ProprietaryPlain,
+            Into::<u8>::into(Se050ApduInstruction::Mgmt) | APDU_INSTRUCTION_TRANSIENT,
+            Se050ApduP1CredType::Default.into(),
+            Se050ApduP2::SessionClose.into(),
+            None
+        );
+
+        let rapdu = self.se050.process_in_session(&self.session_id, &capdu, &mut self.scratch, self.delay)?;
+
+        if rapdu.sw != 0x9000 {
+            error!("SE050 session close Failed: {:x}", rapdu.sw);
+            return Err(Se050Error::UnknownError);
+        }
+
+        debug!("SE050 session close OK");
+        Ok(())
+    }
+}
+
+impl<'a, T, const N: usize> Drop for Se050Session<'a, T, N>
+where
+    T: T1Proto,
+{
+    fn drop(&mut self) {
+        if self.open {
+            self.send_close().ok();
+        }
+    }
+}
@@ -0,0 +1,150 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+// ECDSASign returns, and ECDSAVerify takes, an ASN.1 DER signature
+// SEQUENCE { INTEGER r, INTEGER s }. JWS, COSE and WebAuthn (for some algorithms)
+// use the fixed-size IEEE P1363 form r || s instead, each padded to the length of
+// the group order. The conversion is done here, on the host.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureFormat {
+    // ASN.1 DER, as exchanged with the SE050
+    Der,
+    // r || s, each left-padded with zeros to the curve's scalar_len()
+    Raw,
+}
+
+// longest DER signature of the supported curves (NIST P-521):
+// SEQUENCE header (3 bytes), two INTEGERs of up to 2 + 67 bytes
+pub const MAX_DER_SIGNATURE_LEN: usize = 3 + 2 * (2 + 67);
+
+// Returns the magnitude of the DER INTEGER at the start of `der` and the remaining bytes
+fn der_integer(der: &[u8]) -> Result<(&[u8], &[u8]), Se050Error> {
+    if der.len() < 2 || der[0] != 0x02 || der[1] >= 0x80 || der.len() < 2 + der[1] as usize {
+        error!("SE050 DER signature: malformed INTEGER");
+        return Err(Se050Error::UnknownError);
+    }
+    let (int, rest) = der[2..].split_at(der[1] as usize);
+    // negative, empty, or not minimally encoded
+    if int.is_empty() || int[0] & 0x80 != 0 || (int.len() > 1 && int[0] == 0 && int[1] & 0x80 == 0) {
+        error!("SE050 DER signature: invalid INTEGER");
+        return Err(Se050Error::UnknownError);
+    }
+    let zeros = int.iter().take_while(|b| **b == 0).count();
+    Ok((&int[zeros..], rest))
+}
+
+// Converts a DER signature to r || s with `scalar_len` bytes each; returns the
+// length written to `out` (2 * scalar_len).
+pub fn der_to_raw(der: &[u8], scalar_len: usize, out: &mut [u8]) -> Result<usize, Se050Error> {
+    if der_element_len(der) != Some(der.len()) || der[0] != 0x30 {
+        error!("SE050 DER signature: not a single SEQUENCE");
+        return Err(Se050Error::UnknownError);
+    }
+    let body = &der[if der[1] < 0x80 { 2 } else { 2 + (der[1] & 0x7f) as usize }..];
+    let (r, rest) = der_integer(body)?;
+    let (s, rest) = der_integer(rest)?;
+    if !rest.is_empty() || r.len() > scalar_len || s.len() > scalar_len {
+        error!("SE050 DER signature: does not fit {} byte scalars", scalar_len);
+        return Err(Se050Error::UnknownError);
+    }
+    if out.len() < 2 * scalar_len {
+        error!("SE050 der_to_raw Buffer Too Small");
+        return Err(Se050Error::UnknownError);
+    }
+
+    out[0..2 * scalar_len].fill(0);
+    out[scalar_len - r.len()..scalar_len].copy_from_slice(r);
+    out[2 * scalar_len - s.len()..2 * scalar_len].copy_from_slice(s);
+    Ok(2 * scalar_len)
+}
+
+fn push_der_integer(value: &[u8], out: &mut heapless::Vec<u8, MAX_DER_SIGNATURE_LEN>) -> Result<(), Se050Error> {
+    let zeros = value.iter().take_while(|b| **b == 0).count();
+    // zero is encoded as a single 0x00
+    let value = &value[zeros.min(value.len() - 1)..];
+    let pad = value[0] & 0x80 != 0;
+    out.push(0x02).map_err(|_| Se050Error::UnknownError)?;
+    out.push(value.len() as u8 + pad as u8).map_err(|_| Se050Error::UnknownError)?;
+    if pad {
+        out.push(0x00).map_err(|_| Se050Error::UnknownError)?;
+    }
+    out.extend_from_slice(value).map_err(|_| Se050Error::UnknownError)
+}
+
+// Converts r || s (two halves of equal length) to a DER signature; returns the
+// length written to `out`.
+pub fn raw_to_der(raw: &[u8], out: &mut [u8]) -> Result<usize, Se050Error> {
+    if raw.is_empty() || !raw.len().is_multiple_of(2) || raw.len() > 2 * 66 {
+        error!("SE050 raw signature: invalid length {}", raw.len());
+        return Err(Se050Error::UnknownError);
+    }
+    let (r, s) = raw.split_at(raw.len() / 2);
+    let mut ints: heapless::Vec<u8, MAX_DER_SIGNATURE_LEN> = heapless::Vec::new();
+    push_der_integer(r, &mut ints)?;
+    push_der_integer(s, &mut ints)?;
+
+    let header_len = if ints.len() < 0x80 { 2 } else { 3 };
+    let total = header_len + ints.len();
+    if out.len() < total {
+        error!("SE050 raw_to_der Buffer Too Small");
+        return Err(Se050Error::UnknownError);
+    }
+    out[0] = 0x30;
+    if header_len == 2 {
+        out[1] = ints.len() as u8;
+    } else {
+        out[1] = 0x81;
+        out[2] = ints.len() as u8;
+    }
+    out[header_len..total].copy_from_slice(&ints);
+    Ok(total)
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    //###########################################################################
+    // See AN12413 // 4.10.1.1 ECDSASign P.76-77
+    // As ecdsa_sign, returning the signature in `format`: the length written to
+    // `signature`. For Raw the key's curve is read first (ReadECCurve), to pad r and s.
+
+    #[inline(never)]
+    pub fn ecdsa_sign_formatted(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, inputdata: &[u8], format: SignatureFormat, signature: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, Se050Error> {
+        self.require_enabled()?;
+        let len = match format {
+        SignatureFormat::Der => self.ecdsa_sign(eckeyidentifier, ecsignaturealgo, inputdata, signature, delay)?,
+        SignatureFormat::Raw => {
+            let curve = self.get_eccurve_id(eckeyidentifier, delay)?;
+            let mut der = [0u8; MAX_DER_SIGNATURE_LEN];
+            let len = self.ecdsa_sign(eckeyidentifier, ecsignaturealgo, inputdata, &mut der, delay)?;
+            der_to_raw(&der[0..len], curve.scalar_len(), signature)?
+        },
+        };
+
+        debug!("SE050 ecdsa_sign_formatted OK");
+        Ok(len)
+    }
+
+    //###########################################################################
+    // See AN12413 // 4.10.2.1 ECDSAVerify P.79
+    // As ecdsa_verify, for a signature in `format`; Ok(false) for a bad signature.
+
+    #[inline(never)]
+    pub fn ecdsa_verify_formatted(&mut self, eckeyidentifier: &[u8;4], ecsignaturealgo: Se050ECSignatureAlgo, hashedcomparedata: &[u8], signature: &[u8], format: SignatureFormat, delay: &mut DelayWrapper) -> Result<bool, Se050Error> {
+        self.require_enabled()?;
+        let ok = match format {
+        SignatureFormat::Der => self.ecdsa_verify_result(eckeyidentifier, ecsignaturealgo, hashedcomparedata, signature, delay)?,
+        SignatureFormat::Raw => {
+            let mut der = [0u8; MAX_DER_SIGNATURE_LEN];
+            let len = raw_to_der(signature, &mut der)?;
+            self.ecdsa_verify_result(eckeyidentifier, ecsignaturealgo, hashedcomparedata, &der[0..len], delay)?
+        },
+        };
+
+        debug!("SE050 ecdsa_verify_formatted OK");
+        Ok(ok)
+    }
+}
@@ -1,4 +1,5 @@
 use crate::types::*;
+use core::cell::RefCell;
 use core::convert::{Into, TryInto};
 use byteorder::{ByteOrder, LE, BE};
 
@@ -12,10 +13,32 @@ where
     nad_se2hd: u8,
     iseq_snd: u8,
     iseq_rcv: u8,
+    timeout_ms: u32,
+    elapsed_ms: Option<u32>,
+    // DelayWrapper clock reading when the response budget started
+    started_ms: Option<u32>,
+    edc: T1ErrorDetection,
+    truncate_tlvs: bool,
+    retransmissions: u32,
+    write_error_kind: Option<fn(&<TWI as embedded_hal::blocking::i2c::Write>::Error) -> I2cErrorKind>,
+    read_error_kind: Option<fn(&<TWI as embedded_hal::blocking::i2c::Read>::Error) -> I2cErrorKind>,
+    recorder: Option<FrameRecorder>,
 }
 
 const TWI_RETRIES: usize = 128;
 const TWI_RETRY_DELAY_MS: u32 = 2;
+// ISO 7816-3 11.6.3: requests for one block before the exchange is abandoned
+const MAX_RETRANSMITS: usize = 3;
+// R-block error code: EDC or parity error
+const T1_R_EDC_ERROR: u8 = 0x01;
+// overall budget for receiving one response, independent of BWT and WTX
+const DEFAULT_TIMEOUT_MS: u32 = 10_000;
+
+// worth another attempt: the SE050 NACKs its address while busy; Other is an
+// error the HAL did not classify, retried as before
+fn is_transient(kind: I2cErrorKind) -> bool {
+    matches!(kind, I2cErrorKind::AddressNack | I2cErrorKind::ArbitrationLoss | I2cErrorKind::Other)
+}
 
 #[allow(unused_variables)]
 fn maybe_debug(label: &str, data: &[u8]) {
@@ -27,6 +50,17 @@ fn maybe_debug(label: &str, data: &[u8]) {
     }
 }
 
+// one line per command and per response, in the same format for every APDU
+#[allow(unused_variables)]
+fn log_capdu(cla: ApduClass, ins: u8, p1: u8, p2: u8, le: Option<usize>) {
+    debug!("APDU > CLA {:02x} INS {:02x} P1 {:02x} P2 {:02x} Le {:?}", Into::<u8>::into(cla), ins, p1, p2, le);
+}
+
+#[allow(unused_variables)]
+fn log_rapdu(sw: u16, len: usize) {
+    debug!("APDU < SW {:04x} ({} bytes)", sw, len);
+}
+
 impl<TWI> T1overI2C<TWI>
 where
     TWI: embedded_hal::blocking::i2c::Read + embedded_hal::blocking::i2c::Write,
@@ -40,39 +74,172 @@ where
             nad_se2hd: nad_r,
             iseq_snd: 0,
             iseq_rcv: 0,
+            timeout_ms: DEFAULT_TIMEOUT_MS,
+            elapsed_ms: None,
+            started_ms: None,
+            edc: T1ErrorDetection::Crc,
+            truncate_tlvs: false,
+            retransmissions: 0,
+            write_error_kind: None,
+            read_error_kind: None,
+            recorder: None,
         }
     }
 
+    // gives the bus back, e.g. to hand it to another driver
+    pub fn release(self) -> TWI {
+        self.twi
+    }
+
+    // By default receive_apdu fails with TlvParseError on a response with more than
+    // MAX_TLVS TLVs. With truncation enabled it keeps the first MAX_TLVS and reports
+    // the rest in RApdu::dropped_tlvs; use receive_apdu_raw and tlv_iter to see them.
+    pub fn set_truncate_tlvs(&mut self, enable: bool) {
+        self.truncate_tlvs = enable;
+    }
+
+    // CRC is the SE050 default. The GP DLLP does not advertise the EDC in use, so
+    // the ATR frame of interface_soft_reset() is the validation: with the wrong
+    // EDC it fails its own check.
+    pub fn set_error_detection(&mut self, edc: T1ErrorDetection) {
+        self.edc = edc;
+    }
+
+    pub fn error_detection(&self) -> T1ErrorDetection {
+        self.edc
+    }
+
+    // While a response is awaited, reads are retried (the SE050 NACKs while busy)
+    // until this budget is spent instead of giving up after TWI_RETRIES attempts.
+    // Measured by the delays issued through the DelayWrapper, or in wall time if
+    // the DelayWrapper has a clock.
+    pub fn set_timeout_ms(&mut self, timeout_ms: u32) {
+        self.timeout_ms = timeout_ms;
+    }
+
+    pub fn timeout_ms(&self) -> u32 {
+        self.timeout_ms
+    }
+
+    // Blocks sent again because of a transmission error: response blocks that arrived
+    // with a bad checksum and were requested again, and chained command blocks the
+    // card asked for again, counted since creation or the last reset_retransmissions().
+    // A count that keeps climbing points to a noisy bus.
+    pub fn retransmissions(&self) -> u32 {
+        self.retransmissions
+    }
+
+    pub fn reset_retransmissions(&mut self) {
+        self.retransmissions = 0;
+    }
+
+    // Tells the driver how to read the HAL's I2C errors, for TransmitError and
+    // ReceiveError to carry the cause. It also limits the retries: only a NACKed
+    // address (the SE050 is busy) and a lost arbitration are retried, other
+    // faults fail at once. Without it every failure is retried as Other.
+    pub fn set_i2c_error_kinds(
+        &mut self,
+        write: fn(&<TWI as embedded_hal::blocking::i2c::Write>::Error) -> I2cErrorKind,
+        read: fn(&<TWI as embedded_hal::blocking::i2c::Read>::Error) -> I2cErrorKind,
+    ) {
+        self.write_error_kind = Some(write);
+        self.read_error_kind = Some(read);
+    }
+
+    // Installs a recorder, stopped; see FrameRecorder. Returns the previous one.
+    pub fn set_recorder(&mut self, recorder: FrameRecorder) -> Option<FrameRecorder> {
+        self.recorder.replace(recorder)
+    }
+
+    pub fn take_recorder(&mut self) -> Option<FrameRecorder> {
+        self.recorder.take()
+    }
+
+    pub fn start_recording(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.recording = true;
+        }
+    }
+
+    pub fn stop_recording(&mut self) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.recording = false;
+        }
+    }
+
+    // Moves the oldest recorded frames into `out`, as many whole records as fit,
+    // and returns the number of bytes written; 0 without a recorder.
+    pub fn drain_recording(&mut self, out: &mut [u8]) -> usize {
+        self.recorder.as_mut().map_or(0, |recorder| recorder.drain(out))
+    }
+
+    fn record(&mut self, direction: u8, parts: &[&[u8]]) {
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.record(direction, parts);
+        }
+    }
+
+    // accounts `ms` against the response budget, if one is running; with a clock
+    // the budget is charged the time actually passed instead
+    fn spend(&mut self, ms: u32, delay: &mut DelayWrapper) -> Result<(), T1Error> {
+        if let Some(elapsed) = self.elapsed_ms.as_mut() {
+            *elapsed = match (self.started_ms, delay.now_ms()) {
+            (Some(start), Some(now)) => now.wrapping_sub(start),
+            _ => elapsed.saturating_add(ms),
+            };
+            if *elapsed >= self.timeout_ms {
+                error!("T1 timeout after {} ms", elapsed);
+                return Err(T1Error::Timeout);
+            }
+        }
+        Ok(())
+    }
+
     fn twi_write(&mut self, data: &[u8], delay: &mut DelayWrapper) -> Result<(), T1Error> {
         maybe_debug("T1 W", data);
+        let mut kind = I2cErrorKind::Other;
         for _i in 0..TWI_RETRIES {
             let e = self.twi.write(self.se_address as u8, data);
-            if e.is_ok() {
-                trace!("t1w ok({})", i);
+            match e {
+            Ok(()) => {
+                trace!("t1w ok({})", _i);
                 return Ok(());
+            },
+            Err(e) => kind = self.write_error_kind.map_or(I2cErrorKind::Other, |f| f(&e)),
             }
+            if !is_transient(kind) { break; }
             delay.inner.delay_ms(TWI_RETRY_DELAY_MS);
-            // TODO: we should only loop on AddressNack errors
-            // but the existing traits don't provide an API for that
         }
         trace!("t1w err");
-        Err(T1Error::TransmitError)
+        error!("T1 I2C write failed: {:?}", kind);
+        Err(T1Error::TransmitError(kind))
     }
 
     fn twi_read(&mut self, data: &mut [u8], delay: &mut DelayWrapper) -> Result<(), T1Error> {
-        for _i in 0..TWI_RETRIES {
+        let mut i: usize = 0;
+        let mut kind;
+        loop {
             let e = self.twi.read(self.se_address as u8, data);
-            if e.is_ok() {
+            match e {
+            Ok(()) => {
                 maybe_debug("T1 R", data);
                 trace!("t1r ok({})", i);
                 return Ok(());
+            },
+            Err(e) => kind = self.read_error_kind.map_or(I2cErrorKind::Other, |f| f(&e)),
+            }
+            if !is_transient(kind) { break; }
+            i += 1;
+            if self.elapsed_ms.is_some() {
+                self.spend(TWI_RETRY_DELAY_MS, delay)?;
+            } else if i == TWI_RETRIES {
+                break;
             }
             delay.inner.delay_ms(TWI_RETRY_DELAY_MS);
-            // TODO: we should only loop on AddressNack errors
-            // but the existing traits don't provide an API for that
         }
         trace!("t1r err");
-        Err(T1Error::ReceiveError)
+        error!("T1 I2C read failed: {:?}", kind);
+        Err(T1Error::ReceiveError(kind))
     }
 
     #[inline(never)]
@@ -92,18 +259,27 @@ where
             return Err(T1Error::ProtocolError);
         }
         let dlen = header.len as usize;
-        if dlen + 2 > buf.len() {
-            return Err(T1Error::BufferOverrunError(dlen+2));
+        let elen = self.edc.epilogue_len();
+        if dlen + elen > buf.len() {
+            return Err(T1Error::BufferOverrunError(dlen+elen));
         }
-        let mut crc_state = Se050CRC::new();
-        crc_state.update(&buf[0..3]);
+        let mut prologue: [u8; 3] = [0; 3];
+        prologue.copy_from_slice(&buf[0..3]);
 
         // read T1 frame payload
-        self.twi_read(&mut buf[0..dlen + 2], delay)?;
-        header.crc = LE::read_u16(&buf[dlen..dlen + 2]);
+        self.twi_read(&mut buf[0..dlen + elen], delay)?;
+        self.record(FRAME_RECEIVED, &[&prologue, &buf[0..dlen + elen]]);
+        header.crc = self.edc.read(&buf[dlen..dlen + elen]);
 
-        crc_state.update(&buf[0..dlen]);
-        let calculated_crc = crc_state.get();
+        let calculated_crc = match self.edc {
+        T1ErrorDetection::Crc => {
+            let mut crc_state = Se050CRC::new();
+            crc_state.update(&prologue);
+            crc_state.update(&buf[0..dlen]);
+            crc_state.get()
+        },
+        T1ErrorDetection::Lrc => self.edc.calculate(&prologue) ^ self.edc.calculate(&buf[0..dlen]),
+        };
 
         if calculated_crc != header.crc {
             return Err(T1Error::ChecksumError);
@@ -121,10 +297,11 @@ where
         let mut buf = heapless::Vec::<u8, MAX_T1_FRAME_SIZE>::new();
         buf.extend_from_slice(&[self.nad_hd2se, pcb.into(), data.len() as u8]).unwrap();
         buf.extend_from_slice(data).unwrap();
-        let crc = Se050CRC::calculate(buf.as_slice());
+        let crc = self.edc.calculate(buf.as_slice());
         let mut crcbuf: [u8; 2] = [0, 0];
         LE::write_u16(&mut crcbuf, crc);
-        buf.extend_from_slice(&crcbuf).unwrap();
+        buf.extend_from_slice(&crcbuf[0..self.edc.epilogue_len()]).unwrap();
+        self.record(FRAME_SENT, &[buf.as_slice()]);
         self.twi_write(buf.as_slice(), delay)
     }
 
@@ -142,40 +319,113 @@ where
         code: T1SCode,
         data: &mut [u8],
         delay: &mut DelayWrapper,
-    ) -> Result<(), T1Error> {
+    ) -> Result<usize, T1Error> {
         let header = self.receive_frame(data, delay)?;
         match header.pcb {
-        T1PCB::S(scode, true) if code == scode => { Ok(()) },
+        T1PCB::S(scode, true) if code == scode => { Ok(header.len as usize) },
         T1PCB::S(_, _) => { Err(T1Error::ProtocolError) },
         T1PCB::R(_, r) => { Err(T1Error::RCodeReceived(r)) },
         _ => { Err(T1Error::ProtocolError) }
         }
     }
 
+    // Sends the APDU in I-blocks of up to MAX_IFSC bytes. Every block but the last
+    // has the M bit set and is acknowledged by the card with R(N(R)) naming the
+    // next block (ISO 7816-3 11.6.2.3); an R-block naming the same block again
+    // asks for a retransmission, granted up to MAX_RETRANSMITS times per block.
+    // S(WTX) requests before the R-block are answered.
     fn send_apdu_from_iter(&mut self, apdu_iter: &mut CApduByteIterator, delay: &mut DelayWrapper) -> Result<(), T1Error> {
-        let mut peek: Option<u8> = None;
+        let mut peek: Option<u8> = apdu_iter.next();
         let mut buf: heapless::Vec<u8, MAX_IFSC> = heapless::Vec::new();
 
         loop {
             buf.clear();
+            while let Some(b) = peek {
+                if buf.len() == MAX_IFSC { break; }
+                buf.push(b).ok();
+                peek = apdu_iter.next();
+            }
+            let more = peek.is_some();
+            self.send_frame(T1PCB::I(self.iseq_snd, more), buf.as_slice(), delay)?;
+            if !more {
+                self.iseq_snd ^= 1;
+                break;
+            }
+
+            let mut retries: usize = 0;
             loop {
-                let v = apdu_iter.next();
-                if v.is_none() { break; }
-                buf.push(v.unwrap()).ok();
-                if buf.len() == MAX_IFSC {
-                    peek = apdu_iter.next();
-                    break;
+                let mut rbuf: [u8; 8] = [0; 8];
+                let header = self.receive_frame(&mut rbuf, delay)?;
+                match header.pcb {
+                T1PCB::R(seq, _) if seq != self.iseq_snd => break,
+                T1PCB::R(_, _) if retries < MAX_RETRANSMITS => {
+                    retries += 1;
+                    self.retransmissions = self.retransmissions.saturating_add(1);
+                    error!("T1 block rejected, retransmission {} of {}", retries, MAX_RETRANSMITS);
+                    self.send_frame(T1PCB::I(self.iseq_snd, true), buf.as_slice(), delay)?;
+                },
+                T1PCB::R(_, r) => return Err(T1Error::RCodeReceived(r)),
+                T1PCB::S(T1SCode::WTX, false) => {
+                    // the card needs more time before acknowledging, as in receive_frames;
+                    // not a retransmission, the R-block is still awaited
+                    let wtx = rbuf.get(..header.len as usize).ok_or(T1Error::ProtocolError)?;
+                    self.send_frame(T1PCB::S(T1SCode::WTX, true), wtx, delay)?;
+                    self.spend(TWI_RETRY_DELAY_MS, delay)?;
+                    delay.inner.delay_ms(TWI_RETRY_DELAY_MS);
+                },
+                _ => return Err(T1Error::ProtocolError),
                 }
             }
-            self.send_frame(T1PCB::I(self.iseq_snd, peek.is_some()), buf.as_slice(), delay)?;
             self.iseq_snd ^= 1;
-            if peek.is_none() { break; }
-            // receive R(N(R))
-            todo!();
         }
 
         Ok(())
     }
+
+    // collects the I-blocks of one response into `buf`, answering WTX requests on the way
+    // and requesting corrupted blocks again (up to MAX_RETRANSMITS times per block);
+    // returns the number of bytes received
+    fn receive_frames(&mut self, buf: &mut [u8], delay: &mut DelayWrapper) -> Result<usize, T1Error> {
+        let buf_len: usize = buf.len();
+        let mut buf_offset: usize = 0;
+        let mut retries: usize = 0;
+        loop {
+            let header = match self.receive_frame(&mut buf[buf_offset..buf_len], delay) {
+            Err(T1Error::ChecksumError) if retries < MAX_RETRANSMITS => {
+                // ask for the block again, R(N(R)) names the one still expected
+                retries += 1;
+                self.retransmissions = self.retransmissions.saturating_add(1);
+                error!("T1 checksum error, retransmission {} of {}", retries, MAX_RETRANSMITS);
+                self.send_frame(T1PCB::R(self.iseq_rcv, T1_R_EDC_ERROR), &[], delay)?;
+                continue;
+            },
+            r => r?,
+            };
+            retries = 0;
+            match header.pcb {
+            T1PCB::I(seq, multi) => {
+                if seq != self.iseq_rcv {
+                    return Err(T1Error::ProtocolError);
+                }
+                self.iseq_rcv ^= 1;
+                buf_offset += header.len as usize;
+                if !multi { break; }
+                self.send_frame(T1PCB::R(self.iseq_rcv, 0), &[], delay)?;
+            },
+            T1PCB::S(T1SCode::WTX, false) => {
+                // the card needs more time: acknowledge with the same multiplier
+                let mut wtx: heapless::Vec<u8, 4> = heapless::Vec::new();
+                wtx.extend_from_slice(&buf[buf_offset..buf_offset + header.len as usize])
+                    .map_err(|_| T1Error::ProtocolError)?;
+                self.send_frame(T1PCB::S(T1SCode::WTX, true), &wtx, delay)?;
+                self.spend(TWI_RETRY_DELAY_MS, delay)?;
+                delay.inner.delay_ms(TWI_RETRY_DELAY_MS);
+            },
+            _ => {}
+            }
+        }
+        Ok(buf_offset)
+    }
 }
 
 impl<TWI> T1Proto for T1overI2C<TWI>
@@ -184,11 +434,13 @@ where
 {
     #[inline(never)]
     fn send_apdu(&mut self, apdu: &CApdu, delay: &mut DelayWrapper) -> Result<(), T1Error> {
+        log_capdu(apdu.cla, apdu.ins, apdu.p1, apdu.p2, apdu.le);
         self.send_apdu_from_iter(&mut apdu.byte_iter(), delay)
     }
 
     #[inline(never)]
     fn send_apdu_raw(&mut self, apdu: &RawCApdu, delay: &mut DelayWrapper) -> Result<(), T1Error> {
+        log_capdu(apdu.cla, apdu.ins, apdu.p1, apdu.p2, apdu.le);
         self.send_apdu_from_iter(&mut apdu.byte_iter(), delay)
     }
 
@@ -198,31 +450,8 @@ where
         buf: &'a mut [u8],
         delay: &mut DelayWrapper,
     ) -> Result<RApdu<'a>, T1Error> {
-        let rapdu = self.receive_apdu_raw(buf, delay)?;
-
-        let mut tlvs = heapless::Vec::new();
-        let mut buf_offset: usize = 0;
-
-        loop {
-            if buf_offset == rapdu.data.len() { break; }
-            let tag = rapdu.data[buf_offset];
-            let len: usize;
-            if buf_offset+1 >= rapdu.data.len() { return Err(T1Error::TlvParseError); }
-            if rapdu.data[buf_offset+1] == 0x82 {
-                if buf_offset+4 > rapdu.data.len() { return Err(T1Error::TlvParseError); }
-                len = BE::read_u16(&rapdu.data[buf_offset+2..buf_offset+4]) as usize;
-                if buf_offset+4+len > rapdu.data.len() { return Err(T1Error::TlvParseError); }
-                tlvs.push(SimpleTlv::new(tag, &rapdu.data[buf_offset+4..buf_offset+4+len])).map_err(|_| T1Error::TlvParseError)?;
-                buf_offset += 4 + len;
-            } else if rapdu.data[buf_offset+1] < 0x80 {
-                len = rapdu.data[buf_offset+1] as usize;
-                if buf_offset+2+len > rapdu.data.len() { return Err(T1Error::TlvParseError); }
-                tlvs.push(SimpleTlv::new(tag, &rapdu.data[buf_offset+2..buf_offset+2+len])).map_err(|_| T1Error::TlvParseError)?;
-                buf_offset += 2 + len;
-            }
-        }
-
-        Ok(RApdu { sw: rapdu.sw, tlvs })
+        let truncate_tlvs = self.truncate_tlvs;
+        self.receive_apdu_raw(buf, delay)?.parse_tlvs(truncate_tlvs)
     }
 
     #[inline(never)]
@@ -231,23 +460,16 @@ where
         buf: &'a mut [u8],
         delay: &mut DelayWrapper,
     ) -> Result<RawRApdu<'a>, T1Error> {
-        let buf_len: usize = buf.len();
-        let mut buf_offset: usize = 0;
-        loop {
-            let header = self.receive_frame(&mut buf[buf_offset..buf_len], delay)?;
-            if let T1PCB::I(seq, multi) = header.pcb {
-                if seq != self.iseq_rcv {
-                    return Err(T1Error::ProtocolError);
-                }
-                self.iseq_rcv ^= 1;
-                buf_offset += header.len as usize;
-                if !multi { break; }
-                self.send_frame(T1PCB::R(self.iseq_rcv, 0), &[], delay)?;
-            }
-        }
+        self.elapsed_ms = Some(0);
+        self.started_ms = delay.now_ms();
+        let r = self.receive_frames(buf, delay);
+        self.elapsed_ms = None;
+        self.started_ms = None;
+        let buf_offset = r?;
 
         if buf_offset < 2 { return Err(T1Error::ProtocolError); }
         let sw = BE::read_u16(&buf[buf_offset-2..buf_offset]);
+        log_rapdu(sw, buf_offset-2);
         Ok(RawRApdu { sw, data: &buf[0..buf_offset-2] })
     }
 
@@ -255,36 +477,143 @@ where
     fn interface_soft_reset(&mut self, delay: &mut DelayWrapper) -> Result<AnswerToReset, T1Error> {
         let mut atrbuf: [u8; 64] = [0u8; 64];
         self.send_s(T1SCode::InterfaceSoftReset, &[], delay)?;
-        self.receive_s(T1SCode::InterfaceSoftReset, &mut atrbuf, delay)?;
+        let atrlen = self.receive_s(T1SCode::InterfaceSoftReset, &mut atrbuf, delay)?;
 
-        let atr_pv = atrbuf[0];
-        let dllp_len = atrbuf[6];
-        if dllp_len != 4 {
-            return Err(T1Error::ProtocolError);
+        AnswerToReset::parse(&atrbuf[0..atrlen])
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// T1overI2C owns whatever implements the embedded-hal I2C traits, so a bus manager
+// proxy (e.g. from shared-bus) can be passed in place of the peripheral. Without
+// one, SharedI2c shares a bus among drivers on a single thread of execution: each
+// transfer borrows the RefCell for its duration only.
+//
+//     let bus = RefCell::new(i2c);
+//     let se050 = Se050::new(T1overI2C::new(SharedI2c::new(&bus), 0x48, 0x5a));
+//     let other = OtherDriver::new(SharedI2c::new(&bus));
+
+pub struct SharedI2c<'a, TWI> {
+    bus: &'a RefCell<TWI>,
+}
+
+impl<'a, TWI> SharedI2c<'a, TWI> {
+    pub fn new(bus: &'a RefCell<TWI>) -> Self {
+        Self { bus }
+    }
+}
+
+impl<'a, TWI> embedded_hal::blocking::i2c::Read for SharedI2c<'a, TWI>
+where
+    TWI: embedded_hal::blocking::i2c::Read,
+{
+    type Error = TWI::Error;
+
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().read(address, buffer)
+    }
+}
+
+impl<'a, TWI> embedded_hal::blocking::i2c::Write for SharedI2c<'a, TWI>
+where
+    TWI: embedded_hal::blocking::i2c::Write,
+{
+    type Error = TWI::Error;
+
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), Self::Error> {
+        self.bus.borrow_mut().write(address, bytes)
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Records the T=1 frames T1overI2C exchanges into a caller-provided ring buffer,
+// independent of the log level, to be dumped after a failure. Each record is
+// a direction byte (0x00 sent, 0x01 received), the 2-byte big-endian frame
+// length and the frame as it went over the bus, NAD to checksum; frames that fail
+// their checksum are recorded too. When the buffer is full the oldest records
+// are dropped to make room.
+//
+//     static mut FRAMES: [u8; 1024] = [0; 1024];
+//     t1.set_recorder(FrameRecorder::new(unsafe { &mut FRAMES }));
+//     t1.start_recording();
+//     ...
+//     let len = t1.drain_recording(&mut out);
+
+pub const FRAME_SENT: u8 = 0x00;
+pub const FRAME_RECEIVED: u8 = 0x01;
+const FRAME_RECORD_HEADER_LEN: usize = 3;
+
+pub struct FrameRecorder {
+    buf: &'static mut [u8],
+    start: usize,
+    len: usize,
+    recording: bool,
+    dropped: u32,
+}
+
+impl FrameRecorder {
+    pub fn new(buf: &'static mut [u8]) -> Self {
+        Self { buf, start: 0, len: 0, recording: false, dropped: 0 }
+    }
+
+    // records lost to make room for newer ones, or too large for the buffer
+    pub fn dropped(&self) -> u32 {
+        self.dropped
+    }
+
+    fn byte(&self, offset: usize) -> u8 {
+        self.buf[(self.start + offset) % self.buf.len()]
+    }
+
+    fn oldest_len(&self) -> usize {
+        FRAME_RECORD_HEADER_LEN + BE::read_u16(&[self.byte(1), self.byte(2)]) as usize
+    }
+
+    fn drop_oldest(&mut self) {
+        let n = self.oldest_len();
+        self.start = (self.start + n) % self.buf.len();
+        self.len -= n;
+        self.dropped += 1;
+    }
+
+    fn record(&mut self, direction: u8, parts: &[&[u8]]) {
+        if !self.recording {
+            return;
         }
-        let plp_type = atrbuf[11];
-        let plp_len = atrbuf[12];
-        if plp_type != 2 /* I2C */ || plp_len != 11 {
-            return Err(T1Error::ProtocolError);
+        let flen: usize = parts.iter().map(|p| p.len()).sum();
+        let n = FRAME_RECORD_HEADER_LEN + flen;
+        if n > self.buf.len() {
+            self.dropped += 1;
+            return;
         }
-        let _hb_len = atrbuf[24];
-        /* TODO: check/use length of historical bytes */
-        Ok(AnswerToReset {
-            protocol_version: atr_pv,
-            vendor_id: atrbuf[1..6].try_into().unwrap(),
-            dllp: DataLinkLayerParameters {
-                bwt_ms: BE::read_u16(&atrbuf[7..9]),
-                ifsc: BE::read_u16(&atrbuf[9..11]),
-            },
-            plp: PhysicalLayerParameters::I2C(I2CParameters {
-                mcf: BE::read_u16(&atrbuf[13..15]),
-                configuration: atrbuf[15],
-                mpot_ms: atrbuf[16],
-                rfu: atrbuf[17..20].try_into().unwrap(),
-                segt_us: BE::read_u16(&atrbuf[20..22]),
-                wut_us: BE::read_u16(&atrbuf[22..24]),
-            }),
-            historical_bytes: atrbuf[25..40].try_into().unwrap(),
-        })
+        while self.buf.len() - self.len < n {
+            self.drop_oldest();
+        }
+        let mut lenbuf = [0u8; 2];
+        BE::write_u16(&mut lenbuf, flen as u16);
+        let header = [direction, lenbuf[0], lenbuf[1]];
+        let cap = self.buf.len();
+        for b in header.iter().chain(parts.iter().flat_map(|p| p.iter())) {
+            self.buf[(self.start + self.len) % cap] = *b;
+            self.len += 1;
+        }
+    }
+
+    // moves the oldest records that fit into `out`, returns the bytes written
+    fn drain(&mut self, out: &mut [u8]) -> usize {
+        let mut written = 0;
+        while self.len > 0 {
+            let n = self.oldest_len();
+            if written + n > out.len() {
+                break;
+            }
+            for (i, b) in out[written..written + n].iter_mut().enumerate() {
+                *b = self.byte(i);
+            }
+            written += n;
+            self.start = (self.start + n) % self.buf.len();
+            self.len -= n;
+        }
+        written
     }
 }
@@ -1,9 +1,31 @@
 use crate::types::*;
-use crate::{Se050, Se050Device, T1overI2C};
+use crate::attestation::{AttestationResult, AttestationVerifier};
+use crate::auth::{AuthKind, RESERVED_ID_TRANSPORT, Se050Operation, check_sequence, requires_auth};
+use crate::backup::ExportedObject;
+use crate::cipher::AesCtrContext;
+use crate::desfire::DESFIRE_MAX_DIV_INPUT_LEN;
+use crate::keywrap::SymmKeyType;
+use crate::mac::MacContext;
+use crate::pin::PinResult;
+use crate::provision::{MAX_PROVISION_STEPS, ProvisionError, ProvisionStep};
+use crate::se050::{
+    AppletFeatures, Capabilities, DesKeyType, FACTORY_ATTESTATION_KEY_EC,
+    FACTORY_ATTESTATION_KEY_RSA, KeyConfig, LifecycleState, MemoryInfo, Se050, Se050ApduSecObjType,
+    Se050Device, Se050AttestationAlgo, Se050CipherModeconstants, Se050DigestModeconstants,
+    Se050ECCurveconstants, Se050ECCurveParamconstants, Se050ECSignatureAlgo, Se050EDSignatureAlgo,
+    Se050MACAlgoconstants, Se050Error, Se050Objectpolicies, Se050RSAKeyComponent,
+    Se050RSASignatureAlgo, RSA_KEY_SIZE_MAX, RSA_KEY_SIZE_MIN_FIPS, SE050_APPLET_AID, SelectStatus,
+    SessionPolicy, SessionState, StrictFailures, UserIdObject,
+};
+use crate::selftest::{BENCH_AES_LEN, BenchReport, SelfTestReport};
+use crate::signature::{der_to_raw, raw_to_der, SignatureFormat};
+use crate::t1::{SharedI2c, FRAME_RECEIVED, FRAME_SENT, FrameRecorder, T1overI2C};
+use crate::transient::TransientObject;
 
 extern crate std;
 
 mod test_twi;
+mod test_transport;
 
 #[test]
 fn test_capdu() {
@@ -18,11 +40,174 @@ fn test_capdu() {
                                0x00]);
 }
 
+#[test]
+fn test_simple_tlv_try_new() {
+    let data = std::vec![0xa5u8; 0x10000];
+    // a one-byte length up to 127 bytes
+    let tlv = SimpleTlv::try_new(0x41, &data[0..127]).unwrap();
+    assert_eq!(tlv.get_header().as_slice(), &[0x41, 0x7f]);
+    assert_eq!(tlv.total_len(), 2 + 127);
+    // 0x82 and a two-byte length from 128 bytes on
+    let tlv = SimpleTlv::try_new(0x41, &data[0..128]).unwrap();
+    assert_eq!(tlv.get_header().as_slice(), &[0x41, 0x82, 0x00, 0x80]);
+    assert_eq!(tlv.total_len(), 4 + 128);
+    let tlv = SimpleTlv::try_new(0x42, &data[0..0xffff]).unwrap();
+    assert_eq!(tlv.get_header().as_slice(), &[0x42, 0x82, 0xff, 0xff]);
+    // no length encoding beyond 0xffff
+    assert_eq!(SimpleTlv::try_new(0x43, &data).err(), Some(Iso7816Error::ValueError));
+}
+
+#[test]
+fn test_nested_tlv() {
+    let mut inner: NestedTlv<16> = NestedTlv::new(0x42);
+    inner.push(&SimpleTlv::new(0x01, &[0xaa])).unwrap();
+    inner.push(&SimpleTlv::new(0x02, &[0xbb, 0xcc])).unwrap();
+    let mut outer: NestedTlv<32> = NestedTlv::new(0x41);
+    outer.push(&SimpleTlv::new(0x10, &[0x01])).unwrap();
+    outer.push_nested(&inner).unwrap();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x00, 0x00, None);
+    let tlv = outer.as_tlv().unwrap();
+    c.push(tlv);
+    let v: heapless::Vec<u8, 64> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x80, 0x01, 0x00, 0x00, 0x0e,
+                               0x41, 0x0c, 0x10, 0x01, 0x01,
+                               0x42, 0x07, 0x01, 0x01, 0xaa, 0x02, 0x02, 0xbb, 0xcc]);
+
+    // the value bound is enforced
+    let mut small: NestedTlv<4> = NestedTlv::new(0x43);
+    assert!(small.push(&SimpleTlv::new(0x01, &[0u8; 3])).is_err());
+    // a header that fits with data that does not leaves no partial child behind
+    small.push(&SimpleTlv::new(0x01, &[])).unwrap();
+    assert!(small.push(&SimpleTlv::new(0x02, &[0xbb])).is_err());
+    assert_eq!(small.as_tlv().unwrap().get_data(), &[0x01, 0x00]);
+}
+
+#[test]
+fn test_capdu_tlv_count() {
+    const DATA: &[u8] = &[0xa0,0xa1,0xa2,0xa3,0xa4,0xa5,0xa6,0xa7,0xa8];
+    for n in 0..=MAX_TLVS {
+        for le in [None, Some(0)] {
+            let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x20, 0x40, 0x60, le);
+            let mut expected_body: heapless::Vec<u8, 256> = heapless::Vec::new();
+            for i in 0..n {
+                // includes a zero-length TLV to exercise empty body slices
+                let data = &DATA[0..i];
+                c.push(SimpleTlv::new(0x41 + i as u8, data));
+                expected_body.extend_from_slice(&[0x41 + i as u8, i as u8]).unwrap();
+                expected_body.extend_from_slice(data).unwrap();
+            }
+
+            let mut expected: heapless::Vec<u8, 256> = heapless::Vec::new();
+            expected.extend_from_slice(&[0x80,0x20,0x40,0x60]).unwrap();
+            if !expected_body.is_empty() {
+                expected.push(expected_body.len() as u8).unwrap();
+            }
+            expected.extend_from_slice(&expected_body).unwrap();
+            if let Some(le) = le {
+                expected.push(le as u8).unwrap();
+            }
+
+            let v: heapless::Vec<u8, 256> = c.byte_iter().collect();
+            assert_eq!(v.as_slice(), expected.as_slice(), "{} TLVs, Le {:?}", n, le);
+        }
+    }
+}
+
+#[test]
+fn test_capdu_le() {
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x21, Some(0));
+    let v: heapless::Vec<u8, 256> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x80, 0x04, 0x00, 0x21, 0x00]);
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x21, None);
+    let v: heapless::Vec<u8, 256> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x80, 0x04, 0x00, 0x21]);
+
+    let c = RawCApdu::new(ApduClass::StandardPlain, 0xa4, 0x04, 0x00, &[0xa0, 0x00], Some(0));
+    let v: heapless::Vec<u8, 256> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x00, 0xa4, 0x04, 0x00, 0x02, 0xa0, 0x00, 0x00]);
+
+    let c = RawCApdu::new(ApduClass::StandardPlain, 0xa4, 0x04, 0x00, &[0xa0, 0x00], None);
+    let v: heapless::Vec<u8, 256> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x00, 0xa4, 0x04, 0x00, 0x02, 0xa0, 0x00]);
+}
+
+#[test]
+fn test_capdu_extended() {
+    let data = [0x5au8; 300];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x03, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x43, &data));
+    let v: heapless::Vec<u8, 320> = c.byte_iter().collect();
+    // APDU header, Lc (3B), TLV (3B size), Le (2B)
+    assert_eq!(v.len(), 4+3+(1+3+300)+2);
+    assert_eq!(&v[0..11], &[0x80, 0x03, 0x0c, 0x09, 0x00, 0x01, 0x30, 0x43, 0x82, 0x01, 0x2c]);
+    assert!(v[11..311].iter().all(|&b| b == 0x5a));
+    assert_eq!(&v[311..], &[0x00, 0x00]);
+
+    // a large Le alone switches to extended length as well
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0x0400));
+    let v: heapless::Vec<u8, 16> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x80, 0x02, 0x00, 0x00, 0x00, 0x04, 0x00]);
+
+    let c = RawCApdu::new(ApduClass::StandardPlain, 0xa4, 0x04, 0x00, &[0xa0, 0x00], Some(0x0400));
+    let v: heapless::Vec<u8, 16> = c.byte_iter().collect();
+    assert_eq!(v.as_slice(), &[0x00, 0xa4, 0x04, 0x00, 0x00, 0x00, 0x02, 0xa0, 0x00, 0x04, 0x00]);
+}
+
+// no command data: neither Lc nor its extended-length marker, only Le
+#[test]
+fn test_capdu_le_without_data() {
+    for (le, trailer) in [(255, &[0xff][..]), (256, &[0x00]), (257, &[0x00, 0x01, 0x01]), (65536, &[0x00, 0x00, 0x00])] {
+        let c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(le));
+        let v: heapless::Vec<u8, 16> = c.byte_iter().collect();
+        assert_eq!(&v[0..4], &[0x80, 0x02, 0x00, 0x00]);
+        assert_eq!(&v[4..], trailer, "Le {}", le);
+        assert_eq!(c.serialized_len(), v.len());
+
+        let r = RawCApdu::new(ApduClass::StandardPlain, 0xb0, 0x00, 0x00, &[], Some(le));
+        let v: heapless::Vec<u8, 16> = r.byte_iter().collect();
+        assert_eq!(&v[0..4], &[0x00, 0xb0, 0x00, 0x00]);
+        assert_eq!(&v[4..], trailer, "Le {}", le);
+        assert_eq!(r.serialized_len(), v.len());
+    }
+}
+
+#[test]
+fn test_capdu_serialized_len() {
+    let data = [0u8; 300];
+    for len in [0, 1, 200, 251, 252, 300] {
+        for le in [None, Some(0), Some(0x0400)] {
+            let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x03, 0x0c, 0x09, le);
+            if len > 0 {
+                c.push(SimpleTlv::new(0x43, &data[0..len]));
+            }
+            assert_eq!(c.serialized_len(), c.byte_iter().count(), "TLV {} bytes, Le {:?}", len, le);
+
+            let r = RawCApdu::new(ApduClass::StandardPlain, 0xa4, 0x04, 0x00, &data[0..len], le);
+            assert_eq!(r.serialized_len(), r.byte_iter().count(), "data {} bytes, Le {:?}", len, le);
+        }
+    }
+}
+
 #[test]
 fn test_crc16_ccitt() {
     assert_eq!(0x78a1, Se050CRC::calculate(&[0,48,95,111,242]));
 }
 
+#[test]
+fn test_verify_frame_crc() {
+    let mut frame: heapless::Vec<u8, 64> = heapless::Vec::new();
+    frame.extend_from_slice(T1FRH_S_INTF_RESET).unwrap();
+    frame.extend_from_slice(T1F_ATR).unwrap();
+    assert_eq!(verify_frame_crc(&frame), Ok(()));
+    assert_eq!(verify_frame_crc(T1FC_S_INTF_RESET), Ok(()));
+
+    frame[10] ^= 0x80;
+    assert_eq!(verify_frame_crc(&frame), Err(T1Error::ChecksumError));
+    assert_eq!(verify_frame_crc(&[0x00]), Err(T1Error::ChecksumError));
+}
+
 const T1FC_S_INTF_RESET: &[u8] = &[0x5a, 0xcf, 0x00, 0x37, 0x7f];
 const T1FRH_S_INTF_RESET: &[u8] = &[0xa5, 0xef, 0x23];
 const T1F_ATR: &[u8] =
@@ -47,6 +232,41 @@ fn test_soft_reset() {
     assert_eq!(atr.dllp.ifsc, 254);
 }
 
+#[test]
+fn test_i2c_parameters() {
+    let atr = AnswerToReset::parse(&T1F_ATR[0..T1F_ATR.len() - 2]).unwrap();
+    let PhysicalLayerParameters::I2C(plp) = atr.plp;
+    assert_eq!(plp.max_clock_khz(), 1000);
+    assert_eq!(plp.min_polling_time_ms(), 1);
+    assert_eq!(plp.guard_time_us(), 100);
+    assert_eq!(plp.wake_up_time_us(), 0);
+    assert!(plp.configuration_bit(3));
+    assert!(!plp.configuration_bit(0));
+    assert!(!plp.configuration_bit(8));
+    assert!(!plp.has_rfu_bits());
+}
+
+#[test]
+fn test_atr_parse_truncated() {
+    let atr = &T1F_ATR[0..T1F_ATR.len() - 2];
+    let parsed = AnswerToReset::parse(atr).unwrap();
+    assert_eq!(parsed.dllp.ifsc, 254);
+    assert_eq!(&parsed.historical_bytes[0..10], &atr[25..35]);
+    assert_eq!(&parsed.historical_bytes[10..], &[0u8; 5]);
+
+    for len in 0..atr.len() {
+        assert!(matches!(AnswerToReset::parse(&atr[0..len]), Err(T1Error::ProtocolError)), "len {}", len);
+    }
+
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(&[0xa5, 0xef, 0x05]);
+    xtwi.push_out(&[0x00, 0xa0, 0x00, 0x00, 0x03, 0x4d, 0xd6]);
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    assert!(matches!(t1.interface_soft_reset(&mut delay), Err(T1Error::ProtocolError)));
+}
+
 const T1FC_GP_SELECT_SE050: &[u8] = &[0x5a, 0x00, 0x16,
        0x00, 0xa4, 0x04, 0x00, 0x10,
        0xa0, 0x00, 0x00, 0x03, 0x96, 0x54, 0x53, 0x00,
@@ -71,3 +291,3023 @@ fn test_se050_init() {
     let r = se050.enable(&mut delay);
     assert!(r.is_ok());
 }
+
+fn enable_se050(delay: &mut DelayWrapper) -> Se050<T1overI2C<test_twi::TWI>> {
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    xtwi.push_out(T1FRH_GP_SELECT_SE050);
+    xtwi.push_out(T1F_APP_VERSION);
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    assert!(se050.enable(delay).is_ok());
+    se050
+}
+
+#[test]
+fn test_app_info() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let se050 = enable_se050(&mut delay);
+
+    let info = se050.app_info().unwrap();
+    assert_eq!(info.applet_version(), 0x030101);
+    assert_eq!(info.features(), 0x6fff);
+    assert_eq!(info.applet_features(), AppletFeatures::from_bits(0x6fff));
+    assert_eq!(info.securebox_version(), 0x010b);
+}
+
+#[test]
+fn test_securebox() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let t1 = T1overI2C::new(test_twi::TWI::new(), 0x48, 0x5a);
+    let se050 = Se050::new(t1);
+    // unknown before enable()
+    assert!(!se050.has_securebox());
+    assert_eq!(se050.require_securebox(), Err(Se050Error::SecureBoxUnavailable));
+
+    let se050 = enable_se050(&mut delay);
+    assert!(se050.has_securebox());
+    assert_eq!(se050.require_securebox(), Ok(()));
+}
+
+#[test]
+fn test_apdu_transport() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut transport = test_transport::ApduTransport::new(&T1F_ATR[0..T1F_ATR.len() - 2]);
+    // the SELECT APDU of T1FC_GP_SELECT_SE050, without the T=1 framing
+    transport.push(&T1FC_GP_SELECT_SE050[3..T1FC_GP_SELECT_SE050.len() - 2], &T1F_APP_VERSION[0..9]);
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x02]));
+    let command: heapless::Vec<u8, 16> = random.byte_iter().collect();
+    transport.push(&command, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut se050 = Se050::new(transport);
+    assert!(se050.enable(&mut delay).is_ok());
+    assert_eq!(se050.app_info().unwrap().applet_version(), 0x030101);
+
+    let mut buf = [0u8; 2];
+    assert!(se050.get_random(&mut buf, &mut delay).is_ok());
+    assert_eq!(buf, [0x12, 0x34]);
+}
+
+// newer applets return more than the 7 parsed app info bytes
+#[test]
+fn test_se050_init_long_app_info() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    push_response(&mut xtwi, 0x00, &[0x07, 0x02, 0x00, 0x3f, 0xff, 0x01, 0x0b, 0x12, 0x34, 0x90, 0x00]);
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    assert!(se050.enable(&mut delay).is_ok());
+
+    let info = se050.app_info().unwrap();
+    assert_eq!(info.applet_version(), 0x070200);
+    assert_eq!(info.features(), 0x3fff);
+    assert_eq!(info.securebox_version(), 0x010b);
+}
+
+#[test]
+fn test_select_warning() {
+    let mut delay = test_twi::get_delay_wrapper();
+    assert_eq!(SelectStatus::from_sw(0x9000), Some(SelectStatus::Ok));
+    assert_eq!(SelectStatus::from_sw(0x6283), Some(SelectStatus::Warning(0x6283)));
+    assert_eq!(SelectStatus::from_sw(0x6a82), None);
+
+    for (sw, expected) in [(0x6283, Ok(SelectStatus::Warning(0x6283))), (0x6a82, Err(Se050Error::UnknownError))] {
+        let mut xtwi = test_twi::TWI::new();
+        xtwi.push_in(T1FC_S_INTF_RESET);
+        xtwi.push_out(T1FRH_S_INTF_RESET);
+        xtwi.push_out(T1F_ATR);
+        xtwi.push_in(T1FC_GP_SELECT_SE050);
+        let [sw1, sw2] = u16::to_be_bytes(sw);
+        push_response(&mut xtwi, 0x00, &[0x03, 0x01, 0x01, 0x6f, 0xff, 0x01, 0x0b, sw1, sw2]);
+        let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+        let mut se050 = Se050::new(t1);
+
+        let r = se050.enable(&mut delay);
+        assert_eq!(r, expected);
+        assert_eq!(se050.app_info().is_some(), r.is_ok_and(|status| status.is_warning()));
+    }
+}
+
+#[test]
+fn test_select_applet_custom_aid() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let aid = [0xa0, 0x00, 0x00, 0x03, 0x96, 0x54, 0x53, 0x00, 0x42];
+    let mut apdu: heapless::Vec<u8, 32> = heapless::Vec::new();
+    apdu.extend_from_slice(&[0x00, 0xa4, 0x04, 0x00, aid.len() as u8]).unwrap();
+    apdu.extend_from_slice(&aid).unwrap();
+    apdu.push(0x00).unwrap();
+    xtwi.push_in(&t1_frame(0x5a, 0x00, &apdu));
+    push_response(&mut xtwi, 0x00, &[0x03, 0x01, 0x01, 0x6f, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    assert_eq!(se050.applet_aid(), &SE050_APPLET_AID);
+    assert!(se050.select_applet(&aid[0..4], &mut delay).is_err());
+    assert!(se050.select_applet(&[0u8; 17], &mut delay).is_err());
+
+    assert!(se050.select_applet(&aid, &mut delay).is_ok());
+    assert_eq!(se050.applet_aid(), &aid);
+    assert_eq!(se050.app_info().unwrap().applet_version(), 0x030101);
+}
+
+fn t1_frame(nad: u8, pcb: u8, data: &[u8]) -> heapless::Vec<u8, 261> {
+    let mut frame: heapless::Vec<u8, 261> = heapless::Vec::new();
+    frame.extend_from_slice(&[nad, pcb, data.len() as u8]).unwrap();
+    frame.extend_from_slice(data).unwrap();
+    let crc = Se050CRC::calculate(frame.as_slice());
+    frame.extend_from_slice(&crc.to_le_bytes()).unwrap();
+    frame
+}
+
+// a device past the interface soft reset of enable(), ahead of the frames already
+// queued on `xtwi`; the T=1 sequence numbers start at 0 as after Se050::new
+fn reset_se050(xtwi: test_twi::TWI, delay: &mut DelayWrapper) -> Se050<T1overI2C<test_twi::TWI>> {
+    let mut twi = test_twi::TWI { strict: xtwi.strict, ..test_twi::TWI::new() };
+    twi.push_in(T1FC_S_INTF_RESET);
+    twi.push_out(T1FRH_S_INTF_RESET);
+    twi.push_out(T1F_ATR);
+    for frame in xtwi.next_in.iter() {
+        twi.push_in(frame);
+    }
+    for frame in xtwi.next_out.iter() {
+        twi.push_out(frame);
+    }
+    let mut se050 = Se050::new(T1overI2C::new(twi, 0x48, 0x5a));
+    se050.soft_reset(delay).unwrap();
+    se050
+}
+
+// expect an I-block carrying `capdu` from the host
+fn push_command(xtwi: &mut test_twi::TWI, pcb: u8, capdu: &CApdu) {
+    let apdu: heapless::Vec<u8, 254> = capdu.byte_iter().collect();
+    xtwi.push_in(&t1_frame(0x5a, pcb, &apdu));
+}
+
+// queue an I-block carrying `rapdu` from the SE050, read as header + payload
+fn push_response(xtwi: &mut test_twi::TWI, pcb: u8, rapdu: &[u8]) {
+    let frame = t1_frame(0xa5, pcb, rapdu);
+    xtwi.push_out(&frame[0..3]);
+    xtwi.push_out(&frame[3..]);
+}
+
+const SESSION_ID: [u8; 8] = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+fn push_session_command(xtwi: &mut test_twi::TWI, pcb: u8, inner: &CApdu) {
+    let inner: heapless::Vec<u8, 254> = inner.byte_iter().collect();
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x85, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x10, &SESSION_ID));
+    c.push(SimpleTlv::new(0x41, &inner));
+    push_command(xtwi, pcb, &c);
+}
+
+#[test]
+fn test_session_get_random() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1b, Some(0x0c));
+    create.push(SimpleTlv::new(0x41, &[0x7f, 0xff, 0x02, 0x00]));
+    push_command(&mut xtwi, 0x00, &create);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x90, 0x00]);
+
+    let mut verify = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x2c, None);
+    verify.push(SimpleTlv::new(0x41, b"1234"));
+    push_session_command(&mut xtwi, 0x40, &verify);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x04]));
+    push_session_command(&mut xtwi, 0x00, &random);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x04, 0xde, 0xad, 0xbe, 0xef, 0x90, 0x00]);
+
+    let close = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1c, None);
+    push_session_command(&mut xtwi, 0x40, &close);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    let mut session = se050.open_session(&[0x7f, 0xff, 0x02, 0x00], &mut delay).unwrap();
+    assert_eq!(session.session_id(), &SESSION_ID);
+    assert!(session.verify_user_id(b"1234").is_ok());
+    let mut buf = [0u8; 4];
+    assert!(session.get_random(&mut buf).is_ok());
+    assert_eq!(buf, [0xde, 0xad, 0xbe, 0xef]);
+    assert!(session.close().is_ok());
+    assert_eq!(se050.session_state(), SessionState::None);
+}
+
+#[test]
+fn test_delete_all_not_authenticated() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // sent without a tracked session by default
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x2a, Some(0));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.session_state(), SessionState::None);
+    assert_eq!(se050.delete_all(&mut delay), Ok(()));
+
+    // opted in: rejected before anything is sent
+    se050.set_require_session(true);
+    assert_eq!(se050.delete_all(&mut delay), Err(Se050Error::NotAuthenticated));
+}
+
+#[test]
+fn test_digest_output_len() {
+    assert_eq!(Se050DigestModeconstants::DigestNoHash.output_len(), None);
+    assert_eq!(Se050DigestModeconstants::DigestSha.output_len(), Some(20));
+    assert_eq!(Se050DigestModeconstants::DigestSha224.output_len(), Some(28));
+    assert_eq!(Se050DigestModeconstants::DigestSha256.output_len(), Some(32));
+    assert_eq!(Se050DigestModeconstants::DigestSha384.output_len(), Some(48));
+    assert_eq!(Se050DigestModeconstants::DigestSha512.output_len(), Some(64));
+}
+
+#[test]
+fn test_digest_sha1_sha224() {
+    for (mode, len) in [(Se050DigestModeconstants::DigestSha, 20), (Se050DigestModeconstants::DigestSha224, 28)] {
+        let mut delay = test_twi::get_delay_wrapper();
+        let mut xtwi = test_twi::TWI::new();
+
+        let mode_byte = [mode as u8];
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x00, 0x0e, Some(0));
+        c.push(SimpleTlv::new(0x41, &mode_byte));
+        c.push(SimpleTlv::new(0x42, b"abc"));
+        push_command(&mut xtwi, 0x00, &c);
+        let mut rapdu: heapless::Vec<u8, 64> = heapless::Vec::new();
+        rapdu.extend_from_slice(&[0x41, len as u8]).unwrap();
+        rapdu.extend_from_slice(&[0x5a; 64][0..len]).unwrap();
+        rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+        push_response(&mut xtwi, 0x00, &rapdu);
+
+        let mut se050 = reset_se050(xtwi, &mut delay);
+
+        // too short: rejected before anything is sent
+        let mut short = [0u8; 64];
+        assert!(se050.digest(mode, b"abc", &mut short[0..len-1], &mut delay).is_err());
+
+        let mut out = [0u8; 64];
+        assert_eq!(se050.digest(mode, b"abc", &mut out[0..len], &mut delay), Ok(len));
+        assert_eq!(&out[0..len], &[0x5a; 64][0..len]);
+    }
+}
+
+#[test]
+fn test_ecdsa_sign_message() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // message hashed on the SE with the digest the algorithm names ...
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x00, 0x0e, Some(0));
+    c.push(SimpleTlv::new(0x41, &[Se050DigestModeconstants::DigestSha256 as u8]));
+    c.push(SimpleTlv::new(0x42, b"message"));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut rapdu: heapless::Vec<u8, 64> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x41, 32]).unwrap();
+    rapdu.extend_from_slice(&[0xd1; 32]).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    // ... and only the digest is signed
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x20, 0xe8, 0xa0, 0x01]));
+    c.push(SimpleTlv::new(0x42, &[Se050ECSignatureAlgo::SigEcdsaSha256 as u8]));
+    c.push(SimpleTlv::new(0x43, &[0xd1; 32]));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x04, 0x30, 0x02, 0x05, 0x00, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut sig = [0u8; 72];
+    assert_eq!(se050.ecdsa_sign_message(&[0x20, 0xe8, 0xa0, 0x01], Se050ECSignatureAlgo::SigEcdsaSha256, b"message", &mut sig, &mut delay), Ok(4));
+    assert_eq!(&sig[0..4], &[0x30, 0x02, 0x05, 0x00]);
+
+    // no digest to pick: rejected before anything is sent
+    assert!(se050.ecdsa_sign_message(&[0x20, 0xe8, 0xa0, 0x01], Se050ECSignatureAlgo::SigEcdsaPlain, b"message", &mut sig, &mut delay).is_err());
+}
+
+#[test]
+fn test_signature_format() {
+    // r with the high bit set gets a 0x00 pad, s has a leading zero byte stripped
+    let mut raw = [0u8; 64];
+    raw[0..32].fill(0x81);
+    raw[33..64].fill(0x22);
+    let mut der = [0u8; 80];
+    let len = raw_to_der(&raw, &mut der).unwrap();
+    assert_eq!(len, 2 + 2 + 33 + 2 + 31);
+    assert_eq!(&der[0..5], &[0x30, 68, 0x02, 33, 0x00]);
+    assert_eq!(&der[37..39], &[0x02, 31]);
+    let mut back = [0u8; 64];
+    assert_eq!(der_to_raw(&der[0..len], 32, &mut back), Ok(64));
+    assert_eq!(back, raw);
+
+    // P-521 needs the long form SEQUENCE length
+    let raw = [0xffu8; 132];
+    let mut der = [0u8; crate::MAX_DER_SIGNATURE_LEN];
+    let len = raw_to_der(&raw, &mut der).unwrap();
+    assert_eq!(len, crate::MAX_DER_SIGNATURE_LEN);
+    assert_eq!(&der[0..3], &[0x30, 0x81, 138]);
+    let mut back = [0u8; 132];
+    assert_eq!(der_to_raw(&der[0..len], 66, &mut back), Ok(132));
+    assert_eq!(back, raw);
+
+    // zero
+    assert_eq!(raw_to_der(&[0, 0], &mut der), Ok(8));
+    assert_eq!(&der[0..8], &[0x30, 0x06, 0x02, 0x01, 0x00, 0x02, 0x01, 0x00]);
+
+    // r too long for the curve, trailing garbage, a negative INTEGER
+    assert!(der_to_raw(&[0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01], 0, &mut back).is_err());
+    assert!(der_to_raw(&[0x30, 0x08, 0x02, 0x01, 0x01, 0x02, 0x01, 0x01, 0x00, 0x00], 32, &mut back).is_err());
+    assert!(der_to_raw(&[0x30, 0x06, 0x02, 0x01, 0x81, 0x02, 0x01, 0x01], 32, &mut back).is_err());
+    assert!(raw_to_der(&[0x01, 0x02, 0x03], &mut der).is_err());
+}
+
+#[test]
+fn test_ecdsa_sign_formatted() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+    let obj = [0x20, 0xe8, 0xa0, 0x01];
+
+    // the curve gives the length of r and s ...
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x82, 0x0b, 0x36, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, Se050ECCurveconstants::NistP256 as u8, 0x90, 0x00]);
+
+    // ... for the DER signature from ECDSASign
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECSignatureAlgo::SigEcdsaSha256 as u8]));
+    c.push(SimpleTlv::new(0x43, &[0xd1; 32]));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x08, 0x30, 0x06, 0x02, 0x01, 0x05, 0x02, 0x01, 0x07, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut sig = [0u8; 64];
+    assert_eq!(se050.ecdsa_sign_formatted(&obj, Se050ECSignatureAlgo::SigEcdsaSha256, &[0xd1; 32], SignatureFormat::Raw, &mut sig, &mut delay), Ok(64));
+    let mut raw = [0u8; 64];
+    raw[31] = 0x05;
+    raw[63] = 0x07;
+    assert_eq!(sig, raw);
+}
+
+#[test]
+fn test_read_rsa_public_exponent() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x20, 0xe8, 0xa1, 0x03]));
+    c.push(SimpleTlv::new(0x44, &[0x01]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x03, 0x01, 0x00, 0x01, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let mut out = [0u8; 512];
+    assert!(se050.read_rsa_public_key(&[0x20, 0xe8, 0xa1, 0x03], Se050RSAKeyComponent::RsaCompPrivExp, &mut out, &mut delay).is_err());
+    let r = se050.read_rsa_public_key(&[0x20, 0xe8, 0xa1, 0x03], Se050RSAKeyComponent::RsaCompPubExp, &mut out, &mut delay);
+    assert_eq!(r, Ok(3));
+    assert_eq!(&out[0..3], &[0x01, 0x00, 0x01]);
+}
+
+#[test]
+fn test_transient_object_drop() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut delete = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    delete.push(SimpleTlv::new(0x41, &[0x7e, 0x00, 0x00, 0x01]));
+    push_command(&mut xtwi, 0x00, &delete);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x02]));
+    push_command(&mut xtwi, 0x40, &random);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    {
+        let obj = TransientObject::new(&mut se050, &mut delay, [0x7e, 0x00, 0x00, 0x01]);
+        assert_eq!(obj.id(), &[0x7e, 0x00, 0x00, 0x01]);
+        // early return by error path: the guard is dropped here
+    }
+
+    // only succeeds if DeleteSecureObject was exchanged first
+    let mut buf = [0u8; 2];
+    assert!(se050.get_random(&mut buf, &mut delay).is_ok());
+    assert_eq!(buf, [0x12, 0x34]);
+}
+
+#[test]
+fn test_policy_preflight() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    // AES key, one policy for any auth object granting decrypt only
+    let attributes: [u8; 24] = [
+        0x20, 0x00, 0x00, 0x10, 0x09, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x08, 0x00, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+        0x01,
+    ];
+    assert_eq!(crate::se050::object_policy_allows(&attributes, Se050Objectpolicies::PolicyObjAllowDec as u32), Ok(true));
+    assert_eq!(crate::se050::object_policy_allows(&attributes, Se050Objectpolicies::PolicyObjAllowEnc as u32), Ok(false));
+    assert_eq!(crate::se050::object_policy_allows(&attributes[0..15], Se050Objectpolicies::PolicyObjAllowEnc as u32), Ok(true));
+    assert!(crate::se050::object_policy_allows(&attributes[0..17], Se050Objectpolicies::PolicyObjAllowEnc as u32).is_err());
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x3b, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x20, 0x00, 0x00, 0x10]));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut rapdu: heapless::Vec<u8, 32> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x42, attributes.len() as u8]).unwrap();
+    rapdu.extend_from_slice(&attributes).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    se050.set_policy_preflight(true);
+
+    // the CipherOneShot command is never sent
+    let r = se050.cipher_one_shot_encrypt(&[0x20, 0x00, 0x00, 0x10], Se050CipherModeconstants::AesCbcNopad, &[0u8; 16], &[], &mut delay);
+    assert_eq!(r, Err(Se050Error::PolicyViolation));
+}
+
+#[test]
+fn test_cipher_des_cbc_iv() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    assert_eq!(Se050CipherModeconstants::DesCbcNopad.iv_len(), 8);
+    assert_eq!(Se050CipherModeconstants::AesCbcNopad.iv_len(), 16);
+    assert_eq!(Se050CipherModeconstants::DesEcbNopad.iv_len(), 0);
+
+    let key = [0x20, 0x00, 0x00, 0x20];
+    let mode = Se050CipherModeconstants::DesCbcNopad;
+    let data = [0x11u8; 8];
+    let iv = [0x01, 0x23, 0x45, 0x67, 0x89, 0xab, 0xcd, 0xef];
+
+    // IV goes last, as TLV[TAG_4]
+    let modeid = [mode as u8];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0e, 0x42, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &modeid));
+    c.push(SimpleTlv::new(0x43, &data));
+    c.push(SimpleTlv::new(0x44, &iv));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x08, 0xde, 0xad, 0xbe, 0xef, 0xde, 0xad, 0xbe, 0xef, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    // an AES-sized IV is rejected for DES before anything is sent
+    assert!(se050.cipher_one_shot_encrypt(&key, mode, &data, &[0u8; 16], &mut delay).is_err());
+    assert!(se050.cipher_one_shot_encrypt(&key, Se050CipherModeconstants::AesCbcNopad, &data, &iv, &mut delay).is_err());
+    assert!(se050.cipher_one_shot_encrypt(&key, mode, &data, &iv, &mut delay).is_ok());
+}
+
+#[test]
+fn test_supported_curves() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x82, 0x0b, 0x25, Some(0));
+    push_command(&mut xtwi, 0x00, &c);
+    // NIST P-256 and secp256k1 set, 0x3f not known to the driver
+    let mut rapdu = [0x01u8; 67];
+    rapdu[0] = 0x41;
+    rapdu[1] = 0x3f;
+    rapdu[2 + 2] = 0x02;
+    rapdu[2 + 15] = 0x02;
+    rapdu[2 + 62] = 0x02;
+    rapdu[65] = 0x90;
+    rapdu[66] = 0x00;
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let curves = se050.supported_curves(&mut delay).unwrap();
+    assert_eq!(&curves[..], &[Se050ECCurveconstants::NistP256, Se050ECCurveconstants::Secp256k1]);
+}
+
+#[test]
+fn test_x25519_keygen_and_ecdh() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let key = [0x20, 0x00, 0x00, 0x30];
+    let peer = [0x09u8; 32];
+
+    let mut gen = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    gen.push(SimpleTlv::new(0x41, &key));
+    gen.push(SimpleTlv::new(0x42, &[0x41]));
+    push_command(&mut xtwi, 0x00, &gen);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut dh = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x01, 0x0f, Some(0));
+    dh.push(SimpleTlv::new(0x41, &key));
+    dh.push(SimpleTlv::new(0x42, &peer));
+    push_command(&mut xtwi, 0x40, &dh);
+    let mut rapdu = [0x77u8; 36];
+    rapdu[0] = 0x41;
+    rapdu[1] = 0x20;
+    rapdu[34] = 0x90;
+    rapdu[35] = 0x00;
+    push_response(&mut xtwi, 0x40, &rapdu);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert!(se050.generate_x25519_key(&key, &mut delay).is_ok());
+
+    let mut secret = [0u8; 32];
+    // Weierstrass encodings and Ed25519 are rejected before anything is sent
+    let mut uncompressed = [0u8; 65];
+    uncompressed[0] = 0x04;
+    assert!(se050.ecdh_generate_shared_secret(&key, Se050ECCurveconstants::IdEccMontDh25519, &uncompressed, &mut secret, &mut delay).is_err());
+    assert!(se050.ecdh_generate_shared_secret(&key, Se050ECCurveconstants::NistP256, &peer, &mut secret, &mut delay).is_err());
+    assert!(se050.ecdh_generate_shared_secret(&key, Se050ECCurveconstants::IdEccEd25519, &peer, &mut secret, &mut delay).is_err());
+
+    assert_eq!(se050.ecdh_generate_shared_secret(&key, Se050ECCurveconstants::IdEccMontDh25519, &peer, &mut secret, &mut delay), Ok(32));
+    assert_eq!(secret, [0x77; 32]);
+}
+
+#[test]
+fn test_ed25519_keygen_readback() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let key = [0x20, 0x00, 0x00, 0x31];
+
+    let mut gen = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    gen.push(SimpleTlv::new(0x41, &key));
+    gen.push(SimpleTlv::new(0x42, &[0x40]));
+    let mut read = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+    read.push(SimpleTlv::new(0x41, &key));
+
+    let mut good = [0x3bu8; 36];
+    good[0] = 0x41;
+    good[1] = 0x20;
+    good[34] = 0x90;
+    good[35] = 0x00;
+
+    push_command(&mut xtwi, 0x00, &gen);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &read);
+    push_response(&mut xtwi, 0x40, &good);
+
+    // a 65-byte readback is not an Ed25519 key
+    let mut bad = [0x04u8; 69];
+    bad[0] = 0x41;
+    bad[1] = 0x41;
+    bad[67] = 0x90;
+    bad[68] = 0x00;
+    push_command(&mut xtwi, 0x00, &gen);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &read);
+    push_response(&mut xtwi, 0x40, &bad);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert_eq!(se050.generate_ed25519_key(&key, &mut delay).map(|id| id.0), Ok(key));
+    assert!(se050.generate_ed25519_key(&key, &mut delay).is_err());
+}
+
+#[test]
+fn test_receive_wtx_and_timeout() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    // S(WTX request) is acknowledged with the same multiplier, then the response follows
+    push_response(&mut xtwi, 0xc3, &[0x02]);
+    xtwi.push_in(&t1_frame(0x5a, 0xe3, &[0x02]));
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    // second response never arrives after the WTX
+    push_response(&mut xtwi, 0xc3, &[0x01]);
+    xtwi.push_in(&t1_frame(0x5a, 0xe3, &[0x01]));
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    t1.set_timeout_ms(8);
+
+    let mut buf = [0u8; 16];
+    let r = t1.receive_apdu_raw(&mut buf, &mut delay).map(|r| r.sw);
+    assert_eq!(r, Ok(0x9000));
+
+    let r = t1.receive_apdu_raw(&mut buf, &mut delay).map(|r| r.sw);
+    assert_eq!(r, Err(T1Error::Timeout));
+}
+
+#[test]
+fn test_send_chained_wtx() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let data = [0x5au8; 300];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x05, 0x00, None);
+    c.push(SimpleTlv::new(0x43, &data));
+    let apdu: std::vec::Vec<u8> = c.byte_iter().collect();
+    let blocks: std::vec::Vec<&[u8]> = apdu.chunks(MAX_IFSC).collect();
+    assert_eq!(blocks.len(), 2);
+
+    // the card asks for more time before acknowledging the first block
+    xtwi.push_in(&t1_frame(0x5a, 0x20, blocks[0]));
+    push_response(&mut xtwi, 0xc3, &[0x01]);
+    xtwi.push_in(&t1_frame(0x5a, 0xe3, &[0x01]));
+    push_response(&mut xtwi, 0x90, &[]);
+    xtwi.push_in(&t1_frame(0x5a, 0x40, blocks[1]));
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    assert!(t1.send_apdu(&c, &mut delay).is_ok());
+    assert_eq!(t1.retransmissions(), 0);
+}
+
+#[test]
+fn test_raw_tlv_iter() {
+    let mut data = [0u8; 140];
+    data[0..4].copy_from_slice(&[0x41, 0x02, 0xaa, 0xbb]);
+    data[4..7].copy_from_slice(&[0x42, 0x81, 0x80]);
+    data[135..140].copy_from_slice(&[0x43, 0x82, 0x00, 0x01, 0xcc]);
+
+    let rapdu = RawRApdu { data: &data, sw: 0x9000 };
+    let tlvs: heapless::Vec<_, 8> = rapdu.tlv_iter().collect();
+    assert_eq!(tlvs.len(), 3);
+    assert_eq!(tlvs[0], Ok((0x41, &[0xaa, 0xbb][..])));
+    assert_eq!(tlvs[1], Ok((0x42, &[0u8; 128][..])));
+    assert_eq!(tlvs[2], Ok((0x43, &[0xcc][..])));
+
+    // truncated value: one error, then the iterator is exhausted
+    let rapdu = RawRApdu { data: &data[0..10], sw: 0x9000 };
+    let mut iter = rapdu.tlv_iter();
+    assert_eq!(iter.next(), Some(Ok((0x41, &[0xaa, 0xbb][..]))));
+    assert_eq!(iter.next(), Some(Err(T1Error::TlvParseError)));
+    assert_eq!(iter.next(), None);
+
+    let rapdu = RawRApdu { data: &[0x41, 0x83, 0x00, 0x00, 0x01, 0x00], sw: 0x9000 };
+    assert_eq!(rapdu.tlv_iter().next(), Some(Err(T1Error::TlvParseError)));
+}
+
+#[test]
+fn test_write_binary_object() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let obj = [0x10, 0x00, 0x00, 0x01];
+    let policy = [0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00];
+    let mut data = [0u8; 300];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    // creation with policy and full length fills the first frame
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    create.push(SimpleTlv::new(0x11, &policy));
+    create.push(SimpleTlv::new(0x41, &obj));
+    create.push(SimpleTlv::new(0x42, &[0x00, 0x00]));
+    create.push(SimpleTlv::new(0x43, &[0x01, 0x2c]));
+    create.push(SimpleTlv::new(0x44, &data[0..220]));
+    push_command(&mut xtwi, 0x40, &create);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut update = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    update.push(SimpleTlv::new(0x41, &obj));
+    update.push(SimpleTlv::new(0x42, &[0x00, 0xdc]));
+    update.push(SimpleTlv::new(0x44, &data[220..300]));
+    push_command(&mut xtwi, 0x00, &update);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert!(se050.write_binary_object(&obj, &data, Some(&policy), &mut delay).is_ok());
+}
+
+#[test]
+fn test_find_free_object_id() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // 0x20000010 and 0x20000011 are taken, 0x20000012 is free
+    for (i, result) in [0x01u8, 0x01, 0x02].iter().enumerate() {
+        let seq = if i % 2 == 0 { 0x00 } else { 0x40 };
+        let obj = [0x20, 0x00, 0x00, 0x10 + i as u8];
+        let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+        exist.push(SimpleTlv::new(0x41, &obj));
+        push_command(&mut xtwi, seq, &exist);
+        push_response(&mut xtwi, seq, &[0x41, 0x01, *result, 0x90, 0x00]);
+    }
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.find_free_object_id(0x2000_0010, &mut delay).unwrap().0, [0x20, 0x00, 0x00, 0x12]);
+
+    // nothing to probe below the reserved range
+    assert!(se050.find_free_object_id(0x7fff_0000, &mut delay).is_err());
+}
+
+#[test]
+fn test_provision_rollback() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let key = [0x20, 0x00, 0x00, 0x01];
+    let bin = [0x20, 0x00, 0x00, 0x02];
+    let data = [0xaa; 8];
+
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, &key));
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut write_key = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    write_key.push(SimpleTlv::new(0x41, &key));
+    write_key.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    push_command(&mut xtwi, 0x40, &write_key);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    // provision() and write_binary_object() both look the object up
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, &bin));
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &exist);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    create.push(SimpleTlv::new(0x41, &bin));
+    create.push(SimpleTlv::new(0x42, &[0x00, 0x00]));
+    create.push(SimpleTlv::new(0x43, &[0x00, 0x08]));
+    create.push(SimpleTlv::new(0x44, &data));
+    push_command(&mut xtwi, 0x00, &create);
+    push_response(&mut xtwi, 0x00, &[0x6a, 0x80]);
+    // the failed create left nothing behind
+    push_command(&mut xtwi, 0x40, &exist);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut delete = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    delete.push(SimpleTlv::new(0x41, &key));
+    push_command(&mut xtwi, 0x00, &delete);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let steps = [
+        ProvisionStep::EcKey { objectid: key, curve: Se050ECCurveconstants::NistP256 },
+        ProvisionStep::Binary { objectid: bin, data: &data, policy: None },
+    ];
+    assert_eq!(se050.provision(&steps, true, &mut delay),
+        Err(ProvisionError { step: 1, error: Se050Error::UnknownError, rolled_back: true }));
+
+    // rejected before anything is sent
+    let steps: [ProvisionStep; MAX_PROVISION_STEPS + 1] = core::array::from_fn(|_| ProvisionStep::Binary { objectid: bin, data: &data, policy: None });
+    assert_eq!(se050.provision(&steps, true, &mut delay),
+        Err(ProvisionError { step: 0, error: Se050Error::TooManySteps, rolled_back: false }));
+}
+
+#[test]
+fn test_provision_rollback_partial() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let bin = [0x20, 0x00, 0x00, 0x02];
+    let data = [0xaa; 300];
+
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, &bin));
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &exist);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    // the file is created with its first chunk, the second one fails
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    create.push(SimpleTlv::new(0x41, &bin));
+    create.push(SimpleTlv::new(0x42, &[0x00, 0x00]));
+    create.push(SimpleTlv::new(0x43, &[0x01, 0x2c]));
+    create.push(SimpleTlv::new(0x44, &data[0..231]));
+    push_command(&mut xtwi, 0x00, &create);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    let mut update = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    update.push(SimpleTlv::new(0x41, &bin));
+    update.push(SimpleTlv::new(0x42, &[0x00, 0xe7]));
+    update.push(SimpleTlv::new(0x44, &data[231..300]));
+    push_command(&mut xtwi, 0x40, &update);
+    push_response(&mut xtwi, 0x40, &[0x6a, 0x80]);
+
+    // the half-written file is found and deleted
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x01, 0x90, 0x00]);
+    let mut delete = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    delete.push(SimpleTlv::new(0x41, &bin));
+    push_command(&mut xtwi, 0x40, &delete);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let steps = [ProvisionStep::Binary { objectid: bin, data: &data, policy: None }];
+    assert_eq!(se050.provision(&steps, true, &mut delay),
+        Err(ProvisionError { step: 0, error: Se050Error::UnknownError, rolled_back: true }));
+}
+
+fn lrc_frame(nad: u8, pcb: u8, data: &[u8]) -> heapless::Vec<u8, 261> {
+    let mut frame: heapless::Vec<u8, 261> = heapless::Vec::new();
+    frame.extend_from_slice(&[nad, pcb, data.len() as u8]).unwrap();
+    frame.extend_from_slice(data).unwrap();
+    let lrc = frame.iter().fold(0u8, |lrc, b| lrc ^ b);
+    frame.push(lrc).unwrap();
+    frame
+}
+
+#[test]
+fn test_lrc_error_detection() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x00, None);
+    let apdu: heapless::Vec<u8, 254> = c.byte_iter().collect();
+    xtwi.push_in(&lrc_frame(0x5a, 0x00, &apdu));
+    let frame = lrc_frame(0xa5, 0x00, &[0x90, 0x00]);
+    xtwi.push_out(&frame[0..3]);
+    xtwi.push_out(&frame[3..]);
+
+    // corrupted LRC: the block is requested again with R(1) and an LRC of its own
+    let good = lrc_frame(0xa5, 0x40, &[0x90, 0x00]);
+    let mut frame = good.clone();
+    frame[5] ^= 0x01;
+    xtwi.push_out(&frame[0..3]);
+    xtwi.push_out(&frame[3..]);
+    xtwi.push_in(&lrc_frame(0x5a, 0x91, &[]));
+    xtwi.push_out(&good[0..3]);
+    xtwi.push_out(&good[3..]);
+
+    // an LRC-protected ATR passes its own check, which validates the setting
+    xtwi.push_in(&lrc_frame(0x5a, 0xcf, &[]));
+    let frame = lrc_frame(0xa5, 0xef, &T1F_ATR[0..T1F_ATR.len()-2]);
+    xtwi.push_out(&frame[0..3]);
+    xtwi.push_out(&frame[3..]);
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    assert_eq!(t1.error_detection(), T1ErrorDetection::Crc);
+    t1.set_error_detection(T1ErrorDetection::Lrc);
+
+    assert!(t1.send_apdu(&c, &mut delay).is_ok());
+    let mut buf = [0u8; 16];
+    assert_eq!(t1.receive_apdu_raw(&mut buf, &mut delay).map(|r| r.sw), Ok(0x9000));
+    assert_eq!(t1.receive_apdu_raw(&mut buf, &mut delay).map(|r| r.sw), Ok(0x9000));
+    assert_eq!(t1.retransmissions(), 1);
+
+    let atr = t1.interface_soft_reset(&mut delay).unwrap();
+    assert_eq!(atr.dllp.ifsc, 254);
+    assert_eq!(t1.error_detection(), T1ErrorDetection::Lrc);
+}
+
+#[test]
+fn test_verify_user_id_attempts_remaining() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x2c, None);
+    c.push(SimpleTlv::new(0x41, b"wrong"));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x63, 0xc2]);
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x69, 0x82]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert_eq!(se050.verify_session_user_id(b"wrong", &mut delay), Err(Se050Error::AuthenticationFailed(2)));
+    assert_eq!(se050.verify_session_user_id(b"wrong", &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.session_state(), SessionState::None);
+}
+
+#[test]
+fn test_attest_with_factory_key() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let obj = [0x20, 0x00, 0x00, 0x40];
+    let freshness = [0x5au8; 16];
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x22, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x45, &FACTORY_ATTESTATION_KEY_EC));
+    c.push(SimpleTlv::new(0x46, &[0x21]));
+    c.push(SimpleTlv::new(0x47, &freshness));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0x04, 0x01, 0x46, 0x02, 0x30, 0x00, 0x90, 0x00]);
+
+    // an RSA algorithm selects the RSA factory key
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x22, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x45, &FACTORY_ATTESTATION_KEY_RSA));
+    c.push(SimpleTlv::new(0x46, &[Se050RSASignatureAlgo::RsaSha256Pkcs1 as u8]));
+    c.push(SimpleTlv::new(0x47, &freshness));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x02, 0x04, 0x01, 0x46, 0x01, 0x00, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let mut out = [0u8; 64];
+    let ec = Se050AttestationAlgo::Ec(Se050ECSignatureAlgo::SigEcdsaSha256);
+    assert_eq!(se050.attest_with_factory_key(&obj, ec, &freshness, &mut out, &mut delay), Ok(8));
+    assert_eq!(&out[0..8], &[0x41, 0x02, 0x04, 0x01, 0x46, 0x02, 0x30, 0x00]);
+    let rsa = Se050AttestationAlgo::Rsa(Se050RSASignatureAlgo::RsaSha256Pkcs1);
+    assert_eq!(se050.attest_with_factory_key(&obj, rsa, &freshness, &mut out, &mut delay), Ok(7));
+}
+
+#[test]
+fn test_get_nonce() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x00, 0x08]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x08, 1, 2, 3, 4, 5, 6, 7, 8, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let nonce: [u8; 8] = se050.get_nonce(&mut delay).unwrap();
+    assert_eq!(nonce, [1, 2, 3, 4, 5, 6, 7, 8]);
+}
+
+#[test]
+fn test_get_data() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    xtwi.push_in(&t1_frame(0x5a, 0x00, &[0x00, 0xca, 0x00, 0x66, 0x00]));
+    push_response(&mut xtwi, 0x00, &[0x66, 0x03, 0x73, 0x01, 0x06, 0x90, 0x00]);
+    xtwi.push_in(&t1_frame(0x5a, 0x40, &[0x00, 0xca, 0x00, 0xcf, 0x00]));
+    push_response(&mut xtwi, 0x40, &[0x6a, 0x88]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let mut out = [0u8; 16];
+    assert_eq!(se050.get_data(0x0066, &mut out, &mut delay), Ok(5));
+    assert_eq!(&out[0..5], &[0x66, 0x03, 0x73, 0x01, 0x06]);
+    assert_eq!(se050.get_data(0x00cf, &mut out, &mut delay), Err(Se050Error::UnknownError));
+}
+
+// ReadObject of `data.len()` bytes at `offset`, answered with `data`; `pcb` for the
+// command and the response
+fn push_read_binary(xtwi: &mut test_twi::TWI, pcb: (u8, u8), obj: &[u8; 4], offset: u16, data: &[u8]) {
+    let offset = offset.to_be_bytes();
+    let len = (data.len() as u16).to_be_bytes();
+    let mut read = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+    read.push(SimpleTlv::new(0x41, obj));
+    read.push(SimpleTlv::new(0x42, &offset));
+    read.push(SimpleTlv::new(0x43, &len));
+    push_command(xtwi, pcb.0, &read);
+    let mut rapdu = std::vec::Vec::from([0x41, data.len() as u8]);
+    rapdu.extend_from_slice(data);
+    rapdu.extend_from_slice(&[0x90, 0x00]);
+    push_response(xtwi, pcb.1, &rapdu);
+}
+
+#[test]
+fn test_certificate_store_load() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let obj = [0x10, 0x00, 0x00, 0x02];
+    let mut cert = [0u8; 300];
+    for (i, b) in cert.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    cert[0..4].copy_from_slice(&[0x30, 0x82, 0x01, 0x28]);
+
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &exist);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    create.push(SimpleTlv::new(0x41, &obj));
+    create.push(SimpleTlv::new(0x42, &[0x00, 0x00]));
+    create.push(SimpleTlv::new(0x43, &[0x01, 0x2c]));
+    create.push(SimpleTlv::new(0x44, &cert[0..231]));
+    push_command(&mut xtwi, 0x40, &create);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut update = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x06, 0x00, None);
+    update.push(SimpleTlv::new(0x41, &obj));
+    update.push(SimpleTlv::new(0x42, &[0x00, 0xe7]));
+    update.push(SimpleTlv::new(0x44, &cert[231..300]));
+    push_command(&mut xtwi, 0x00, &update);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    // the DER header first, then the next 253 bytes in a chained response
+    push_read_binary(&mut xtwi, (0x40, 0x40), &obj, 0, &cert[0..4]);
+    let mut read = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+    read.push(SimpleTlv::new(0x41, &obj));
+    read.push(SimpleTlv::new(0x42, &[0x00, 0x04]));
+    read.push(SimpleTlv::new(0x43, &[0x00, 0xfd]));
+    push_command(&mut xtwi, 0x00, &read);
+    let mut rapdu: heapless::Vec<u8, 300> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x41, 0x81, 0xfd]).unwrap();
+    rapdu.extend_from_slice(&cert[4..257]).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x20, &rapdu[0..254]);
+    xtwi.push_in(&t1_frame(0x5a, 0x90, &[]));
+    push_response(&mut xtwi, 0x40, &rapdu[254..]);
+    push_read_binary(&mut xtwi, (0x40, 0x00), &obj, 257, &cert[257..300]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    // trailing data after the SEQUENCE
+    assert_eq!(se050.store_certificate(&obj, &[0x30, 0x01, 0x00, 0x00], &mut delay), Err(Se050Error::UnknownError));
+    assert!(se050.store_certificate(&obj, &cert, &mut delay).is_ok());
+
+    let mut out = [0u8; 512];
+    assert_eq!(se050.load_certificate(&obj, &mut out, &mut delay), Ok(300));
+    assert_eq!(&out[0..300], &cert[..]);
+}
+
+#[test]
+fn test_certificate_load_short() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // a file of exactly 100 bytes: nothing beyond it is ever requested
+    let obj = [0x10, 0x00, 0x00, 0x03];
+    let mut cert = [0x11u8; 100];
+    cert[0..2].copy_from_slice(&[0x30, 0x62]);
+    push_read_binary(&mut xtwi, (0x00, 0x00), &obj, 0, &cert[0..4]);
+    push_read_binary(&mut xtwi, (0x40, 0x40), &obj, 4, &cert[4..100]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out = [0u8; 512];
+    assert_eq!(se050.load_certificate(&obj, &mut out, &mut delay), Ok(100));
+    assert_eq!(&out[0..100], &cert[..]);
+}
+
+// stand-in for AES-CTR on the card: the keystream only depends on the counter block
+fn fake_ctr(counter: u128, data: &[u8]) -> heapless::Vec<u8, 256> {
+    data.iter().enumerate().map(|(i, b)| {
+        let block = (counter + (i / 16) as u128).to_be_bytes();
+        b ^ block[15] ^ block[14] ^ (i % 16) as u8
+    }).collect()
+}
+
+// expect the CipherOneShot commands for one AesCtrContext::update() call
+fn push_ctr_update(xtwi: &mut test_twi::TWI, pcb: &mut u8, key: &[u8; 4], counter: &mut u128, data: &[u8]) {
+    for chunk in data.chunks(208) {
+        let iv = counter.to_be_bytes();
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0e, 0x42, Some(0));
+        c.push(SimpleTlv::new(0x41, key));
+        c.push(SimpleTlv::new(0x42, &[Se050CipherModeconstants::AesCtr as u8]));
+        c.push(SimpleTlv::new(0x43, chunk));
+        c.push(SimpleTlv::new(0x44, &iv));
+        push_command(xtwi, *pcb, &c);
+
+        let enc = fake_ctr(*counter, chunk);
+        let mut r: heapless::Vec<u8, 256> = heapless::Vec::new();
+        r.extend_from_slice(&[0x41, 0x81, enc.len() as u8]).unwrap();
+        r.extend_from_slice(&enc).unwrap();
+        r.extend_from_slice(&[0x90, 0x00]).unwrap();
+        push_response(xtwi, *pcb, &r);
+
+        *pcb ^= 0x40;
+        *counter += chunk.len().div_ceil(16) as u128;
+    }
+}
+
+#[test]
+fn test_aes_ctr_chunked() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let key = [0x20, 0x00, 0x00, 0x30];
+    let iv: [u8; 16] = [0xf0, 0xf1, 0xf2, 0xf3, 0xf4, 0xf5, 0xf6, 0xf7, 0xf8, 0xf9, 0xfa, 0xfb, 0xfc, 0xfd, 0xfe, 0xf0];
+    let mut data = [0u8; 1024];
+    for (i, b) in data.iter_mut().enumerate() {
+        *b = (i * 7) as u8;
+    }
+    let pieces = [48, 512, 464];
+
+    let mut pcb = 0x00;
+    let mut counter = u128::from_be_bytes(iv);
+    push_ctr_update(&mut xtwi, &mut pcb, &key, &mut counter, &data);
+    let mut counter = u128::from_be_bytes(iv);
+    let mut off = 0;
+    for len in pieces {
+        push_ctr_update(&mut xtwi, &mut pcb, &key, &mut counter, &data[off..off + len]);
+        off += len;
+    }
+    let mut counter = u128::from_be_bytes(iv);
+    push_ctr_update(&mut xtwi, &mut pcb, &key, &mut counter, &data[0..8]);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+
+    let mut oneshot = [0u8; 1024];
+    let mut ctx = AesCtrContext::new(key, iv);
+    assert_eq!(ctx.update(&mut se050, &data, &mut oneshot, &mut delay), Ok(1024));
+    // the low byte wraps into the next one after 16 blocks
+    assert_eq!(ctx.counter(), &(u128::from_be_bytes(iv) + 64).to_be_bytes());
+
+    let mut chunked = [0u8; 1024];
+    let mut ctx = AesCtrContext::new(key, iv);
+    let mut off = 0;
+    for len in pieces {
+        assert_eq!(ctx.update(&mut se050, &data[off..off + len], &mut chunked[off..], &mut delay), Ok(len));
+        off += len;
+    }
+    assert_eq!(&chunked[..], &oneshot[..]);
+
+    // a partial block ends the stream
+    let mut ctx = AesCtrContext::new(key, iv);
+    assert_eq!(ctx.update(&mut se050, &data[0..8], &mut chunked, &mut delay), Ok(8));
+    assert_eq!(ctx.update(&mut se050, &data[0..16], &mut chunked, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_get_userid_attempts() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    // UserID, authentication object, 2 failed attempts out of 5, no policy
+    let mut attributes: [u8; 15] = [
+        0x7f, 0xff, 0x02, 0x00, 0x01, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x05,
+        0x01,
+    ];
+    assert_eq!(crate::se050::auth_attempts_remaining(&attributes), Ok(3));
+    attributes[7] = 0x07;
+    assert_eq!(crate::se050::auth_attempts_remaining(&attributes), Ok(0));
+    attributes[13] = 0x00;
+    assert_eq!(crate::se050::auth_attempts_remaining(&attributes), Ok(u16::MAX));
+    attributes[5] = 0x00;
+    assert!(crate::se050::auth_attempts_remaining(&attributes).is_err());
+    attributes[5] = 0x01;
+    attributes[7] = 0x02;
+    attributes[13] = 0x05;
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x3b, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x7f, 0xff, 0x02, 0x00]));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut rapdu: heapless::Vec<u8, 32> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x42, attributes.len() as u8]).unwrap();
+    rapdu.extend_from_slice(&attributes).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert_eq!(se050.get_userid_attempts(ObjectId([0x7f, 0xff, 0x02, 0x00]), &mut delay), Ok(3));
+}
+
+#[test]
+fn test_transport_state() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x3e, None);
+    c.push(SimpleTlv::new(0x41, &[0x02]));
+    c.push(SimpleTlv::new(0x42, &[0x01]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x5b, Some(0));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x03, 0x01, 0x02, 0x02, 0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x3e, None);
+    c.push(SimpleTlv::new(0x41, &[0x02]));
+    c.push(SimpleTlv::new(0x42, &[0x02]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x69, 0x82]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    assert!(se050.set_transport_state(true, &mut delay).is_ok());
+    assert_eq!(se050.get_transport_state(&mut delay), Ok(true));
+    // unlocking needs the RESERVED_ID_TRANSPORT session
+    assert!(se050.set_transport_state(false, &mut delay).is_err());
+}
+
+#[test]
+fn test_applet_lifecycle() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x5b, Some(0));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x03, 0x02, 0x00, 0x01, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let state = se050.get_applet_lifecycle(&mut delay).unwrap();
+    assert_eq!(state, LifecycleState { locked: false, restrict_mode: 0, platform_scp_required: true });
+    assert!(!state.is_operational());
+
+    // short ReadState response
+    assert_eq!(se050.get_applet_lifecycle(&mut delay), Err(Se050Error::MissingResponseTlv));
+}
+
+#[test]
+fn test_refresh_session() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let policy = [0x00, 0x10, 0x00, 0x00];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1e, None);
+    c.push(SimpleTlv::new(0x11, &policy));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x00, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert!(se050.refresh_session(&policy, &mut delay).is_ok());
+    // RefreshSession has no response data
+    assert_eq!(se050.refresh_session(&policy, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_set_eccurve_param() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // curve id, parameter and value under TAG_1, TAG_2 and TAG_3
+    let a = [0xffu8; 32];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x0b, 0x40, None);
+    c.push(SimpleTlv::new(0x41, &[0x03]));
+    c.push(SimpleTlv::new(0x42, &[0x01]));
+    c.push(SimpleTlv::new(0x43, &a));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert!(se050.set_eccurve_param(Se050ECCurveconstants::NistP256, Se050ECCurveParamconstants::CurveParamA, &a, &mut delay).is_ok());
+}
+
+#[test]
+fn test_rsa_verify() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // the signature goes under TAG_5, after the data under TAG_3
+    let key = [0x20, 0x00, 0x00, 0x41];
+    let data = [0x5au8; 32];
+    let sig = [0xa5u8; 64];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x0a, Some(3));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &[0x28]));
+    c.push(SimpleTlv::new(0x43, &data));
+    c.push(SimpleTlv::new(0x45, &sig));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x01, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert!(se050.rsa_verify(&key, Se050RSASignatureAlgo::RsaSha256Pkcs1, &data, &sig, &mut delay).is_ok());
+}
+
+#[test]
+fn test_get_eccurve_id() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0x40];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x82, 0x0b, 0x36, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x01, 0x10, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x3f, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.get_eccurve_id(&obj, &mut delay), Ok(Se050ECCurveconstants::Secp256k1));
+    // not an ECCurve constant
+    assert_eq!(se050.get_eccurve_id(&obj, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_write_ec_public_key() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let obj = [0x20, 0x00, 0x00, 0x40];
+    let mut pubkey = [0x5au8; 65];
+    pubkey[0] = 0x04;
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x21, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    c.push(SimpleTlv::new(0x44, &pubkey));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    // wrong length for the curve, compressed point: rejected before sending
+    assert!(se050.write_ec_public_key(&obj, Se050ECCurveconstants::NistP384, &pubkey, &mut delay).is_err());
+    let mut compressed = [0x5au8; 33];
+    compressed[0] = 0x02;
+    assert!(se050.write_ec_public_key(&obj, Se050ECCurveconstants::NistP256, &compressed, &mut delay).is_err());
+
+    let id = se050.write_ec_public_key(&obj, Se050ECCurveconstants::NistP256, &pubkey, &mut delay).unwrap();
+    assert_eq!(id.0, obj);
+}
+
+#[test]
+fn test_applet_variant_param() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x3f, Some(0));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0xa2, 0x01, 0x90, 0x00]);
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x40, Some(0));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x6d, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    let mut out = [0u8; 8];
+    assert_eq!(se050.get_applet_variant(&mut out, &mut delay), Ok(2));
+    assert_eq!(&out[0..2], &[0xa2, 0x01]);
+    assert_eq!(se050.get_applet_param(&mut out, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_eddsa_sign_chained() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let key = [0x20, 0x00, 0x00, 0x25];
+    let mut message = std::vec::Vec::new();
+    for i in 0..4096u32 {
+        message.push((i * 7 + (i >> 8)) as u8);
+    }
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &[0xa3]));
+    c.push(SimpleTlv::new(0x43, &message));
+    let apdu: std::vec::Vec<u8> = c.byte_iter().collect();
+    assert_eq!(apdu.len(), c.serialized_len());
+
+    // 254-byte I-blocks, each acknowledged by R(N(R)) for the next one;
+    // the card asks for the third block twice
+    let blocks: std::vec::Vec<&[u8]> = apdu.chunks(MAX_IFSC).collect();
+    assert_eq!(blocks.len(), 17);
+    for (i, block) in blocks.iter().enumerate() {
+        let seq = (i % 2) as u8;
+        let more = i + 1 < blocks.len();
+        let pcb = (seq << 6) | if more { 0x20 } else { 0 };
+        xtwi.push_in(&t1_frame(0x5a, pcb, block));
+        if i == 2 {
+            push_response(&mut xtwi, 0x80 | (seq << 4) | 0x01, &[]);
+            xtwi.push_in(&t1_frame(0x5a, pcb, block));
+        }
+        if more {
+            push_response(&mut xtwi, 0x80 | ((seq ^ 1) << 4), &[]);
+        }
+    }
+    // the key in the SE is the one of RFC 8032 7.1 TEST 1, secret key
+    // 9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60; the signature
+    // of `message` under it is the one of a reference Ed25519 implementation
+    let sig = [
+        0x49, 0x6a, 0xcd, 0xc3, 0xa3, 0x11, 0xf9, 0x7d, 0xda, 0x9b, 0x64, 0x1f, 0x8c, 0xd0, 0xc4, 0x45,
+        0x54, 0x02, 0xb9, 0x7f, 0x11, 0xc7, 0x1b, 0x78, 0x0d, 0x97, 0x89, 0xd2, 0xeb, 0x0d, 0xbc, 0x56,
+        0xc5, 0xb4, 0x17, 0x8d, 0x74, 0xcc, 0xa2, 0xa9, 0xcc, 0xcd, 0x5e, 0x93, 0xcf, 0xd8, 0xc2, 0xfb,
+        0x35, 0xb1, 0x28, 0x1d, 0x62, 0x3d, 0x77, 0xa0, 0xfe, 0xb5, 0x14, 0xf6, 0x99, 0x0c, 0x7e, 0x0b,
+    ];
+    let mut rapdu = std::vec::Vec::from([0x41, 0x40]);
+    rapdu.extend_from_slice(&sig);
+    rapdu.extend_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    // RFC 8032 7.1 TEST 1 itself: the empty message, sent in one I-block
+    let rfc_sig = [
+        0xe5, 0x56, 0x43, 0x00, 0xc3, 0x60, 0xac, 0x72, 0x90, 0x86, 0xe2, 0xcc, 0x80, 0x6e, 0x82, 0x8a,
+        0x84, 0x87, 0x7f, 0x1e, 0xb8, 0xe5, 0xd9, 0x74, 0xd8, 0x73, 0xe0, 0x65, 0x22, 0x49, 0x01, 0x55,
+        0x5f, 0xb8, 0x82, 0x15, 0x90, 0xa3, 0x3b, 0xac, 0xc6, 0x1e, 0x39, 0x70, 0x1c, 0xf9, 0xb4, 0x6b,
+        0xd2, 0x5b, 0xf5, 0xf0, 0x59, 0x5b, 0xbe, 0x24, 0x65, 0x51, 0x41, 0x43, 0x8e, 0x7a, 0x10, 0x0b,
+    ];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &[0xa3]));
+    c.push(SimpleTlv::new(0x43, &[]));
+    push_command(&mut xtwi, 0x40, &c);
+    let mut rapdu = std::vec::Vec::from([0x41, 0x40]);
+    rapdu.extend_from_slice(&rfc_sig);
+    rapdu.extend_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x40, &rapdu);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut signature = [0u8; 64];
+    assert!(se050.eddsa_sign(&key, Se050EDSignatureAlgo::SigEd25519pure, &message, &mut signature, &mut delay).is_ok());
+    assert_eq!(signature, sig);
+    assert!(se050.eddsa_sign(&key, Se050EDSignatureAlgo::SigEd25519pure, &[], &mut signature, &mut delay).is_ok());
+    assert_eq!(signature, rfc_sig);
+}
+
+#[test]
+fn test_receive_retransmission() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let rapdu = [0x41, 0x02, 0x12, 0x34, 0x90, 0x00];
+    let mut corrupt = t1_frame(0xa5, 0x00, &rapdu);
+    let last = corrupt.len() - 1;
+    corrupt[last] ^= 0xff;
+
+    // one bad block, then the good one after R(0) with EDC error
+    xtwi.push_out(&corrupt[0..3]);
+    xtwi.push_out(&corrupt[3..]);
+    xtwi.push_in(&t1_frame(0x5a, 0x81, &[]));
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    // the sequence number moves on: retransmissions now ask for block 1
+    for _ in 0..4 {
+        let mut corrupt = t1_frame(0xa5, 0x40, &rapdu);
+        corrupt[last] ^= 0xff;
+        xtwi.push_out(&corrupt[0..3]);
+        xtwi.push_out(&corrupt[3..]);
+    }
+    for _ in 0..3 {
+        xtwi.push_in(&t1_frame(0x5a, 0x91, &[]));
+    }
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut buf = [0u8; 64];
+    let r = t1.receive_apdu(&mut buf, &mut delay).unwrap();
+    assert_eq!(r.get_tlv(0x41).unwrap().get_data(), &[0x12, 0x34]);
+    assert_eq!(t1.retransmissions(), 1);
+
+    let mut buf = [0u8; 64];
+    assert!(matches!(t1.receive_apdu(&mut buf, &mut delay), Err(T1Error::ChecksumError)));
+    assert_eq!(t1.retransmissions(), 4);
+    t1.reset_retransmissions();
+    assert_eq!(t1.retransmissions(), 0);
+    t1.release().assert_done();
+}
+
+#[test]
+fn test_receive_apdu_trailing_bytes() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0x12, 0x34, 0x42, 0x01, 0x05, 0x01, 0x90, 0x00]);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut buf = [0u8; 64];
+    let r = t1.receive_apdu(&mut buf, &mut delay).unwrap();
+    assert_eq!(r.sw, 0x9000);
+    assert_eq!(r.tlvs.len(), 2);
+    assert_eq!(r.get_tlv(0x42).unwrap().get_data(), &[0x05]);
+    assert_eq!(r.trailing, &[0x01]);
+
+    let mut buf = [0u8; 64];
+    let r = t1.receive_apdu(&mut buf, &mut delay).unwrap();
+    assert_eq!(r.get_tlv(0x41).unwrap().get_data(), &[0x12, 0x34]);
+    assert!(r.trailing.is_empty());
+}
+
+#[test]
+fn test_receive_apdu_truncation() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut rapdu: heapless::Vec<u8, 64> = heapless::Vec::new();
+    for i in 0..(MAX_TLVS as u8 + 2) {
+        rapdu.extend_from_slice(&[0x41 + i, 0x01, i]).unwrap();
+    }
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &rapdu);
+    push_response(&mut xtwi, 0x40, &rapdu);
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut buf = [0u8; 64];
+    assert!(matches!(t1.receive_apdu(&mut buf, &mut delay), Err(T1Error::TlvParseError)));
+
+    t1.set_truncate_tlvs(true);
+    let r = t1.receive_apdu(&mut buf, &mut delay).unwrap();
+    assert!(r.is_truncated());
+    assert_eq!(r.dropped_tlvs, 2);
+    assert_eq!(r.tlvs.len(), MAX_TLVS);
+    assert_eq!(r.sw, 0x9000);
+}
+
+#[test]
+fn test_self_test() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let key = [0x20, 0x00, 0x00, 0x50];
+    let random = [0x11u8; 16];
+    let algo = [0x21];
+    let mut hash = [0u8; 32];
+    hash[0..16].copy_from_slice(&random);
+    let sig = [0x30, 0x06, 0x02, 0x01, 0x01, 0x02, 0x01, 0x02];
+
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    xtwi.push_out(T1FRH_GP_SELECT_SE050);
+    xtwi.push_out(T1F_APP_VERSION);
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x20, Some(0x0b));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x07, 0x03, 0x01, 0x01, 0x6f, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x00, 0x10]));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut r: heapless::Vec<u8, 32> = heapless::Vec::new();
+    r.extend_from_slice(&[0x41, 0x10]).unwrap();
+    r.extend_from_slice(&random).unwrap();
+    r.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &r);
+
+    // the key pair is generated in transient memory
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &algo));
+    c.push(SimpleTlv::new(0x43, &hash));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut r: heapless::Vec<u8, 32> = heapless::Vec::new();
+    r.extend_from_slice(&[0x41, sig.len() as u8]).unwrap();
+    r.extend_from_slice(&sig).unwrap();
+    r.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &r);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x0a, Some(3));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &algo));
+    c.push(SimpleTlv::new(0x43, &hash));
+    c.push(SimpleTlv::new(0x45, &sig));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x01, 0x90, 0x00]);
+
+    // the key pair is removed again
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    c.push(SimpleTlv::new(0x41, &key));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+
+    let report = se050.self_test(Some(&key), &mut delay).unwrap();
+    assert_eq!(report, SelfTestReport { reset: true, select: true, version: true, random: true, sign_verify: Some(true) });
+    assert!(report.passed());
+    assert!(se050.app_info().is_some());
+}
+
+// stand-in for AES-ECB under the KEK on the card
+fn fake_ecb(block: &[u8; 16]) -> [u8; 16] {
+    let mut out = [0u8; 16];
+    for (i, b) in out.iter_mut().enumerate() {
+        *b = block[(i + 5) % 16] ^ 0x3c ^ i as u8;
+    }
+    out
+}
+
+#[test]
+fn test_key_wrap() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let kek = [0x20, 0x00, 0x00, 0x50];
+    let target = [0x20, 0x00, 0x00, 0x51];
+    let key: [u8; 16] = [0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd, 0xee, 0xff];
+
+    // RFC3394 2.2.1, one CipherOneShot per step
+    let mut pcb = 0x00;
+    let mut a = [0xa6u8; 8];
+    let mut r = [[0u8; 8]; 2];
+    r[0].copy_from_slice(&key[0..8]);
+    r[1].copy_from_slice(&key[8..16]);
+    for j in 0..6 {
+        for (i, ri) in r.iter_mut().enumerate() {
+            let mut block = [0u8; 16];
+            block[0..8].copy_from_slice(&a);
+            block[8..16].copy_from_slice(ri);
+            let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0e, 0x42, Some(0));
+            c.push(SimpleTlv::new(0x41, &kek));
+            c.push(SimpleTlv::new(0x42, &[Se050CipherModeconstants::AesEcbNopad as u8]));
+            c.push(SimpleTlv::new(0x43, &block));
+            push_command(&mut xtwi, pcb, &c);
+
+            let enc = fake_ecb(&block);
+            let mut resp: heapless::Vec<u8, 32> = heapless::Vec::new();
+            resp.extend_from_slice(&[0x41, 0x10]).unwrap();
+            resp.extend_from_slice(&enc).unwrap();
+            resp.extend_from_slice(&[0x90, 0x00]).unwrap();
+            push_response(&mut xtwi, pcb, &resp);
+            pcb ^= 0x40;
+
+            let t = (2 * j + i + 1) as u64;
+            a = (u64::from_be_bytes(enc[0..8].try_into().unwrap()) ^ t).to_be_bytes();
+            ri.copy_from_slice(&enc[8..16]);
+        }
+    }
+    let mut expected = [0u8; 24];
+    expected[0..8].copy_from_slice(&a);
+    expected[8..16].copy_from_slice(&r[0]);
+    expected[16..24].copy_from_slice(&r[1]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x03, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &target));
+    c.push(SimpleTlv::new(0x42, &kek));
+    c.push(SimpleTlv::new(0x43, &expected));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    // not a whole number of 64-bit blocks, output too small: rejected before sending
+    let mut out = [0u8; 24];
+    assert!(se050.wrap_key(ObjectId(kek), &key[0..12], &mut out, &mut delay).is_err());
+    assert!(se050.wrap_key(ObjectId(kek), &key, &mut out[0..23], &mut delay).is_err());
+    assert!(se050.unwrap_key(ObjectId(kek), ObjectId(target), SymmKeyType::Aes, &expected[0..16], &mut delay).is_err());
+
+    assert_eq!(se050.wrap_key(ObjectId(kek), &key, &mut out, &mut delay), Ok(24));
+    assert_eq!(out, expected);
+    let id = se050.unwrap_key(ObjectId(kek), ObjectId(target), SymmKeyType::Aes, &out, &mut delay).unwrap();
+    assert_eq!(id.0, target);
+}
+
+#[test]
+fn test_import_wrapped_aes_key() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let kek = [0x20, 0x00, 0x00, 0x52];
+    let target = [0x20, 0x00, 0x00, 0x53];
+    let wrapped = [0x3cu8; 40];
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x03, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &target));
+    c.push(SimpleTlv::new(0x42, &kek));
+    c.push(SimpleTlv::new(0x43, &wrapped));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    // 48 bytes would unwrap to 40, not an AES key length
+    assert_eq!(se050.import_wrapped_aes_key(ObjectId(target), ObjectId(kek), &[0x3c; 48], &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.import_wrapped_aes_key(ObjectId(target), ObjectId(kek), &wrapped[..20], &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.import_wrapped_aes_key(ObjectId(target), ObjectId(kek), &wrapped, &mut delay), Ok(()));
+}
+
+#[test]
+fn test_write_symm_key_length() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let xtwi = test_twi::TWI::scripted();
+
+    // keys other than 16 bytes are refused before anything is sent
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.write_aes_key(&[0x11; 32], &mut delay), Err(Se050Error::UnknownError));
+    let id = [0x20, 0x00, 0x00, 0x54];
+    assert_eq!(se050.write_hmac_key(&[], &id, &[0; 4], &[0x11; 24], &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_object_count() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let pages: [(u16, u8, usize); 2] = [(0, 0x02, 3), (3, 0x01, 2)];
+    let mut pcb = 0x00;
+    for (offset, more, n) in pages {
+        let offset = offset.to_be_bytes();
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x82, 0x00, 0x25, Some(0));
+        c.push(SimpleTlv::new(0x41, &offset));
+        c.push(SimpleTlv::new(0x42, &[0xff]));
+        push_command(&mut xtwi, pcb, &c);
+
+        let mut r: heapless::Vec<u8, 32> = heapless::Vec::new();
+        r.extend_from_slice(&[0x41, 0x01, more, 0x42, (4 * n) as u8]).unwrap();
+        for i in 0..n {
+            r.extend_from_slice(&[0x20, 0x00, 0x00, i as u8]).unwrap();
+        }
+        r.extend_from_slice(&[0x90, 0x00]).unwrap();
+        push_response(&mut xtwi, pcb, &r);
+        pcb ^= 0x40;
+    }
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.object_count(&mut delay), Ok(5));
+}
+
+#[test]
+fn test_des_key_checks() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    // CONFIG_DES (0x0100) cleared
+    push_response(&mut xtwi, 0x00, &[0x03, 0x01, 0x01, 0x6e, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+
+    assert_eq!(DesKeyType::from_key_len(8), Some(DesKeyType::SingleDes));
+    assert_eq!(DesKeyType::from_key_len(16), Some(DesKeyType::TwoKey3Des));
+    assert_eq!(DesKeyType::from_key_len(24), Some(DesKeyType::ThreeKey3Des));
+    assert_eq!(DesKeyType::from_key_len(12), None);
+    assert!(Se050CipherModeconstants::DesEcbPkcs5.is_des());
+    assert!(!Se050CipherModeconstants::AesEcbNopad.is_des());
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let obj = [0x20, 0x00, 0x00, 0x60];
+    let kek = [0u8; 4];
+    assert_eq!(se050.write_des_key(&[], &obj, &kek, &[0u8; 12], &mut delay), Err(Se050Error::UnknownError));
+
+    assert!(se050.enable(&mut delay).is_ok());
+    assert!(!se050.app_info().unwrap().has_des());
+    assert_eq!(se050.write_des_key(&[], &obj, &kek, &[0u8; 8], &mut delay), Err(Se050Error::DesUnavailable));
+    assert_eq!(se050.write_des_key(&[], &obj, &kek, &[0u8; 24], &mut delay), Err(Se050Error::DesUnavailable));
+    let mode = Se050CipherModeconstants::DesCbcNopad;
+    assert_eq!(se050.cipher_one_shot_encrypt(&obj, mode, &[0u8; 8], &[], &mut delay), Err(Se050Error::DesUnavailable));
+    assert_eq!(se050.cipher_one_shot_decrypt(&obj, mode, &[0u8; 8], &[], &mut delay), Err(Se050Error::DesUnavailable));
+}
+
+#[test]
+fn test_fips_mode() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x20, Some(0));
+    // CONFIG_FIPS_MODE_DISABLED (0x1000) set, then cleared
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x07, 0x03, 0x01, 0x01, 0x7f, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x07, 0x03, 0x01, 0x01, 0x6f, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.is_fips_mode(&mut delay), Ok(false));
+    assert_eq!(se050.is_fips_mode(&mut delay), Ok(true));
+    assert!(se050.app_info().unwrap().is_fips_mode());
+
+    // rejected before anything is sent
+    let obj = [0x20, 0x00, 0x00, 0x60];
+    assert_eq!(se050.write_des_key(&[], &obj, &[0u8; 4], &[0u8; 8], &mut delay), Err(Se050Error::FipsRestricted));
+    let mut sig = [0u8; 72];
+    assert_eq!(se050.ecdsa_sign(&obj, Se050ECSignatureAlgo::SigEcdsaSha, &[0u8; 20], &mut sig, &mut delay), Err(Se050Error::FipsRestricted));
+    assert_eq!(se050.rsa_sign(&obj, Se050RSASignatureAlgo::RsaSha1Pkcs1, &[0u8; 20], &mut delay), Err(Se050Error::FipsRestricted));
+}
+
+// stand-in for a host ECDSA implementation
+struct FakeVerifier;
+
+impl AttestationVerifier for FakeVerifier {
+    fn verify(&self, publickey: &[u8], message: &[u8], signature: &[u8]) -> bool {
+        let sum = message.iter().fold(publickey[0], |acc, b| acc.wrapping_add(*b));
+        signature == [sum]
+    }
+}
+
+#[test]
+fn test_attestation_verify() {
+    let freshness = [0x5au8; 16];
+    let mut resp: heapless::Vec<u8, 128> = heapless::Vec::new();
+    resp.extend_from_slice(&[0x41, 0x02, 0xaa, 0xbb]).unwrap();
+    resp.extend_from_slice(&[0x42, 0x03, 0x01, 0x02, 0x03]).unwrap();
+    resp.extend_from_slice(&[0x43, 0x0c]).unwrap();
+    resp.extend_from_slice(&[0x11; 12]).unwrap();
+    resp.extend_from_slice(&[0x44, 0x10]).unwrap();
+    resp.extend_from_slice(&freshness).unwrap();
+    resp.extend_from_slice(&[0x45, 0x12]).unwrap();
+    resp.extend_from_slice(&[0x22; 18]).unwrap();
+    let signed_len = resp.len();
+    let publickey = [0x04, 0x01];
+    let sum = resp.iter().fold(publickey[0], |acc: u8, b| acc.wrapping_add(*b));
+    resp.extend_from_slice(&[0x46, 0x01, sum]).unwrap();
+
+    let att = AttestationResult::parse(&resp).unwrap();
+    assert_eq!(att.data, &[0xaa, 0xbb]);
+    assert_eq!(att.attributes, &[0x01, 0x02, 0x03]);
+    assert_eq!(att.chip_id, &[0x22; 18]);
+    assert_eq!(att.signed_data, &resp[0..signed_len]);
+    assert_eq!(att.verify(&FakeVerifier, &publickey, &freshness), Ok(()));
+    assert_eq!(att.verify(&FakeVerifier, &[0x05], &freshness), Err(Se050Error::VerificationFailed));
+    assert_eq!(att.verify(&FakeVerifier, &publickey, &[0u8; 16]), Err(Se050Error::VerificationFailed));
+
+    // missing signature, duplicate or foreign tags
+    assert!(AttestationResult::parse(&resp[0..signed_len]).is_err());
+    assert!(AttestationResult::parse(&[0x41, 0x00, 0x41, 0x00]).is_err());
+    assert!(AttestationResult::parse(&[0x49, 0x00]).is_err());
+}
+
+#[test]
+fn test_read_object_attested() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0x01];
+    let key = [0x20, 0x00, 0x00, 0x02];
+    let freshness = [0x5au8; 16];
+    let algo = Se050AttestationAlgo::Ec(Se050ECSignatureAlgo::SigEcdsaSha256);
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x22, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x45, &key));
+    c.push(SimpleTlv::new(0x46, &[0x21]));
+    c.push(SimpleTlv::new(0x47, &freshness));
+    for (pcb, fresh) in [(0x00, freshness), (0x40, [0u8; 16])] {
+        push_command(&mut xtwi, pcb, &c);
+        let mut resp: heapless::Vec<u8, 128> = heapless::Vec::new();
+        resp.extend_from_slice(&[0x41, 0x03, 0x04, 0xaa, 0xbb]).unwrap();
+        resp.extend_from_slice(&[0x42, 0x01, 0x01, 0x43, 0x0c]).unwrap();
+        resp.extend_from_slice(&[0x11; 12]).unwrap();
+        resp.extend_from_slice(&[0x44, 0x10]).unwrap();
+        resp.extend_from_slice(&fresh).unwrap();
+        resp.extend_from_slice(&[0x45, 0x12]).unwrap();
+        resp.extend_from_slice(&[0x22; 18]).unwrap();
+        resp.extend_from_slice(&[0x46, 0x02, 0x30, 0x00, 0x90, 0x00]).unwrap();
+        push_response(&mut xtwi, pcb, &resp);
+    }
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out = [0u8; 128];
+    let att = se050.read_object_attested(&obj, &key, algo, &freshness, &mut out, &mut delay).unwrap();
+    assert_eq!(att.data, &[0x04, 0xaa, 0xbb]);
+    assert_eq!(att.chip_id, &[0x22; 18]);
+    assert_eq!(att.signature, &[0x30, 0x00]);
+    // the signature covers every TLV before it
+    assert_eq!(att.signed_data.len(), 5 + 3 + 14 + 18 + 20);
+    // a response that does not echo our freshness random is rejected
+    assert_eq!(se050.read_object_attested(&obj, &key, algo, &freshness, &mut out, &mut delay), Err(Se050Error::VerificationFailed));
+}
+
+#[test]
+fn test_requires_auth() {
+    assert_eq!(requires_auth(Se050Operation::DeleteAll), Some(AuthKind::FactoryReset));
+    assert_eq!(requires_auth(Se050Operation::SetLockStateUnlocked).unwrap().object_id(), RESERVED_ID_TRANSPORT);
+    assert_eq!(requires_auth(Se050Operation::SetLockStateLocked), None);
+    assert_eq!(requires_auth(Se050Operation::GetRandom), None);
+
+    let ops = [Se050Operation::GetVersion, Se050Operation::SetLockStateUnlocked, Se050Operation::DeleteAll];
+    assert_eq!(check_sequence(&ops[0..2], Some(&RESERVED_ID_TRANSPORT)), Ok(()));
+    assert_eq!(check_sequence(&ops, None), Err(1));
+    assert_eq!(check_sequence(&ops, Some(&RESERVED_ID_TRANSPORT)), Err(2));
+}
+
+#[test]
+fn test_get_memory_info() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let free: [u16; 3] = [0x7fff, 0x0400, 0x0230];
+    let mut pcb = 0x00;
+    for (i, f) in free.iter().enumerate() {
+        let memory = [i as u8 + 1];
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x22, Some(0));
+        c.push(SimpleTlv::new(0x41, &memory));
+        push_command(&mut xtwi, pcb, &c);
+        let f = f.to_be_bytes();
+        push_response(&mut xtwi, pcb, &[0x41, 0x02, f[0], f[1], 0x90, 0x00]);
+        pcb ^= 0x40;
+    }
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let info = se050.get_memory_info(&mut delay).unwrap();
+    assert_eq!(info, MemoryInfo { persistent: 0x7fff, transient_reset: 0x0400, transient_deselect: 0x0230 });
+}
+
+#[test]
+fn test_recover() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    xtwi.push_out(T1FRH_GP_SELECT_SE050);
+    xtwi.push_out(T1F_APP_VERSION);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    se050.set_session_state(SessionState::UserId);
+    assert!(se050.recover(&mut delay).is_ok());
+    assert_eq!(se050.session_state(), SessionState::None);
+    assert_eq!(se050.app_info().unwrap().applet_version(), 0x030101);
+}
+
+#[test]
+fn test_generate_aes_key() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let xtwi = test_twi::TWI::scripted();
+
+    // no command is sent
+    let obj = [0x20, 0x00, 0x00, 0x70];
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.generate_aes_key(&obj, 256, &mut delay), Err(Se050Error::Unsupported));
+}
+
+#[test]
+fn test_key_config() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0x71];
+    let policy = [0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x20, 0x00, 0x00];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0xc1, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x11, &policy));
+    c.push(SimpleTlv::new(0x12, &[0x00, 0x05]));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[0x03]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let kek = [0x20, 0x00, 0x00, 0x10];
+    let wrapped = [0x77u8; 24];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x03, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &kek));
+    c.push(SimpleTlv::new(0x43, &wrapped));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let config = KeyConfig { policy: Some(&policy), transient: true, max_attempts: Some(5) };
+    assert_eq!(se050.generate_ec_key_with_config(&obj, Se050ECCurveconstants::NistP256, &config, &mut delay).unwrap().0, obj);
+    let id = se050.write_symm_key_with_config(&obj, SymmKeyType::Aes, Some(&kek), &wrapped, &KeyConfig::default(), &mut delay).unwrap();
+    assert_eq!(id.0, obj);
+}
+
+#[test]
+fn test_mac_context() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let key = [0x20, 0x00, 0x00, 0x30];
+    let obj = [0x00, 0x01];
+    let mut msg = [0u8; 200];
+    for (i, b) in msg.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    // the mock "MAC" is a byte sum, enough to see every input byte arrives once
+    let mac = [msg.iter().fold(0u8, |acc, b| acc.wrapping_add(*b)), 0x5a];
+    let mac_resp = [0x41, 0x02, mac[0], mac[1], 0x90, 0x00];
+
+    let mut pcb = 0x00;
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x10, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[0x03]));
+    c.push(SimpleTlv::new(0x43, &[0x19]));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+    pcb ^= 0x40;
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0d, 0x03, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &obj));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+    pcb ^= 0x40;
+
+    for chunk in [&msg[0..50], &msg[50..]] {
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0d, 0x0c, None);
+        c.push(SimpleTlv::new(0x41, chunk));
+        c.push(SimpleTlv::new(0x42, &obj));
+        push_command(&mut xtwi, pcb, &c);
+        push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+        pcb ^= 0x40;
+    }
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0d, 0x0d, Some(0));
+    c.push(SimpleTlv::new(0x41, &[]));
+    c.push(SimpleTlv::new(0x42, &obj));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &mac_resp);
+    pcb ^= 0x40;
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x10, 0x28, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+    pcb ^= 0x40;
+
+    // the same message in one shot
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0d, 0x45, Some(0));
+    c.push(SimpleTlv::new(0x41, &key));
+    c.push(SimpleTlv::new(0x42, &[0x19]));
+    c.push(SimpleTlv::new(0x43, &msg));
+    push_command(&mut xtwi, pcb, &c);
+    push_response(&mut xtwi, pcb, &mac_resp);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut ctx = MacContext::init(&mut se050, &key, Se050MACAlgoconstants::HmacSha256, obj, &mut delay).unwrap();
+    ctx.update(&mut se050, &msg[0..50], &mut delay).unwrap();
+    ctx.update(&mut se050, &msg[50..], &mut delay).unwrap();
+    let mut chunked = [0u8; 32];
+    let len = ctx.finish(&mut se050, &mut chunked, &mut delay).unwrap();
+
+    let mut oneshot = [0u8; 32];
+    let oneshot_len = se050.mac_one_shot_into(&key, Se050MACAlgoconstants::HmacSha256, &msg, &mut oneshot, &mut delay).unwrap();
+    assert_eq!(&chunked[0..len], &oneshot[0..oneshot_len]);
+    assert_eq!(&chunked[0..len], &mac);
+}
+
+#[test]
+fn test_shared_i2c() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    xtwi.push_out(T1FRH_GP_SELECT_SE050);
+    xtwi.push_out(T1F_APP_VERSION);
+    let bus = core::cell::RefCell::new(xtwi);
+
+    {
+        let t1 = T1overI2C::new(SharedI2c::new(&bus), 0x48, 0x5a);
+        let mut se050 = Se050::new(t1);
+        assert!(se050.enable(&mut delay).is_ok());
+        // the bus is free again between transfers
+        assert!(bus.try_borrow_mut().is_ok());
+    }
+
+    // release() hands the proxy back for another driver on the same bus
+    let t1 = T1overI2C::new(SharedI2c::new(&bus), 0x48, 0x5a);
+    let mut shared = t1.release();
+    // reaches the mock, which has nothing left to read
+    assert!(embedded_hal::blocking::i2c::Read::read(&mut shared, 0x48, &mut [0u8; 1]).is_err());
+}
+
+#[test]
+fn test_write_ec_private_key() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let obj = [0x20, 0x00, 0x00, 0x80];
+    let mut d = [0u8; 32];
+    d[31] = 0x01;
+    let mut pubkey = [0x5au8; 65];
+    pubkey[0] = 0x04;
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x41, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    c.push(SimpleTlv::new(0x43, &d));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    c.push(SimpleTlv::new(0x43, &d));
+    c.push(SimpleTlv::new(0x44, &pubkey));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    // n - 1 is the largest valid scalar
+    let order = Se050ECCurveconstants::NistP256.order().unwrap();
+    let mut n_minus_1 = [0u8; 32];
+    n_minus_1.copy_from_slice(order);
+    n_minus_1[31] -= 1;
+    assert!(Se050ECCurveconstants::NistP256.check_private_key(&n_minus_1).is_ok());
+    assert_eq!(Se050ECCurveconstants::NistP521.order().unwrap().len(), 66);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+
+    // zero, n, wrong length, curve without a known order: rejected before sending
+    assert!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::NistP256, &[0u8; 32], None, &mut delay).is_err());
+    assert!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::NistP256, order, None, &mut delay).is_err());
+    assert!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::NistP256, &d[1..], None, &mut delay).is_err());
+    assert!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::Brainpool320, &[1u8; 40], None, &mut delay).is_err());
+
+    assert_eq!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::NistP256, &d, None, &mut delay).unwrap().0, obj);
+    assert_eq!(se050.write_ec_private_key(&obj, Se050ECCurveconstants::NistP256, &d, Some(&pubkey), &mut delay).unwrap().0, obj);
+}
+
+#[test]
+fn test_process() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut inner = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    inner.push(SimpleTlv::new(0x41, &[0x00, 0x02]));
+    push_command(&mut xtwi, 0x00, &inner);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+    push_session_command(&mut xtwi, 0x40, &inner);
+    push_response(&mut xtwi, 0x40, &[0x69, 0x85]);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+
+    let mut buf = [0u8; 16];
+    let rapdu = se050.process(&inner, None, &mut buf, &mut delay).unwrap();
+    assert_eq!(rapdu.sw, 0x9000);
+    assert_eq!(rapdu.get_tlv(0x41).unwrap().get_data(), &[0x12, 0x34]);
+
+    let mut buf = [0u8; 16];
+    let rapdu = se050.process(&inner, Some(&SessionId(SESSION_ID)), &mut buf, &mut delay).unwrap();
+    assert_eq!(rapdu.sw, 0x6985);
+}
+
+#[test]
+fn test_not_initialized() {
+    let mut delay = test_twi::get_delay_wrapper();
+    // nothing may reach the bus before enable()
+    let t1 = T1overI2C::new(test_twi::TWI::scripted(), 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    assert!(!se050.is_enabled());
+    assert_eq!(se050.generate_p256_key(&[0x20, 0xe8, 0xa1, 0x01], &mut delay).err(), Some(Se050Error::NotInitialized));
+    let mut out = [0u8; 16];
+    assert_eq!(se050.aes_cbc_encrypt(&[0x20, 0xe8, 0xa1, 0x02], &[0u8; 16], &mut out, &mut delay).err(), Some(Se050Error::NotInitialized));
+    assert_eq!(se050.get_data(0x0066, &mut out, &mut delay), Err(Se050Error::NotInitialized));
+    let mut wrapped = [0u8; 24];
+    assert_eq!(se050.wrap_key(ObjectId([0x20, 0xe8, 0xa1, 0x03]), &[0u8; 16], &mut wrapped, &mut delay), Err(Se050Error::NotInitialized));
+
+    let se050 = reset_se050(test_twi::TWI::new(), &mut delay);
+    assert!(se050.is_enabled());
+}
+
+#[test]
+fn test_scripted_commands() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+
+    let obj = [0x20, 0xe8, 0xa1, 0x01];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[0x03]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let data = [0xde, 0xad, 0xbe, 0xef];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0x00));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[0x21]));
+    c.push(SimpleTlv::new(0x43, &data));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x02, 0x30, 0x00, 0x90, 0x00]);
+
+    // borrow the mock through the bus proxy to check the script ran to its end
+    let bus = core::cell::RefCell::new(xtwi);
+    let t1 = T1overI2C::new(SharedI2c::new(&bus), 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    se050.soft_reset(&mut delay).unwrap();
+    assert!(se050.generate_p256_key(&obj, &mut delay).is_ok());
+    let mut sig = [0u8; 72];
+    assert_eq!(se050.ecdsa_sign(&obj, Se050ECSignatureAlgo::SigEcdsaSha256, &data, &mut sig, &mut delay), Ok(2));
+    assert_eq!(&sig[0..2], &[0x30, 0x00]);
+    bus.borrow().assert_done();
+}
+
+#[test]
+fn test_frame_diff() {
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &[0x20, 0x00, 0x00, 0x01]));
+    let expected: heapless::Vec<u8, 254> = c.byte_iter().collect();
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &[0x20, 0x00, 0x00, 0x02]));
+    c.push(SimpleTlv::new(0x42, &[0x03]));
+    let got: heapless::Vec<u8, 254> = c.byte_iter().collect();
+
+    let diff = test_twi::frame_diff(&t1_frame(0x5a, 0x00, &expected), &t1_frame(0x5a, 0x00, &got));
+    assert!(diff.contains("INS: expected 01, got 81"));
+    assert!(diff.contains("TLV[0]: expected tag 41 len 4 [20, 00, 00, 01], got tag 41 len 4 [20, 00, 00, 02]"));
+    assert!(diff.contains("TLV[1]: unexpected tag 42 len 1 [03]"));
+    assert!(!diff.contains("P1"));
+}
+
+#[test]
+#[should_panic(expected = "P2: expected 03, got 00")]
+fn test_scripted_mismatch() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0xe8, 0xa1, 0x01];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x03, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[0x03]));
+    push_command(&mut xtwi, 0x00, &c);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    se050.generate_p256_key(&obj, &mut delay).ok();
+}
+
+#[test]
+fn test_i2c_error_kinds() {
+    fn classify(e: &test_twi::TestError) -> I2cErrorKind {
+        match e {
+        test_twi::TestError::DequeUnderflow => I2cErrorKind::AddressNack,
+        test_twi::TestError::Mismatch => I2cErrorKind::DataNack,
+        test_twi::TestError::BufferOverflow => I2cErrorKind::Overrun,
+        test_twi::TestError::DequeOverflow => I2cErrorKind::Bus,
+        }
+    }
+
+    let mut delay = test_twi::get_delay_wrapper();
+    let capdu = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x00, None);
+
+    // unclassified errors are retried, then reported as Other
+    let mut t1 = T1overI2C::new(test_twi::TWI::new(), 0x48, 0x5a);
+    assert_eq!(t1.send_apdu(&capdu, &mut delay), Err(T1Error::TransmitError(I2cErrorKind::Other)));
+
+    let mut xtwi = test_twi::TWI::new();
+    // would be accepted by a retry, but a NACKed data byte is not retried
+    xtwi.push_in(&[0x00]);
+    xtwi.push_in(&t1_frame(0x5a, 0x00, &[0x80, 0x04, 0x00, 0x00]));
+    xtwi.push_out(&[0u8; 8]);
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    t1.set_i2c_error_kinds(classify, classify);
+    assert_eq!(t1.send_apdu(&capdu, &mut delay), Err(T1Error::TransmitError(I2cErrorKind::DataNack)));
+
+    let mut buf = [0u8; 4];
+    assert_eq!(t1.receive_apdu_raw(&mut buf, &mut delay).err(), Some(T1Error::ReceiveError(I2cErrorKind::Overrun)));
+    // a busy SE050 is polled until the response budget runs out
+    let mut buf = [0u8; 4];
+    assert_eq!(t1.receive_apdu_raw(&mut buf, &mut delay).err(), Some(T1Error::Timeout));
+}
+
+fn push_ephemeral_sign(xtwi: &mut test_twi::TWI, obj: &[u8; 4], sign_response: &[u8], pubkey: Option<&[u8; 65]>) {
+    let mut exist = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    exist.push(SimpleTlv::new(0x41, obj));
+    push_command(xtwi, 0x00, &exist);
+    push_response(xtwi, 0x00, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    // a P-256 key pair in transient memory
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    push_command(xtwi, 0x40, &c);
+    push_response(xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECSignatureAlgo::SigEcdsaSha256 as u8]));
+    c.push(SimpleTlv::new(0x43, &[0xd1; 32]));
+    push_command(xtwi, 0x00, &c);
+    push_response(xtwi, 0x00, sign_response);
+
+    let mut seq = 0x40;
+    if let Some(pubkey) = pubkey {
+        let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+        c.push(SimpleTlv::new(0x41, obj));
+        push_command(xtwi, seq, &c);
+        let mut r: heapless::Vec<u8, 80> = heapless::Vec::new();
+        r.extend_from_slice(&[0x41, 65]).unwrap();
+        r.extend_from_slice(pubkey).unwrap();
+        r.extend_from_slice(&[0x90, 0x00]).unwrap();
+        push_response(xtwi, seq, &r);
+        seq = 0x00;
+    }
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    c.push(SimpleTlv::new(0x41, obj));
+    push_command(xtwi, seq, &c);
+    push_response(xtwi, seq, &[0x90, 0x00]);
+}
+
+#[test]
+fn test_ephemeral_p256_sign() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let obj = [0x7f, 0xfe, 0x00, 0x00];
+    let mut pubkey = [0x5au8; 65];
+    pubkey[0] = 0x04;
+
+    let mut xtwi = test_twi::TWI::scripted();
+    push_ephemeral_sign(&mut xtwi, &obj, &[0x41, 0x04, 0x30, 0x02, 0x05, 0x00, 0x90, 0x00], Some(&pubkey));
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut sig = [0u8; 72];
+    let (len, key) = se050.ephemeral_p256_sign(&[0xd1; 32], &mut sig, &mut delay).unwrap();
+    assert_eq!(&sig[0..len], &[0x30, 0x02, 0x05, 0x00]);
+    assert_eq!(key, pubkey);
+
+    // the key pair is deleted when signing fails
+    let mut xtwi = test_twi::TWI::scripted();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    push_ephemeral_sign(&mut xtwi, &obj, &[0x69, 0x85], None);
+    let bus = core::cell::RefCell::new(xtwi);
+    let mut se050 = Se050::new(T1overI2C::new(SharedI2c::new(&bus), 0x48, 0x5a));
+    se050.soft_reset(&mut delay).unwrap();
+    assert!(se050.ephemeral_p256_sign(&[0xd1; 32], &mut sig, &mut delay).is_err());
+    bus.borrow().assert_done();
+}
+
+#[test]
+fn test_constant_convs() {
+    use core::convert::TryFrom;
+    assert_eq!(u8::from(Se050CipherModeconstants::AesCbcNopad), 0x0d);
+    assert_eq!(Se050CipherModeconstants::try_from(0xf0), Ok(Se050CipherModeconstants::AesCtr));
+    assert_eq!(Se050MACAlgoconstants::try_from(0x19), Ok(Se050MACAlgoconstants::HmacSha256));
+    assert_eq!(Se050DigestModeconstants::try_from(0x06), Ok(Se050DigestModeconstants::DigestSha512));
+    assert_eq!(Se050ECCurveconstants::try_from(0x03), Ok(Se050ECCurveconstants::NistP256));
+    assert_eq!(u8::from(Se050RSASignatureAlgo::RsaSha256Pkcs1Pss), 0x2c);
+    // not a constant of the table
+    assert_eq!(Se050CipherModeconstants::try_from(0x00), Err(Iso7816Error::ValueError));
+    assert_eq!(Se050ECSignatureAlgo::try_from(0xa3), Err(Iso7816Error::ValueError));
+}
+
+#[test]
+fn test_exchange_session_policies() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // at most 16 APDUs, refresh allowed: one policy with both header bits
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1f, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x04, 0xa0, 0x00, 0x00, 0x10]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert!(se050.exchange_session_policies(&[SessionPolicy::MaxApdu(16), SessionPolicy::AllowRefresh], &mut delay).is_ok());
+
+    assert_eq!(crate::se050::encode_session_policy(&[SessionPolicy::AllowRefresh]).unwrap(), [0x02, 0x20, 0x00]);
+    // rejected before anything is sent: an entry given twice
+    assert!(se050.exchange_session_policies(&[SessionPolicy::MaxApdu(16), SessionPolicy::MaxApdu(4)], &mut delay).is_err());
+    assert!(se050.exchange_session_policies(&[SessionPolicy::AllowRefresh, SessionPolicy::AllowRefresh], &mut delay).is_err());
+}
+
+#[test]
+fn test_capabilities() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // ECDSA, EdDSA, HMAC, RSA CRT, AES, PBKDF and I2CM: no DES, no plain RSA, no Mifare
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x20, Some(0));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x07, 0x03, 0x01, 0x01, 0x22, 0xd6, 0x01, 0x0b, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let caps = se050.capabilities(&mut delay).unwrap();
+    assert_eq!(caps, Capabilities {
+        ecdsa: true,
+        eddsa: true,
+        hmac: true,
+        rsa_crt: true,
+        aes: true,
+        pbkdf: true,
+        i2cm: true,
+        ..Default::default()
+    });
+    assert_eq!(se050.app_info().unwrap().capabilities(), caps);
+    assert_eq!(Capabilities::from_features(AppletFeatures::from_bits(0x3fff)), Capabilities {
+        ecdaa: true, ecdsa: true, eddsa: true, dh_mont: true, hmac: true, rsa_plain: true, rsa_crt: true,
+        aes: true, des: true, pbkdf: true, tls: true, mifare: true, i2cm: true,
+    });
+}
+
+#[test]
+fn test_desfire_keys() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let master = [0x20, 0x00, 0x00, 0x80];
+    let diversified = [0x20, 0x00, 0x00, 0x81];
+    let key = [0x5au8; 16];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x03, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &master));
+    c.push(SimpleTlv::new(0x43, &key));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let divinput = [0x01, 0x04, 0x78, 0x2e, 0x21, 0x80, 0x1d, 0x80];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x00, 0x10, None);
+    c.push(SimpleTlv::new(0x41, &master));
+    c.push(SimpleTlv::new(0x42, &diversified));
+    c.push(SimpleTlv::new(0x43, &divinput));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x69, 0x85]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.write_desfire_key(&master, None, &key, &KeyConfig::default(), &mut delay).unwrap().0, master);
+    assert_eq!(se050.write_desfire_key(&master, None, &[0u8; 24], &KeyConfig::default(), &mut delay).err(), Some(Se050Error::UnknownError));
+    assert_eq!(se050.dfdiversifykey(&master, &diversified, &[], &mut delay), Err(Se050Error::UnknownError));
+    let long = [0u8; DESFIRE_MAX_DIV_INPUT_LEN + 1];
+    assert_eq!(se050.dfdiversifykey(&master, &diversified, &long, &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.dfdiversifykey(&master, &diversified, &divinput, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_desfire_unavailable() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+    xtwi.push_in(T1FC_S_INTF_RESET);
+    xtwi.push_out(T1FRH_S_INTF_RESET);
+    xtwi.push_out(T1F_ATR);
+    xtwi.push_in(T1FC_GP_SELECT_SE050);
+    // CONFIG_MIFARE (0x0800) cleared
+    push_response(&mut xtwi, 0x00, &[0x03, 0x01, 0x01, 0x66, 0xff, 0x01, 0x0b, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert!(se050.enable(&mut delay).is_ok());
+    let obj = [0x20, 0x00, 0x00, 0x80];
+    assert_eq!(se050.write_desfire_key(&obj, None, &[0u8; 16], &KeyConfig::default(), &mut delay).err(), Some(Se050Error::MifareUnavailable));
+    assert_eq!(se050.dfdiversifykey(&obj, &obj, &[0x01], &mut delay), Err(Se050Error::MifareUnavailable));
+}
+
+#[test]
+fn test_write_user_id() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0x90];
+    let policy = [0x08, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x08, 0x00];
+    let value = [0x31u8; 16];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x07, 0x00, None);
+    c.push(SimpleTlv::new(0x11, &policy));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &value));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0xc1, 0x07, 0x00, None);
+    c.push(SimpleTlv::new(0x11, &policy));
+    c.push(SimpleTlv::new(0x12, &[0x00, 0x03]));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &value));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.write_user_id(&policy, &obj, &value, None, &mut delay), Ok(UserIdObject { id: ObjectId(obj), max_attempts: None }));
+    let user = se050.write_user_id(&policy, &obj, &value, Some(3), &mut delay).unwrap();
+    assert_eq!(user.id.0, obj);
+    assert_eq!(user.max_attempts, Some(3));
+}
+
+#[test]
+fn test_verify_pin() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x7f, 0xff, 0x02, 0x00];
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1b, Some(0x0c));
+    create.push(SimpleTlv::new(0x41, &obj));
+    let mut verify = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x2c, None);
+    verify.push(SimpleTlv::new(0x41, b"123456"));
+    let close = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1c, None);
+
+    let mut seq = 0x00;
+    for sw in [[0x90, 0x00], [0x63, 0xc2], [0x69, 0x83], [0x6a, 0x80]] {
+        push_command(&mut xtwi, seq, &create);
+        push_response(&mut xtwi, seq, &[0x41, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x90, 0x00]);
+        seq ^= 0x40;
+        push_session_command(&mut xtwi, seq, &verify);
+        push_response(&mut xtwi, seq, &sw);
+        seq ^= 0x40;
+        push_session_command(&mut xtwi, seq, &close);
+        push_response(&mut xtwi, seq, &[0x90, 0x00]);
+        seq ^= 0x40;
+    }
+
+    assert_eq!(PinResult::from_sw(0x63c0), Some(PinResult::Locked));
+    assert_eq!(PinResult::from_sw(0x6985), None);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.verify_pin(&obj, b"12", &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.verify_pin(&obj, b"123456", &mut delay), Ok(PinResult::Ok));
+    assert_eq!(se050.session_state(), SessionState::None);
+    assert_eq!(se050.verify_pin(&obj, b"123456", &mut delay), Ok(PinResult::WrongPin { attempts_left: 2 }));
+    assert_eq!(se050.verify_pin(&obj, b"123456", &mut delay), Ok(PinResult::Locked));
+    assert_eq!(se050.verify_pin(&obj, b"123456", &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_export_restore_object() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0xa0];
+    let mut attributes = [0u8; 20];
+    attributes[0..4].copy_from_slice(&obj);
+    attributes[4] = Se050ApduSecObjType::ECKeyPair as u8;
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x3b, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut rapdu: heapless::Vec<u8, 32> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x42, attributes.len() as u8]).unwrap();
+    rapdu.extend_from_slice(&attributes).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    let blob = [0xc3u8; 40];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x19, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x40, &c);
+    let mut rapdu: heapless::Vec<u8, 64> = heapless::Vec::new();
+    rapdu.extend_from_slice(&[0x41, blob.len() as u8]).unwrap();
+    rapdu.extend_from_slice(&blob).unwrap();
+    rapdu.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(&mut xtwi, 0x40, &rapdu);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x00, 0x18, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x43, &blob));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out = [0u8; 64];
+    let exported = se050.export_object(&obj, None, &mut out, &mut delay).unwrap();
+    assert_eq!(exported.id, ObjectId(obj));
+    assert_eq!(exported.object_type, Se050ApduSecObjType::ECKeyPair);
+    assert_eq!(exported.rsa_component, None);
+    assert_eq!(exported.blob, &blob);
+    let exported = ExportedObject { blob: &blob, ..exported };
+    assert!(se050.restore_object(&exported, &mut delay).is_ok());
+}
+
+static FAKE_CLOCK_MS: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(u32::MAX - 1000);
+
+// advances by one second per reading, starting just before the wraparound
+struct FakeClock;
+
+impl Clock for FakeClock {
+    fn now_ms(&mut self) -> u32 {
+        FAKE_CLOCK_MS.fetch_add(1000, core::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn test_receive_timeout_with_clock() {
+    let clock: &'static mut FakeClock = std::boxed::Box::leak(std::boxed::Box::new(FakeClock));
+    let mut delay = test_twi::get_delay_wrapper().with_clock(clock);
+    // the response never arrives
+    let mut t1 = T1overI2C::new(test_twi::TWI::new(), 0x48, 0x5a);
+    t1.set_timeout_ms(10_000);
+
+    let start = FAKE_CLOCK_MS.load(core::sync::atomic::Ordering::SeqCst);
+    let mut buf = [0u8; 16];
+    assert_eq!(t1.receive_apdu_raw(&mut buf, &mut delay).err(), Some(T1Error::Timeout));
+    // one reading to start the budget and one per failed read until it is spent,
+    // rather than 5000 retries of TWI_RETRY_DELAY_MS
+    let readings = FAKE_CLOCK_MS.load(core::sync::atomic::Ordering::SeqCst).wrapping_sub(start) / 1000;
+    assert_eq!(readings, 11);
+}
+
+#[test]
+fn test_generate_ec_key_transient() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let obj = [0x20, 0x00, 0x00, 0xb0];
+    let mut persistent = CApdu::new(ApduClass::ProprietaryPlain, 0x01, 0x61, 0x00, None);
+    persistent.push(SimpleTlv::new(0x41, &obj));
+    persistent.push(SimpleTlv::new(0x42, &[0x03]));
+    let mut transient = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x61, 0x00, None);
+    transient.push(SimpleTlv::new(0x41, &obj));
+    transient.push(SimpleTlv::new(0x42, &[0x03]));
+
+    push_command(&mut xtwi, 0x00, &persistent);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &transient);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+    // transient memory exhausted
+    push_command(&mut xtwi, 0x00, &transient);
+    push_response(&mut xtwi, 0x00, &[0x6a, 0x84]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let curve = Se050ECCurveconstants::NistP256;
+    let config = KeyConfig { transient: true, ..Default::default() };
+    assert_eq!(se050.generate_ec_key_with_config(&obj, curve, &KeyConfig::default(), &mut delay), Ok(ObjectId(obj)));
+    assert_eq!(se050.generate_ec_key_with_config(&obj, curve, &config, &mut delay), Ok(ObjectId(obj)));
+    assert_eq!(se050.generate_ec_key_with_config(&obj, curve, &config, &mut delay), Err(Se050Error::TransientUnavailable));
+}
+
+#[test]
+fn test_session_scratch() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::new();
+
+    let mut create = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x1b, Some(0x0c));
+    create.push(SimpleTlv::new(0x41, &[0x7f, 0xff, 0x02, 0x00]));
+    push_command(&mut xtwi, 0x00, &create);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x08, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x90, 0x00]);
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x04]));
+    push_session_command(&mut xtwi, 0x40, &random);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x04, 0xde, 0xad, 0xbe, 0xef, 0x90, 0x00]);
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x20]));
+    push_session_command(&mut xtwi, 0x00, &random);
+    let mut rapdu = [0u8; 36];
+    rapdu[0..2].copy_from_slice(&[0x41, 0x20]);
+    rapdu[34..36].copy_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &rapdu);
+
+    let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    let mut se050 = Se050::new(t1);
+    let mut session = se050.open_session_with_scratch::<16>(&[0x7f, 0xff, 0x02, 0x00], &mut delay).unwrap();
+    let mut buf = [0u8; 4];
+    assert!(session.get_random(&mut buf).is_ok());
+    assert_eq!(buf, [0xde, 0xad, 0xbe, 0xef]);
+    // 32 random bytes don't fit the 16-byte buffer
+    let mut buf = [0u8; 32];
+    assert!(session.get_random(&mut buf).is_err());
+}
+
+#[test]
+fn test_seed_rng() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // one GetRandom of exactly 32 bytes
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x20]));
+    let seed: std::vec::Vec<u8> = (0..32u8).map(|i| i ^ 0xa5).collect();
+    push_command(&mut xtwi, 0x00, &random);
+    let mut rapdu = std::vec::Vec::from([0x41, 0x20]);
+    rapdu.extend_from_slice(&seed);
+    rapdu.extend_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &rapdu);
+    // then a failing one, and one answering a short value
+    push_command(&mut xtwi, 0x40, &random);
+    push_response(&mut xtwi, 0x40, &[0x69, 0x85]);
+    push_command(&mut xtwi, 0x00, &random);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut buf = [0u8; 32];
+    assert!(se050.seed_rng(&mut buf, &mut delay).is_ok());
+    assert_eq!(buf.as_slice(), seed.as_slice());
+
+    // on error the previous seed is left untouched
+    assert_eq!(se050.seed_rng(&mut buf, &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.seed_rng(&mut buf, &mut delay), Err(Se050Error::MissingResponseTlv));
+    assert_eq!(buf.as_slice(), seed.as_slice());
+}
+
+#[test]
+fn test_missing_response_tlv() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x02]));
+    // data under TAG_2 instead of TAG_1, then a TAG_1 of the wrong length
+    push_command(&mut xtwi, 0x00, &random);
+    push_response(&mut xtwi, 0x00, &[0x42, 0x02, 0x12, 0x34, 0x90, 0x00]);
+    push_command(&mut xtwi, 0x40, &random);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x03, 0x12, 0x34, 0x56, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut buf = [0u8; 2];
+    assert_eq!(se050.get_random(&mut buf, &mut delay), Err(Se050Error::MissingResponseTlv));
+    assert_eq!(se050.get_random(&mut buf, &mut delay), Err(Se050Error::MissingResponseTlv));
+    assert_eq!(buf, [0, 0]);
+}
+
+#[test]
+fn test_get_oef() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let get_data = [0x80, 0xca, 0x00, 0xfe, 0x02, 0xdf, 0x28, 0x00];
+    xtwi.push_in(&t1_frame(0x5a, 0x00, &get_data));
+    push_response(&mut xtwi, 0x00, &[0xfe, 0x09, 0xdf, 0x28, 0x06, 0x00, 0x01, 0xa2, 0x01, 0x00, 0x00, 0x90, 0x00]);
+    // DF28 shorter than its length claims
+    xtwi.push_in(&t1_frame(0x5a, 0x40, &get_data));
+    push_response(&mut xtwi, 0x40, &[0xfe, 0x05, 0xdf, 0x28, 0x06, 0x00, 0x01, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let oef = se050.get_oef(&mut delay).unwrap();
+    assert_eq!(oef, 0xa201);
+    assert_eq!(se050.get_oef(&mut delay), Err(Se050Error::MissingResponseTlv));
+}
+
+#[test]
+fn test_frame_recorder() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x20, Some(0));
+    for pcb in [0x00, 0x40, 0x00] {
+        push_command(&mut xtwi, pcb, &c);
+        push_response(&mut xtwi, pcb, &[0x90, 0x00]);
+    }
+
+    let mut t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+    assert_eq!(t1.drain_recording(&mut [0u8; 8]), 0);
+    // room for one command (3 + 10) and one response (3 + 7) record
+    let frames: &'static mut [u8] = std::boxed::Box::leak(std::boxed::Box::new([0u8; 23]));
+    assert!(t1.set_recorder(FrameRecorder::new(frames)).is_none());
+
+    let mut buf = [0u8; 16];
+    // not recorded before start_recording
+    t1.send_apdu(&c, &mut delay).unwrap();
+    t1.receive_apdu_raw(&mut buf, &mut delay).unwrap();
+    t1.start_recording();
+    t1.send_apdu(&c, &mut delay).unwrap();
+    t1.receive_apdu_raw(&mut buf, &mut delay).unwrap();
+    t1.send_apdu(&c, &mut delay).unwrap();
+    t1.receive_apdu_raw(&mut buf, &mut delay).unwrap();
+    t1.stop_recording();
+
+    let command: heapless::Vec<u8, 16> = c.byte_iter().collect();
+    let sent = t1_frame(0x5a, 0x00, &command);
+    let received = t1_frame(0xa5, 0x00, &[0x90, 0x00]);
+    let mut out = [0u8; 64];
+    // the buffer holds one exchange, so the first recorded one was dropped
+    let len = t1.drain_recording(&mut out);
+    assert_eq!(len, 23);
+    assert_eq!(&out[0..3], &[FRAME_SENT, 0x00, sent.len() as u8]);
+    assert_eq!(&out[3..13], sent.as_slice());
+    assert_eq!(&out[13..16], &[FRAME_RECEIVED, 0x00, received.len() as u8]);
+    assert_eq!(&out[16..23], received.as_slice());
+    assert_eq!(t1.drain_recording(&mut out), 0);
+    assert_eq!(t1.take_recorder().unwrap().dropped(), 2);
+}
+
+fn push_cbc_oneshot(xtwi: &mut test_twi::TWI, pcb: u8, p2: u8, key: &[u8; 4], iv: &[u8], input: &[u8], output: &[u8]) {
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0e, p2, Some(0));
+    c.push(SimpleTlv::new(0x41, key));
+    c.push(SimpleTlv::new(0x42, &[Se050CipherModeconstants::AesCbcNopad as u8]));
+    c.push(SimpleTlv::new(0x43, input));
+    c.push(SimpleTlv::new(0x44, iv));
+    push_command(xtwi, pcb, &c);
+
+    let mut r: heapless::Vec<u8, 256> = heapless::Vec::new();
+    r.extend_from_slice(&[0x41, 0x81, output.len() as u8]).unwrap();
+    r.extend_from_slice(output).unwrap();
+    r.extend_from_slice(&[0x90, 0x00]).unwrap();
+    push_response(xtwi, pcb, &r);
+}
+
+#[test]
+fn test_aes_cbc_random_iv() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let key = [0x20, 0x00, 0x00, 0xc0];
+    let iv = [0xa5u8; 16];
+    let mut plaintext = [0u8; 224];
+    for (i, b) in plaintext.iter_mut().enumerate() {
+        *b = i as u8;
+    }
+    // the card's "ciphertext" is the plaintext with every byte inverted
+    let mut ciphertext = plaintext;
+    for b in ciphertext.iter_mut() {
+        *b = !*b;
+    }
+
+    let mut random = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    random.push(SimpleTlv::new(0x41, &[0x00, 0x10]));
+    push_command(&mut xtwi, 0x00, &random);
+    let mut r = [0u8; 20];
+    r[0..2].copy_from_slice(&[0x41, 0x10]);
+    r[2..18].copy_from_slice(&iv);
+    r[18..20].copy_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &r);
+    // 208 bytes fit in one command, the second is chained to the last ciphertext block
+    push_cbc_oneshot(&mut xtwi, 0x40, 0x42, &key, &iv, &plaintext[..208], &ciphertext[..208]);
+    push_cbc_oneshot(&mut xtwi, 0x00, 0x42, &key, &ciphertext[192..208], &plaintext[208..], &ciphertext[208..]);
+
+    push_cbc_oneshot(&mut xtwi, 0x40, 0x43, &key, &iv, &ciphertext[..208], &plaintext[..208]);
+    push_cbc_oneshot(&mut xtwi, 0x00, 0x43, &key, &ciphertext[192..208], &ciphertext[208..], &plaintext[208..]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out = [0u8; 224];
+    assert_eq!(se050.aes_cbc_encrypt(&key, &plaintext[..15], &mut out, &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.aes_cbc_encrypt(&key, &plaintext, &mut out[..208], &mut delay), Err(Se050Error::UnknownError));
+    assert_eq!(se050.aes_cbc_encrypt(&key, &plaintext, &mut out, &mut delay), Ok((iv, 224)));
+    assert_eq!(&out[..], &ciphertext[..]);
+
+    let mut decrypted = [0u8; 224];
+    assert_eq!(se050.aes_cbc_decrypt(&key, &iv, &ciphertext, &mut decrypted, &mut delay), Ok(224));
+    assert_eq!(&decrypted[..], &plaintext[..]);
+}
+
+#[test]
+fn test_encrypt_stream() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    let key = [0x20, 0x00, 0x00, 0xc1];
+    let iv = [0x5au8; 16];
+    let mut plaintext = [0u8; 272];
+    for (i, b) in plaintext.iter_mut().enumerate() {
+        *b = (i * 3) as u8;
+    }
+    let mut ciphertext = plaintext;
+    for b in ciphertext.iter_mut() {
+        *b = !*b;
+    }
+
+    // the pieces are re-blocked into one full command and the 64 bytes left over
+    push_cbc_oneshot(&mut xtwi, 0x00, 0x42, &key, &iv, &plaintext[..208], &ciphertext[..208]);
+    push_cbc_oneshot(&mut xtwi, 0x40, 0x42, &key, &ciphertext[192..208], &plaintext[208..], &ciphertext[208..]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out: heapless::Vec<u8, 272> = heapless::Vec::new();
+    let mut calls = 0;
+    let pieces = [&plaintext[..5], &plaintext[5..255], &plaintext[255..]];
+    let sink = |c: &[u8]| { out.extend_from_slice(c).unwrap(); calls += 1; };
+    assert_eq!(se050.encrypt_stream(&key, Se050CipherModeconstants::AesCbcNopad, &iv, pieces.into_iter(), sink, &mut delay), Ok(272));
+    assert_eq!(calls, 2);
+    assert_eq!(&out[..], &ciphertext[..]);
+
+    // padding modes cannot be chained, and CBC needs whole blocks
+    let pieces = [&plaintext[..16]];
+    assert_eq!(se050.encrypt_stream(&key, Se050CipherModeconstants::AesCbcPkcs5, &iv, pieces.into_iter(), |_| {}, &mut delay), Err(Se050Error::UnknownError));
+    let pieces = [&plaintext[..8], &plaintext[8..15]];
+    assert_eq!(se050.encrypt_stream(&key, Se050CipherModeconstants::AesCbcNopad, &iv, pieces.into_iter(), |_| {}, &mut delay), Err(Se050Error::UnknownError));
+}
+
+#[test]
+fn test_extended_le() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    assert_eq!(crate::se050::tlv_response_len(100), 102);
+    assert_eq!(crate::se050::tlv_response_len(253), 256);
+    assert_eq!(crate::se050::tlv_response_len(600), 604);
+    assert_eq!(crate::se050::response_le(256), Some(0));
+    assert_eq!(crate::se050::response_le(604), Some(604));
+    assert_eq!(crate::se050::response_le(70000), Some(1020));
+
+    // a 600-byte buffer asks for the whole TLV with an extended Le
+    let obj = [0x20, 0x00, 0x00, 0xd0];
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(604));
+    c.push(SimpleTlv::new(0x41, &obj));
+    let command: heapless::Vec<u8, 16> = c.byte_iter().collect();
+    assert_eq!(&command[..], &[0x80, 0x02, 0x00, 0x00, 0x00, 0x00, 0x06, 0x41, 0x04, 0x20, 0x00, 0x00, 0xd0, 0x02, 0x5c]);
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x02, 0x00, 0x00, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x02, 0x12, 0x34, 0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let mut out = [0u8; 600];
+    assert_eq!(se050.read_object_into(&obj, &mut out, &mut delay), Ok(2));
+    assert_eq!(se050.read_object_into(&obj, &mut out[..65], &mut delay), Ok(2));
+    assert_eq!(&out[0..2], &[0x12, 0x34]);
+}
+
+static BENCH_CLOCK_MS: core::sync::atomic::AtomicU32 = core::sync::atomic::AtomicU32::new(0);
+
+// advances by one millisecond per reading
+struct BenchClock;
+
+impl Clock for BenchClock {
+    fn now_ms(&mut self) -> u32 {
+        BENCH_CLOCK_MS.fetch_add(1, core::sync::atomic::Ordering::SeqCst)
+    }
+}
+
+#[test]
+fn test_benchmark() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+    let obj = [0x7f, 0xfe, 0x00, 0x00];
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x49, Some(0));
+    c.push(SimpleTlv::new(0x41, &[0x00, 0x20]));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut r = [0x11u8; 36];
+    r[0..2].copy_from_slice(&[0x41, 0x20]);
+    r[34..36].copy_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &r);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x27, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x01, 0x02, 0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x61, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECCurveconstants::NistP256 as u8]));
+    push_command(&mut xtwi, 0x00, &c);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0c, 0x09, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050ECSignatureAlgo::SigEcdsaSha256 as u8]));
+    c.push(SimpleTlv::new(0x43, &[0x11; 32]));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x41, 0x04, 0x30, 0x02, 0x05, 0x00, 0x90, 0x00]);
+    let mut delete = CApdu::new(ApduClass::ProprietaryPlain, 0x04, 0x00, 0x28, None);
+    delete.push(SimpleTlv::new(0x41, &obj));
+    push_command(&mut xtwi, 0x00, &delete);
+    push_response(&mut xtwi, 0x00, &[0x90, 0x00]);
+
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x81, 0x03, 0x00, None);
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x43, &[0x11; 16]));
+    push_command(&mut xtwi, 0x40, &c);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+    let mut c = CApdu::new(ApduClass::ProprietaryPlain, 0x83, 0x0e, 0x42, Some(0));
+    c.push(SimpleTlv::new(0x41, &obj));
+    c.push(SimpleTlv::new(0x42, &[Se050CipherModeconstants::AesEcbNopad as u8]));
+    c.push(SimpleTlv::new(0x43, &[0; BENCH_AES_LEN]));
+    push_command(&mut xtwi, 0x00, &c);
+    let mut r = [0x22u8; BENCH_AES_LEN + 4];
+    r[0..2].copy_from_slice(&[0x41, BENCH_AES_LEN as u8]);
+    r[BENCH_AES_LEN + 2..].copy_from_slice(&[0x90, 0x00]);
+    push_response(&mut xtwi, 0x00, &r);
+    push_command(&mut xtwi, 0x40, &delete);
+    push_response(&mut xtwi, 0x40, &[0x90, 0x00]);
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    assert_eq!(se050.benchmark(&mut delay), Err(Se050Error::ClockUnavailable));
+
+    let clock: &'static mut BenchClock = std::boxed::Box::leak(std::boxed::Box::new(BenchClock));
+    let mut delay = delay.with_clock(clock);
+    // the start and end readings and the one starting the receive timeout
+    let report = BenchReport { get_random_ms: 2, p256_sign_ms: 2, aes_encrypt_ms: 2 };
+    assert_eq!(se050.benchmark(&mut delay), Ok(report));
+}
+
+#[test]
+fn test_strict_mode() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let lifecycle = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x5b, Some(0));
+
+    // CONFIG_FIPS_MODE_DISABLED (0x1000) set and unlocked, then FIPS mode and locked
+    for (features, lock, failures) in [
+        (0x7fff_u16, 0x02, StrictFailures { fips_disabled: true, transport_unlocked: true }),
+        (0x6fff, 0x01, StrictFailures::default()),
+    ] {
+        let mut xtwi = test_twi::TWI::new();
+        xtwi.push_in(T1FC_S_INTF_RESET);
+        xtwi.push_out(T1FRH_S_INTF_RESET);
+        xtwi.push_out(T1F_ATR);
+        xtwi.push_in(T1FC_GP_SELECT_SE050);
+        let [f1, f2] = features.to_be_bytes();
+        push_response(&mut xtwi, 0x00, &[0x03, 0x01, 0x01, f1, f2, 0x01, 0x0b, 0x90, 0x00]);
+        push_command(&mut xtwi, 0x40, &lifecycle);
+        push_response(&mut xtwi, 0x40, &[0x41, 0x03, lock, 0x00, 0x02, 0x90, 0x00]);
+        let t1 = T1overI2C::new(xtwi, 0x48, 0x5a);
+        let mut se050 = Se050::new(t1);
+        se050.set_strict(true);
+
+        if failures.any() {
+            assert_eq!(se050.enable(&mut delay), Err(Se050Error::PolicyNotMet(failures)));
+            assert!(!se050.is_enabled());
+        } else {
+            assert_eq!(se050.enable(&mut delay), Ok(SelectStatus::Ok));
+            assert!(se050.is_enabled());
+        }
+    }
+}
+
+#[test]
+fn test_applet_features() {
+    // all groups except DES, FIPS mode on
+    let features = AppletFeatures::from_bits(0x6eff);
+    assert!(features.contains(AppletFeatures::RSA_CRT));
+    assert!(features.contains(AppletFeatures::ECC_ALL | AppletFeatures::AES));
+    assert!(!features.contains(AppletFeatures::DES));
+    assert!(!features.contains(AppletFeatures::ALL));
+    assert!(features.intersects(AppletFeatures::DES | AppletFeatures::MIFARE));
+    assert!(!features.intersects(AppletFeatures::DES | AppletFeatures::FIPS_MODE_DISABLED));
+    assert_eq!((features & AppletFeatures::RSA_ALL).bits(), 0x0060);
+    assert!(AppletFeatures::empty().is_empty());
+    assert!(features.contains(AppletFeatures::empty()));
+}
+
+#[test]
+fn test_rsa_key_size_range() {
+    let mut delay = test_twi::get_delay_wrapper();
+    let mut xtwi = test_twi::TWI::scripted();
+
+    // RSA CRT in FIPS mode, then no RSA, then everything without FIPS mode
+    let c = CApdu::new(ApduClass::ProprietaryPlain, 0x84, 0x00, 0x20, Some(0));
+    let mut pcb = 0x00;
+    for [f1, f2] in [[0x22, 0xd6], [0x3f, 0x9f], [0x3f, 0xff]] {
+        push_command(&mut xtwi, pcb, &c);
+        push_response(&mut xtwi, pcb, &[0x41, 0x07, 0x03, 0x01, 0x01, f1, f2, 0x01, 0x0b, 0x90, 0x00]);
+        pcb ^= 0x40;
+    }
+
+    let mut se050 = reset_se050(xtwi, &mut delay);
+    let obj = [0x20, 0x00, 0x00, 0xe0];
+    assert_eq!(se050.rsa_key_size_range(&mut delay), Ok((RSA_KEY_SIZE_MIN_FIPS, RSA_KEY_SIZE_MAX)));
+    // rejected before the key generation is sent
+    assert_eq!(se050.write_rsa_key(&[], &obj, &1024u16.to_be_bytes(), &mut delay), Err(Se050Error::RsaKeySizeUnsupported));
+    assert_eq!(se050.write_rsa_key(&[], &obj, &8192u16.to_be_bytes(), &mut delay), Err(Se050Error::RsaKeySizeUnsupported));
+
+    assert_eq!(se050.rsa_key_size_range(&mut delay), Err(Se050Error::RsaUnavailable));
+    assert_eq!(se050.write_rsa_key(&[], &obj, &2048u16.to_be_bytes(), &mut delay), Err(Se050Error::RsaUnavailable));
+
+    assert_eq!(se050.rsa_key_size_range(&mut delay), Ok((512, 4096)));
+}
@@ -0,0 +1,59 @@
+use crate::types::*;
+use heapless::{Deque, Vec};
+
+type Apdu = Vec<u8, 300>;
+
+// T1Proto at APDU level, with no link layer underneath: commands are checked
+// against a script and answered with canned responses (data and SW). It is the
+// minimal third-party transport, and shows Se050 needs nothing beyond T1Proto.
+pub struct ApduTransport {
+    pub atr: Apdu,
+    pub expected: Deque<Apdu, 16>,
+    pub responses: Deque<Apdu, 16>,
+}
+
+impl ApduTransport {
+    pub fn new(atr: &[u8]) -> Self {
+        Self { atr: Vec::from_slice(atr).unwrap(), expected: Deque::new(), responses: Deque::new() }
+    }
+
+    pub fn push(&mut self, command: &[u8], response: &[u8]) {
+        self.expected.push_back(Vec::from_slice(command).unwrap()).unwrap();
+        self.responses.push_back(Vec::from_slice(response).unwrap()).unwrap();
+    }
+
+    fn check(&mut self, mut bytes: CApduByteIterator) -> Result<(), T1Error> {
+        let got: Apdu = bytes.by_ref().collect();
+        let expected = self.expected.pop_front().ok_or(T1Error::TransmitError(I2cErrorKind::Other))?;
+        assert_eq!(got, expected, "unexpected command APDU");
+        Ok(())
+    }
+}
+
+impl T1Proto for ApduTransport {
+    fn send_apdu(&mut self, apdu: &CApdu, _delay: &mut DelayWrapper) -> Result<(), T1Error> {
+        self.check(apdu.byte_iter())
+    }
+
+    fn send_apdu_raw(&mut self, apdu: &RawCApdu, _delay: &mut DelayWrapper) -> Result<(), T1Error> {
+        self.check(apdu.byte_iter())
+    }
+
+    fn receive_apdu_raw<'a>(&mut self, buf: &'a mut [u8], _delay: &mut DelayWrapper) -> Result<RawRApdu<'a>, T1Error> {
+        let response = self.responses.pop_front().ok_or(T1Error::ReceiveError(I2cErrorKind::Other))?;
+        let len = response.len();
+        if len < 2 { return Err(T1Error::ProtocolError); }
+        if len > buf.len() { return Err(T1Error::BufferOverrunError(len)); }
+        buf[0..len].copy_from_slice(&response);
+        let sw = ((buf[len - 2] as u16) << 8) | buf[len - 1] as u16;
+        Ok(RawRApdu { sw, data: &buf[0..len - 2] })
+    }
+
+    fn receive_apdu<'a>(&mut self, buf: &'a mut [u8], delay: &mut DelayWrapper) -> Result<RApdu<'a>, T1Error> {
+        self.receive_apdu_raw(buf, delay)?.parse_tlvs(false)
+    }
+
+    fn interface_soft_reset(&mut self, _delay: &mut DelayWrapper) -> Result<AnswerToReset, T1Error> {
+        AnswerToReset::parse(&self.atr)
+    }
+}
@@ -2,17 +2,28 @@ use heapless::{Deque, Vec};
 
 extern crate std;
 
+use std::format;
+use std::string::String;
+
 type Msg = Vec<u8, 261>;
 
 pub struct TWI {
-    pub next_in: Deque<Msg, 32>,
-    pub next_out: Deque<Msg, 32>,
-    pub seen_in: Deque<Msg, 32>,
+    pub next_in: Deque<Msg, 64>,
+    pub next_out: Deque<Msg, 64>,
+    pub seen_in: Deque<Msg, 64>,
+    // panic with a field-level diff on the first unexpected write
+    pub strict: bool,
 }
 
 impl TWI {
     pub fn new() -> Self {
-        Self { next_in: Deque::new(), next_out: Deque::new(), seen_in: Deque::new() }
+        Self { next_in: Deque::new(), next_out: Deque::new(), seen_in: Deque::new(), strict: false }
+    }
+
+    // replays a script of expected commands and canned responses;
+    // any write that does not match the next expected frame fails the test
+    pub fn scripted() -> Self {
+        Self { strict: true, ..Self::new() }
     }
 
     pub fn push_in(&mut self, slice: &[u8]) {
@@ -24,6 +35,96 @@ impl TWI {
         let vec = Vec::from_slice(slice).unwrap();
         self.next_out.push_back(vec).unwrap();
     }
+
+    // every scripted frame was written and every response read
+    pub fn assert_done(&self) {
+        assert!(self.next_in.is_empty(), "{} expected write(s) not seen, next: {:02x?}",
+            self.next_in.len(), self.next_in.iter().next().map(|m| m.as_slice()));
+        assert!(self.next_out.is_empty(), "{} response(s) not read", self.next_out.len());
+    }
+}
+
+//////////////////////////////////////////////////////////////////////////////
+// Decodes a T=1 frame into labelled fields: the block prologue and, for an
+// unchained I-block, CLA/INS/P1/P2, Lc (short or extended), each TLV and Le.
+
+fn t1_fields(frame: &[u8]) -> std::vec::Vec<(String, String)> {
+    let mut fields = std::vec::Vec::new();
+    if frame.len() < 5 {
+        fields.push((String::from("frame"), format!("{:02x?}", frame)));
+        return fields;
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    fields.push((String::from("NAD"), format!("{:02x}", body[0])));
+    fields.push((String::from("PCB"), format!("{:02x}", body[1])));
+    fields.push((String::from("LEN"), format!("{:02x}", body[2])));
+    let inf = &body[3..];
+    if body[1] & 0xa0 == 0 && inf.len() >= 4 {
+        apdu_fields(inf, &mut fields);
+    } else {
+        fields.push((String::from("INF"), format!("{:02x?}", inf)));
+    }
+    fields.push((String::from("CRC"), format!("{:02x?}", crc)));
+    fields
+}
+
+fn apdu_fields(apdu: &[u8], fields: &mut std::vec::Vec<(String, String)>) {
+    for (name, b) in ["CLA", "INS", "P1", "P2"].iter().zip(apdu) {
+        fields.push((String::from(*name), format!("{:02x}", b)));
+    }
+    let rest = &apdu[4..];
+    if rest.len() <= 1 {
+        if let Some(le) = rest.first() {
+            fields.push((String::from("Le"), format!("{:02x}", le)));
+        }
+        return;
+    }
+    let (lc, hdr) = if rest[0] == 0 && rest.len() > 3 {
+        (((rest[1] as usize) << 8) | rest[2] as usize, 3)
+    } else {
+        (rest[0] as usize, 1)
+    };
+    fields.push((String::from("Lc"), format!("{:02x?}", &rest[..hdr])));
+    let end = core::cmp::min(rest.len(), hdr + lc);
+    let mut data = &rest[hdr..end];
+    let mut i = 0;
+    while !data.is_empty() {
+        let (len, off) = match data.get(1) {
+            Some(0x81) if data.len() > 2 => (data[2] as usize, 3),
+            Some(0x82) if data.len() > 3 => (((data[2] as usize) << 8) | data[3] as usize, 4),
+            Some(&l) if l < 0x80 => (l as usize, 2),
+            _ => (usize::MAX, 0),
+        };
+        if off == 0 || data.len() < off + len {
+            fields.push((format!("TLV[{}]", i), format!("malformed {:02x?}", data)));
+            break;
+        }
+        fields.push((format!("TLV[{}]", i), format!("tag {:02x} len {} {:02x?}", data[0], len, &data[off..off + len])));
+        data = &data[off + len..];
+        i += 1;
+    }
+    if rest.len() > end {
+        fields.push((String::from("Le"), format!("{:02x?}", &rest[end..])));
+    }
+}
+
+pub fn frame_diff(expected: &[u8], got: &[u8]) -> String {
+    let expected = t1_fields(expected);
+    let got = t1_fields(got);
+    let mut diff = String::new();
+    for (name, value) in expected.iter() {
+        match got.iter().find(|(n, _)| n == name) {
+            Some((_, v)) if v == value => {}
+            Some((_, v)) => diff += &format!("  {}: expected {}, got {}\n", name, value, v),
+            None => diff += &format!("  {}: expected {}, missing\n", name, value),
+        }
+    }
+    for (name, value) in got.iter() {
+        if !expected.iter().any(|(n, _)| n == name) {
+            diff += &format!("  {}: unexpected {}\n", name, value);
+        }
+    }
+    diff
 }
 
 pub enum TestError {
@@ -37,7 +138,11 @@ impl embedded_hal::blocking::i2c::Read for TWI {
     type Error = TestError;
 
     fn read(&mut self, _addr: u8, buf: &mut [u8]) -> Result<(), Self::Error> {
+        let strict = self.strict;
         let output = self.next_out.pop_front().ok_or_else(|| {
+            if strict {
+                panic!("READ: Empty");
+            }
             std::println!("READ: Empty");
             TestError::DequeUnderflow })?;
 
@@ -54,12 +159,20 @@ impl embedded_hal::blocking::i2c::Write for TWI {
     type Error = TestError;
 
     fn write(&mut self, _addr: u8, buf: &[u8]) -> Result<(), Self::Error> {
+        let strict = self.strict;
         let expected = self.next_in.pop_front().ok_or_else(|| {
+            if strict {
+                panic!("WRITE: Empty, unexpected {:02x?}", buf);
+            }
             std::println!("WRITE: Empty");
             TestError::DequeUnderflow })?;
 
         if expected.as_slice() != buf {
-            std::println!("WRITE: Expectation Mismatch ({:?} != {:?})", expected.as_slice(), buf);
+            let diff = frame_diff(expected.as_slice(), buf);
+            if self.strict {
+                panic!("WRITE: Expectation Mismatch (write {})\n{}", self.seen_in.len(), diff);
+            }
+            std::println!("WRITE: Expectation Mismatch ({:?} != {:?})\n{}", expected.as_slice(), buf, diff);
             return Err(TestError::Mismatch);
         }
         self.seen_in.push_back(expected).map_err(|_| {
@@ -80,5 +193,5 @@ impl embedded_hal::blocking::delay::DelayMs<u32> for DummyDelay {
 }
 
 pub fn get_delay_wrapper() -> crate::types::DelayWrapper {
-    crate::types::DelayWrapper { inner: unsafe { GLOBAL_DUMMY_DELAY.as_mut().unwrap() } }
+    crate::types::DelayWrapper::new(unsafe { GLOBAL_DUMMY_DELAY.as_mut().unwrap() })
 }
@@ -0,0 +1,95 @@
+use crate::types::*;
+use crate::se050::*;
+
+//////////////////////////////////////////////////////////////////////////////
+// Guard for a temporary Secure Object (e.g. an imported public key or an
+// ephemeral key pair): the object is deleted with DeleteSecureObject when the
+// guard is dropped, so early returns on error don't strand it on the device.
+// Use keep() to retain the object, or delete() to observe the result.
+
+// first identifier probed for the key pair of ephemeral_p256_sign
+pub const EPHEMERAL_OBJECT_ID_START: u32 = 0x7ffe_0000;
+
+pub struct TransientObject<'a, T>
+where
+    T: T1Proto,
+{
+    se050: &'a mut Se050<T>,
+    delay: &'a mut DelayWrapper,
+    objectidentifier: [u8; 4],
+    armed: bool,
+}
+
+impl<'a, T> TransientObject<'a, T>
+where
+    T: T1Proto,
+{
+    // takes over an object that already exists on the device
+    pub fn new(se050: &'a mut Se050<T>, delay: &'a mut DelayWrapper, objectidentifier: [u8; 4]) -> Self {
+        Self { se050, delay, objectidentifier, armed: true }
+    }
+
+    pub fn id(&self) -> &[u8; 4] {
+        &self.objectidentifier
+    }
+
+    // device and delay for operations on the guarded object
+    pub fn parts(&mut self) -> (&mut Se050<T>, &mut DelayWrapper, &[u8; 4]) {
+        (self.se050, self.delay, &self.objectidentifier)
+    }
+
+    pub fn keep(mut self) -> [u8; 4] {
+        self.armed = false;
+        self.objectidentifier
+    }
+
+    pub fn delete(mut self) -> Result<(), Se050Error> {
+        self.armed = false;
+        self.se050.delete_secure_object(&self.objectidentifier, self.delay)
+    }
+}
+
+impl<'a, T> Drop for TransientObject<'a, T>
+where
+    T: T1Proto,
+{
+    fn drop(&mut self) {
+        if self.armed && self.se050.delete_secure_object(&self.objectidentifier, self.delay).is_err() {
+            error!("SE050 TransientObject: failed to delete {:?}", self.objectidentifier);
+        }
+    }
+}
+
+impl<T> Se050<T>
+where
+    T: T1Proto,
+{
+    //###########################################################################
+    // Signs a SHA-256 `digest` with a P-256 key pair generated for this signature
+    // alone: the key is created in transient memory under a free identifier from
+    // EPHEMERAL_OBJECT_ID_START on, used for ECDSASign, its public key is read,
+    // and the object is deleted again, also when a step fails.
+    // Returns the length of the ASN.1 signature written to `signature` and the
+    // public key (0x04 || X || Y) that verifies it.
+
+    #[inline(never)]
+    pub fn ephemeral_p256_sign(&mut self, digest: &[u8; 32], signature: &mut [u8], delay: &mut DelayWrapper) -> Result<(usize, [u8; 65]), Se050Error> {
+        let id = self.find_free_object_id(EPHEMERAL_OBJECT_ID_START, delay)?;
+        let config = KeyConfig { transient: true, ..Default::default() };
+        self.generate_ec_key_with_config(&id.0, Se050ECCurveconstants::NistP256, &config, delay)?;
+
+        let mut key = TransientObject::new(self, delay, id.0);
+        let (se050, delay, id) = key.parts();
+        let len = se050.ecdsa_sign(id, Se050ECSignatureAlgo::SigEcdsaSha256, digest, signature, delay)?;
+
+        let mut publickey = [0u8; 65];
+        if se050.read_object_into(id, &mut publickey, delay)? != publickey.len() {
+            error!("SE050 ephemeral_p256_sign: public key Length Mismatch");
+            return Err(Se050Error::UnknownError);
+        }
+        key.delete()?;
+
+        debug!("SE050 ephemeral_p256_sign OK");
+        Ok((len, publickey))
+    }
+}
@@ -1,5 +1,6 @@
 use core::convert::{From, Into, TryFrom};
 use embedded_hal::blocking::delay::DelayMs;
+use byteorder::{ByteOrder, BE, LE};
 
 // SE050 T1 mandates a single-byte LEN field, so IFS is strictly limited
 pub const MAX_IFSC: usize = 254;
@@ -10,20 +11,49 @@ pub const MAX_T1_FRAME_SIZE: usize = 3 + MAX_IFSC + 2;
 // 8 TLV payload objects should be enough for every request?
 pub const MAX_TLVS: usize = 8;
 
+// Monotonic millisecond clock, e.g. a timer peripheral or a SysTick counter.
+// Only differences between readings are used, so it may wrap around.
+pub trait Clock {
+    fn now_ms(&mut self) -> u32;
+}
+
 pub struct DelayWrapper {
     pub inner: &'static mut dyn DelayMs<u32>,
+    // Without a clock, timeouts count the milliseconds passed to `inner`, which
+    // leaves out the time spent on the bus; with one they are measured in wall time.
+    clock: Option<&'static mut dyn Clock>,
 }
 impl<T> From<&'static mut T> for DelayWrapper
 where
     T: DelayMs<u32>,
 {
     fn from(delay: &'static mut T) -> Self {
-        Self { inner: delay }
+        Self::new(delay)
+    }
+}
+
+impl DelayWrapper {
+    pub fn new(inner: &'static mut dyn DelayMs<u32>) -> Self {
+        Self { inner, clock: None }
+    }
+
+    pub fn with_clock(mut self, clock: &'static mut dyn Clock) -> Self {
+        self.clock = Some(clock);
+        self
+    }
+
+    pub fn has_clock(&self) -> bool {
+        self.clock.is_some()
+    }
+
+    pub fn now_ms(&mut self) -> Option<u32> {
+        self.clock.as_mut().map(|clock| clock.now_ms())
     }
 }
 
 //////////////////////////////////////////////////////////////////////////////
 
+#[derive(Debug, PartialEq, Eq)]
 pub enum Iso7816Error {
     ValueError,
 }
@@ -37,6 +67,11 @@ pub enum ApduClass {
     ProprietarySecure = 0b1000_0100,
 }
 
+// ISO 7816-4 interindustry instructions. Only SELECT and GET DATA (identification
+// data) are used: the Secure Objects of the SE050 IoT applet are not an ISO file
+// system, so the record instructions (ReadRecords, WriteRecord, AppendRecord)
+// have no record-structured file to act on. Log-style data goes into a binary
+// object, written at increasing offsets.
 #[derive(Copy, Clone)]
 #[allow(dead_code)]
 #[repr(u8)]
@@ -66,25 +101,33 @@ pub enum ApduStandardInstruction {
 #[derive(Debug)]
 pub struct SimpleTlv<'a> {
     tag: u8,
-    header: heapless::Vec<u8, 3>,
+    header: heapless::Vec<u8, 4>,
     data: &'a [u8],
 }
 
 impl<'a> SimpleTlv<'a> {
+    /// Panics if `data` is longer than 65535 bytes; see `try_new`.
     pub fn new(tag: u8, data: &'a [u8]) -> Self {
+        Self::try_new(tag, data).unwrap()
+    }
+
+    pub fn try_new(tag: u8, data: &'a [u8]) -> Result<Self, Iso7816Error> {
         let header = if data.len() < 128 {
-            heapless::Vec::from_slice(&[tag, data.len() as u8]).unwrap()
-        } else { 
-            heapless::Vec::from_slice(&[tag, 0x82, (data.len() >> 8) as u8, data.len() as u8]).unwrap()
+            heapless::Vec::from_slice(&[tag, data.len() as u8])
+        } else if data.len() <= 0xffff {
+            heapless::Vec::from_slice(&[tag, 0x82, (data.len() >> 8) as u8, data.len() as u8])
+        } else {
+            return Err(Iso7816Error::ValueError);
         };
-        Self { tag, header, data }
+        let header = header.map_err(|_| Iso7816Error::ValueError)?;
+        Ok(Self { tag, header, data })
     }
 
     pub fn total_len(&self) -> usize {
         self.header.len() + self.data.len()
     }
 
-    pub fn get_header(&self) -> &heapless::Vec<u8, 3> {
+    pub fn get_header(&self) -> &heapless::Vec<u8, 4> {
         &self.header
     }
 
@@ -93,6 +136,38 @@ impl<'a> SimpleTlv<'a> {
     }
 }
 
+// A TLV whose value is itself a sequence of TLVs, e.g. for policies or curve
+// parameters. The children are serialized into the value as they are pushed;
+// as_tlv() then gives a SimpleTlv borrowing it for CApdu::push, so nesting goes
+// to any depth by pushing one NestedTlv into another. N bounds the value length.
+pub struct NestedTlv<const N: usize> {
+    tag: u8,
+    value: heapless::Vec<u8, N>,
+}
+
+impl<const N: usize> NestedTlv<N> {
+    pub fn new(tag: u8) -> Self {
+        Self { tag, value: heapless::Vec::new() }
+    }
+
+    // All or nothing: a child that does not fit leaves the value untouched
+    pub fn push(&mut self, tlv: &SimpleTlv) -> Result<(), Iso7816Error> {
+        if tlv.get_header().len() + tlv.get_data().len() > N - self.value.len() {
+            return Err(Iso7816Error::ValueError);
+        }
+        self.value.extend_from_slice(tlv.get_header()).map_err(|_| Iso7816Error::ValueError)?;
+        self.value.extend_from_slice(tlv.get_data()).map_err(|_| Iso7816Error::ValueError)
+    }
+
+    pub fn push_nested<const M: usize>(&mut self, tlv: &NestedTlv<M>) -> Result<(), Iso7816Error> {
+        self.push(&tlv.as_tlv()?)
+    }
+
+    pub fn as_tlv(&self) -> Result<SimpleTlv<'_>, Iso7816Error> {
+        SimpleTlv::try_new(self.tag, &self.value)
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////
 
 pub struct RawRApdu<'a> {
@@ -100,12 +175,105 @@ pub struct RawRApdu<'a> {
     pub sw: u16,
 }
 
+impl<'a> RawRApdu<'a> {
+    // Parses the response TLVs lazily, without the MAX_TLVS bound of RApdu.
+    pub fn tlv_iter(&self) -> RawTlvIterator<'a> {
+        RawTlvIterator { data: self.data, done: false }
+    }
+
+    // The RApdu receive_apdu returns: at most MAX_TLVS TLVs, more fail with
+    // TlvParseError unless `truncate_tlvs` drops them (see RApdu::dropped_tlvs).
+    pub fn parse_tlvs(&self, truncate_tlvs: bool) -> Result<RApdu<'a>, T1Error> {
+        let mut tlvs = heapless::Vec::new();
+        let mut dropped_tlvs = 0;
+        let mut iter = self.tlv_iter();
+        let mut trailing: &[u8] = &[];
+        for tlv in iter.by_ref() {
+            // bytes that do not form a TLV end the list: some applet versions
+            // append indicator bytes after the TLVs, just before the SW
+            let Ok((tag, data)) = tlv else {
+                trailing = iter.remaining();
+                debug!("T1 response: {} trailing bytes after TLVs", trailing.len());
+                break;
+            };
+            if tlvs.push(SimpleTlv::new(tag, data)).is_err() {
+                if !truncate_tlvs {
+                    return Err(T1Error::TlvParseError);
+                }
+                dropped_tlvs += 1;
+            }
+        }
+        // the caller opted in to truncation, so this is no error
+        if dropped_tlvs > 0 {
+            warn!("T1 response truncated, {} TLVs dropped", dropped_tlvs);
+        }
+
+        Ok(RApdu { sw: self.sw, tlvs, dropped_tlvs, trailing })
+    }
+}
+
+// Yields (tag, value) pairs; a malformed TLV is reported as a final Err item.
+pub struct RawTlvIterator<'a> {
+    data: &'a [u8],
+    done: bool,
+}
+
+impl<'a> RawTlvIterator<'a> {
+    // the bytes not yet parsed
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+
+    fn parse_next(&mut self) -> Result<(u8, &'a [u8]), T1Error> {
+        let data = self.data;
+        if data.len() < 2 { return Err(T1Error::TlvParseError); }
+        let (len, off) = match data[1] {
+            l if l < 0x80 => (l as usize, 2),
+            0x81 => {
+                if data.len() < 3 { return Err(T1Error::TlvParseError); }
+                (data[2] as usize, 3)
+            },
+            0x82 => {
+                if data.len() < 4 { return Err(T1Error::TlvParseError); }
+                (BE::read_u16(&data[2..4]) as usize, 4)
+            },
+            _ => { return Err(T1Error::TlvParseError); }
+        };
+        if off + len > data.len() { return Err(T1Error::TlvParseError); }
+        self.data = &data[off + len..];
+        Ok((data[0], &data[off..off + len]))
+    }
+}
+
+impl<'a> Iterator for RawTlvIterator<'a> {
+    type Item = Result<(u8, &'a [u8]), T1Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done || self.data.is_empty() {
+            return None;
+        }
+        let item = self.parse_next();
+        if item.is_err() {
+            self.done = true;
+        }
+        Some(item)
+    }
+}
+
 pub struct RApdu<'a> {
     pub tlvs: heapless::Vec<SimpleTlv<'a>, MAX_TLVS>,
     pub sw: u16,
+    // TLVs beyond MAX_TLVS, only non-zero with T1overI2C::set_truncate_tlvs
+    pub dropped_tlvs: usize,
+    // bytes after the last well-formed TLV (e.g. an indicator byte), not parsed
+    pub trailing: &'a [u8],
 }
 
 impl<'a> RApdu<'a> {
+    pub fn is_truncated(&self) -> bool {
+        self.dropped_tlvs > 0
+    }
+
     pub fn get_tlv(&self, tag: u8) -> Option<&SimpleTlv<'a>> {
         for tlv in self.tlvs.iter() {
             if tlv.tag == tag {
@@ -124,6 +292,7 @@ pub struct RawCApdu<'a> {
     pub p1: u8,
     pub p2: u8,
     pub data: &'a [u8],
+    // see CApdu::le
     pub le: Option<usize>,
 }
 
@@ -142,6 +311,11 @@ impl<'a> RawCApdu<'a> {
     pub fn byte_iter(&self) -> CApduByteIterator<'_> {
         CApduByteIterator::from_capdu_raw(self)
     }
+
+    // see CApdu::serialized_len
+    pub fn serialized_len(&self) -> usize {
+        capdu_len(self.data.len(), self.le)
+    }
 }
 
 pub struct CApdu<'a> {
@@ -151,6 +325,10 @@ pub struct CApdu<'a> {
     pub p2: u8,
     tlvs: heapless::Vec<SimpleTlv<'a>, MAX_TLVS>,
     payload_len: usize,
+    // Some(0): Le byte 0x00, the card may return up to 256 bytes (65536 if extended);
+    // Some(n): up to n bytes (256 is the short Le 0x00); None: no Le, for commands
+    // that return only a status word.
+    // A card answering a command sent without Le withholds its response data.
     pub le: Option<usize>,
 }
 
@@ -175,32 +353,62 @@ impl<'a> CApdu<'a> {
     pub fn byte_iter(&self) -> CApduByteIterator<'_> {
         CApduByteIterator::from_capdu(self)
     }
+
+    // number of bytes byte_iter() yields: header, Lc, TLVs and Le
+    pub fn serialized_len(&self) -> usize {
+        capdu_len(self.payload_len, self.le)
+    }
+}
+
+// a short Le of 0x00 stands for 256
+fn capdu_is_extended(lc: usize, le: Option<usize>) -> bool {
+    lc > 255 || le.is_some_and(|le| le > 256)
+}
+
+fn capdu_len(lc: usize, le: Option<usize>) -> usize {
+    let is_extended = capdu_is_extended(lc, le);
+    let lc_len = match lc {
+        0 => 0,
+        _ if is_extended => 3,
+        _ => 1,
+    };
+    let le_len = match le {
+        None => 0,
+        Some(_) if is_extended && lc > 0 => 2,
+        Some(_) if is_extended => 3,
+        Some(_) => 1,
+    };
+    4 + lc_len + lc + le_len
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CApduByteIteratorState {
+    Header,
+    Body(usize),	// index into body slices
+    Trailer,
+    Done,
 }
 
 pub struct CApduByteIterator<'a> {
-    // capdu: &'a CApdu<'a>,
     capdu_header: heapless::Vec<u8, 7>,
-    body: heapless::Deque<&'a [u8], {2*MAX_TLVS}>,
+    body: heapless::Vec<&'a [u8], {2*MAX_TLVS}>,
     capdu_trailer: heapless::Vec<u8, 3>,
-    area: usize,
+    state: CApduByteIteratorState,
     off: usize,
 }
 
 impl<'a> CApduByteIterator<'a> {
     fn from_capdu_common(cla: ApduClass, ins: u8, p1: u8, p2: u8, lc: usize, le: Option<usize>) -> Self {
-        let is_extended = lc > 255 || le.map_or(false, |le| le > 255);
+        let is_extended = capdu_is_extended(lc, le);
 
         let mut obj = Self {
-            // capdu: capdu,
             capdu_header: heapless::Vec::from_slice(&[cla.into(), ins, p1, p2]).unwrap(),
-            body: heapless::Deque::new(),
+            body: heapless::Vec::new(),
             capdu_trailer: heapless::Vec::new(),
-            area: 0,
+            state: CApduByteIteratorState::Header,
             off: 0
         };
 
-
-
         if lc > 0 {
             if is_extended {
                 obj.capdu_header.extend_from_slice(&[0x00, (lc >> 8) as u8, lc as u8]).unwrap();
@@ -209,7 +417,11 @@ impl<'a> CApduByteIterator<'a> {
             }
         }
         if let Some(le) = le {
-            if is_extended {
+            // ISO7816-4 5.1: extended Le is two bytes after an extended Lc,
+            // three (with a leading 0x00) when there is no command data
+            if is_extended && lc > 0 {
+                obj.capdu_trailer.extend_from_slice(&[(le >> 8) as u8, le as u8]).unwrap();
+            } else if is_extended {
                 obj.capdu_trailer.extend_from_slice(&[0x00, (le >> 8) as u8, le as u8]).unwrap();
             } else {
                 obj.capdu_trailer.push(le as u8).unwrap();
@@ -223,8 +435,8 @@ impl<'a> CApduByteIterator<'a> {
         let mut obj = Self::from_capdu_common(capdu.cla, capdu.ins, capdu.p1, capdu.p2, capdu.payload_len, capdu.le);
 
         for tlv in &capdu.tlvs {
-            obj.body.push_back(tlv.header.as_slice()).unwrap();
-            obj.body.push_back(tlv.data).unwrap();
+            obj.body.push(tlv.header.as_slice()).unwrap();
+            obj.body.push(tlv.data).unwrap();
         }
 
         obj
@@ -234,54 +446,43 @@ impl<'a> CApduByteIterator<'a> {
         let mut obj = Self::from_capdu_common(capdu.cla, capdu.ins, capdu.p1, capdu.p2, capdu.data.len(), capdu.le);
 
         if !capdu.data.is_empty() {
-            obj.body.push_back(capdu.data).unwrap();
+            obj.body.push(capdu.data).unwrap();
         }
 
         obj
     }
+
+    fn next_state(&self) -> CApduByteIteratorState {
+        match self.state {
+        CApduByteIteratorState::Header if self.body.is_empty() => CApduByteIteratorState::Trailer,
+        CApduByteIteratorState::Header => CApduByteIteratorState::Body(0),
+        CApduByteIteratorState::Body(idx) if idx + 1 < self.body.len() => CApduByteIteratorState::Body(idx + 1),
+        CApduByteIteratorState::Body(_) => CApduByteIteratorState::Trailer,
+        CApduByteIteratorState::Trailer | CApduByteIteratorState::Done => CApduByteIteratorState::Done,
+        }
+    }
 }
 
 impl<'a> Iterator for CApduByteIterator<'a> {
     type Item = u8;
 
     fn next(&mut self) -> Option<u8> {
-        match self.area {
-        0 => {
-            let ret = self.capdu_header[self.off];
-            self.off += 1;
-            if self.off == self.capdu_header.len() {
-                self.area = 1;
-                self.off = 0;
-            }
-            Some(ret)
-        },
-        1 => {
-            let curr = self.body.front().unwrap();
-            let ret = curr[self.off];
-            self.off += 1;
-            if self.off >= curr.len() {
-                self.off = 0;
-                self.body.pop_front();
-                if self.body.front().is_none() {
-                    self.area = 2;
-                }
-            }
-            Some(ret)
-        },
-        2 => {
-            if self.capdu_trailer.len() == 0 {
-                None
-            } else {
-                let ret = self.capdu_trailer[self.off];
+        // sections may be empty (e.g. no Le, zero-length TLV data), so skip
+        // forward until a byte is found or all sections are exhausted
+        loop {
+            let section: &[u8] = match self.state {
+            CApduByteIteratorState::Header => self.capdu_header.as_slice(),
+            CApduByteIteratorState::Body(idx) => self.body[idx],
+            CApduByteIteratorState::Trailer => self.capdu_trailer.as_slice(),
+            CApduByteIteratorState::Done => { return None; }
+            };
+            if self.off < section.len() {
+                let ret = section[self.off];
                 self.off += 1;
-                if self.off == self.capdu_trailer.len() {
-                    self.area = 3;
-                    self.off = 0;
-                }
-                Some(ret)
+                return Some(ret);
             }
-        },
-        _ => { None }
+            self.off = 0;
+            self.state = self.next_state();
         }
     }
 }
@@ -301,6 +502,37 @@ pub struct T1Header {
     pub crc: u16,
 }
 
+// Epilogue field of a T=1 block: CRC16 (2 bytes, LE) or LRC (1 byte, XOR of the block)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum T1ErrorDetection {
+    Crc,
+    Lrc,
+}
+
+impl T1ErrorDetection {
+    pub fn epilogue_len(&self) -> usize {
+        match self {
+        T1ErrorDetection::Crc => 2,
+        T1ErrorDetection::Lrc => 1,
+        }
+    }
+
+    pub fn calculate(&self, data: &[u8]) -> u16 {
+        match self {
+        T1ErrorDetection::Crc => Se050CRC::calculate(data),
+        T1ErrorDetection::Lrc => data.iter().fold(0u8, |lrc, b| lrc ^ b) as u16,
+        }
+    }
+
+    // reads the epilogue as sent on the wire
+    pub fn read(&self, epilogue: &[u8]) -> u16 {
+        match self {
+        T1ErrorDetection::Crc => LE::read_u16(&epilogue[0..2]),
+        T1ErrorDetection::Lrc => epilogue[0] as u16,
+        }
+    }
+}
+
 #[derive(PartialEq, Eq)]
 pub enum T1PCB {
     I(u8, bool),		// seq, multi
@@ -329,7 +561,7 @@ impl core::convert::From<T1PCB> for u8 {
     fn from(value: T1PCB) -> u8 {
         match value {
         T1PCB::I(seq, multi) => (seq << 6) | { if multi { 0x20 } else { 0 }},
-        T1PCB::R(seq, err) => T1_R_CODE | (seq << 5) | err,
+        T1PCB::R(seq, err) => T1_R_CODE | (seq << 4) | err,
         T1PCB::S(code, false) => T1_S_REQUEST_CODE | <T1SCode as Into<u8>>::into(code),
         T1PCB::S(code, true) => T1_S_RESPONSE_CODE | <T1SCode as Into<u8>>::into(code),
         }
@@ -349,17 +581,53 @@ pub enum T1SCode {
     InterfaceSoftReset = 15,
 }
 
+// Cause of a failed I2C transfer, named after embedded-hal 1.0 i2c::ErrorKind.
+// embedded-hal 0.2 errors are opaque, so T1overI2C only knows the cause once
+// set_i2c_error_kinds() tells it how to read the HAL's error; until then every
+// failure is Other.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum I2cErrorKind {
+    // nobody acknowledged the address: the SE050 is busy (or absent, or miswired)
+    AddressNack,
+    // a data byte was not acknowledged
+    DataNack,
+    // another master took the bus
+    ArbitrationLoss,
+    // misplaced START/STOP, a bus fault
+    Bus,
+    Overrun,
+    Other,
+}
+
 #[derive(Debug, PartialEq, Eq)]
 pub enum T1Error {
-    TransmitError,
-    ReceiveError,
+    // I2C write failed, with the cause of the last attempt
+    TransmitError(I2cErrorKind),
+    // I2C read failed, with the cause of the last attempt
+    ReceiveError(I2cErrorKind),
     BufferOverrunError(usize),
     ChecksumError,
     ProtocolError,
     RCodeReceived(u8),
     TlvParseError,
+    Timeout,
 }
 
+// Transport between Se050 and the secure element. T1overI2C implements it for
+// GP T=1 over I2C; another physical layer (e.g. T=1 over SPI for the SE051)
+// only has to implement these five methods, Se050 relies on nothing else:
+// - Commands and responses strictly alternate: Se050 follows every send_apdu or
+//   send_apdu_raw with exactly one receive_apdu or receive_apdu_raw.
+// - send_*: deliver the whole APDU (CApdu::byte_iter gives its encoding, with
+//   serialized_len bytes), doing any framing and chaining of the link layer.
+// - receive_apdu_raw: read one complete response APDU into `buf`, waiting out
+//   any busy indication (T=1 WTX) of the card, and split off the SW1-SW2
+//   trailer. A status word other than 0x9000 is not a transport error.
+// - receive_apdu: as receive_apdu_raw followed by RawRApdu::parse_tlvs.
+// - interface_soft_reset: bring the link to a known state and return the
+//   card's ATR (see AnswerToReset::parse). Se050::enable calls it first.
+// Errors are T1Error; Se050 reports them as Se050Error::UnknownError or
+// Se050Error::T1Error and expects recover() to reset the link afterwards.
 pub trait T1Proto {
     fn send_apdu(&mut self, apdu: &CApdu, delay: &mut DelayWrapper) -> Result<(), T1Error>;
     fn send_apdu_raw(&mut self, apdu: &RawCApdu, delay: &mut DelayWrapper) -> Result<(), T1Error>;
@@ -390,6 +658,57 @@ pub struct AnswerToReset {
     pub historical_bytes: [u8; 15],
 }
 
+// GP T=1 over I2C ATR layout: PVER(1) VID(5) DLLP(len + 4) PLP(type + len + 11) HB(len + n).
+// Every length is checked before use; a truncated or malformed ATR yields
+// ProtocolError rather than a panic, a misbehaving or absent card included.
+impl AnswerToReset {
+    pub fn parse(atr: &[u8]) -> Result<Self, T1Error> {
+        if atr.len() < 7 {
+            return Err(T1Error::ProtocolError);
+        }
+        let protocol_version = atr[0];
+        let mut vendor_id = [0u8; 5];
+        vendor_id.copy_from_slice(&atr[1..6]);
+
+        let dllp_len = atr[6] as usize;
+        let dllp_data = atr.get(7..7 + dllp_len).ok_or(T1Error::ProtocolError)?;
+        if dllp_len != 4 {
+            return Err(T1Error::ProtocolError);
+        }
+        let dllp = DataLinkLayerParameters {
+            bwt_ms: BE::read_u16(&dllp_data[0..2]),
+            ifsc: BE::read_u16(&dllp_data[2..4]),
+        };
+
+        let off = 7 + dllp_len;
+        let plp_hdr = atr.get(off..off + 2).ok_or(T1Error::ProtocolError)?;
+        let (plp_type, plp_len) = (plp_hdr[0], plp_hdr[1] as usize);
+        let plp_data = atr.get(off + 2..off + 2 + plp_len).ok_or(T1Error::ProtocolError)?;
+        if plp_type != 2 /* I2C */ || plp_len != 11 {
+            return Err(T1Error::ProtocolError);
+        }
+        let mut rfu = [0u8; 3];
+        rfu.copy_from_slice(&plp_data[4..7]);
+        let plp = PhysicalLayerParameters::I2C(I2CParameters {
+            mcf: BE::read_u16(&plp_data[0..2]),
+            configuration: plp_data[2],
+            mpot_ms: plp_data[3],
+            rfu,
+            segt_us: BE::read_u16(&plp_data[7..9]),
+            wut_us: BE::read_u16(&plp_data[9..11]),
+        });
+
+        let off = off + 2 + plp_len;
+        let hb_len = *atr.get(off).ok_or(T1Error::ProtocolError)? as usize;
+        let hb = atr.get(off + 1..off + 1 + hb_len).ok_or(T1Error::ProtocolError)?;
+        let mut historical_bytes = [0u8; 15];
+        let n = hb.len().min(historical_bytes.len());
+        historical_bytes[0..n].copy_from_slice(&hb[0..n]);
+
+        Ok(AnswerToReset { protocol_version, vendor_id, dllp, plp, historical_bytes })
+    }
+}
+
 #[derive(Debug)]
 pub struct DataLinkLayerParameters {
     pub bwt_ms: u16,
@@ -411,12 +730,65 @@ pub struct I2CParameters {
     pub wut_us: u16,
 }
 
+// GP T=1 over I2C, I2C Physical Layer Parameters (PLID 2).
+// AN12413 does not assign the Configuration bits; they are exposed individually so
+// callers can match on what their part reports (e.g. 0x08 on SE050).
+impl I2CParameters {
+    // Maximum Clock Frequency
+    pub fn max_clock_khz(&self) -> u16 {
+        self.mcf
+    }
+
+    // Minimum Polling Time: wait at least this long between polls of a busy card
+    pub fn min_polling_time_ms(&self) -> u8 {
+        self.mpot_ms
+    }
+
+    // Secure Element Guard Time: minimum gap between two I2C transactions
+    pub fn guard_time_us(&self) -> u16 {
+        self.segt_us
+    }
+
+    // Wake-Up Time after power-up or a deep power-down
+    pub fn wake_up_time_us(&self) -> u16 {
+        self.wut_us
+    }
+
+    // bit 0..=7 of the Configuration byte
+    pub fn configuration_bit(&self, bit: u8) -> bool {
+        bit < 8 && self.configuration & (1 << bit) != 0
+    }
+
+    // set RFU bytes indicate a newer profile than the driver knows about
+    pub fn has_rfu_bits(&self) -> bool {
+        self.rfu != [0; 3]
+    }
+}
+
 //////////////////////////////////////////////////////////////////////////////
 
 pub type Se050CRC = crc16::State<crc16::X_25>;
 
+// Checks the trailing CRC16 (LE) of a complete T=1 frame (prologue, INF, CRC)
+#[allow(dead_code)]
+pub fn verify_frame_crc(frame: &[u8]) -> Result<(), T1Error> {
+    if frame.len() < 2 {
+        return Err(T1Error::ChecksumError);
+    }
+    let (body, crc) = frame.split_at(frame.len() - 2);
+    if Se050CRC::calculate(body) != LE::read_u16(crc) {
+        return Err(T1Error::ChecksumError);
+    }
+    Ok(())
+}
+
 //////////////////////////////////////////////////////////////////////////////
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub struct ObjectId(pub [u8; 4]);
 
+// identifier returned by CreateSession
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SessionId(pub [u8; 8]);
+
 include!("types_convs.rs");